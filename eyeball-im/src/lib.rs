@@ -6,6 +6,16 @@
 #![warn(missing_debug_implementations, missing_docs)]
 #![allow(clippy::new_without_default)]
 
+mod map;
+mod set;
 mod vector;
 
-pub use vector::{ObservableVector, VectorDiff, VectorSubscriber};
+pub use map::{
+    BatchedMapSubscriber, MapDiff, MapKeySubscriber, MapSubscriber, ObservableMap,
+    ObservableMapEntry, ObservableMapOccupiedEntry, ObservableMapVacantEntry,
+};
+pub use set::{BatchedSetSubscriber, ObservableSet, SetDiff, SetSubscriber};
+pub use vector::{
+    BatchedVectorSubscriber, FilteredVectorSubscriber, ObservableVector, SortedVectorSubscriber,
+    VectorDiff, VectorSubscriber, WindowedVectorSubscriber,
+};
@@ -0,0 +1,578 @@
+use std::{
+    borrow::Borrow,
+    collections::VecDeque,
+    fmt,
+    hash::Hash,
+    ops,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_core::Stream;
+use im::HashMap;
+use tokio::sync::broadcast::{self, Sender};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+
+/// A hash map that broadcasts any changes made to it.
+pub struct ObservableMap<K: Clone + Hash + Eq, V: Clone> {
+    values: HashMap<K, V>,
+    sender: Sender<BroadcastMessage<K, V>>,
+    /// The diffs of the ongoing [`transaction`][Self::transaction], if any.
+    pending: Option<Vec<MapDiff<K, V>>>,
+}
+
+impl<K, V> ObservableMap<K, V>
+where
+    K: Clone + Hash + Eq + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    /// Create a new `ObservableMap`.
+    ///
+    /// As of the time of writing, this is equivalent to
+    /// `ObservableMap::with_capacity(16)`, but the internal buffer capacity
+    /// is subject to change in non-breaking releases.
+    ///
+    /// See [`with_capacity`][Self::with_capacity] for details about the buffer
+    /// capacity.
+    pub fn new() -> Self {
+        Self::with_capacity(16)
+    }
+
+    /// Create a new `ObservableMap` with the given capacity for the inner
+    /// buffer.
+    ///
+    /// Up to `capacity` updates that have not been received by all of the
+    /// subscribers yet will be retained in the inner buffer. If an update
+    /// happens while the buffer is at capacity, the oldest update is discarded
+    /// from it and all subscribers that have not yet received it will instead
+    /// see [`MapDiff::Reset`] as the next update.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is `0`, or larger than `usize::MAX / 2`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { values: HashMap::new(), sender, pending: None }
+    }
+
+    /// Turn the `ObservableMap` back into a regular `HashMap`.
+    pub fn into_inner(self) -> HashMap<K, V> {
+        self.values
+    }
+
+    /// Get the number of entries in the `HashMap`.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if the `HashMap` contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Get a reference to the value for the given `key`, without cloning the
+    /// `HashMap`.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.values.get(key)
+    }
+
+    /// Returns `true` if the `HashMap` contains an entry for the given `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.values.contains_key(key)
+    }
+
+    /// Obtain a new subscriber.
+    ///
+    /// The first item the subscriber yields is always a [`MapDiff::Reset`]
+    /// with a snapshot of the entries at the time of subscribing.
+    pub fn subscribe(&self) -> MapSubscriber<K, V> {
+        MapSubscriber::new(self.subscribe_batched())
+    }
+
+    /// Obtain a new subscriber that yields all diffs of a
+    /// [`transaction`][Self::transaction] as a single item.
+    ///
+    /// See [`ObservableVector::subscribe_batched`][crate::ObservableVector::subscribe_batched].
+    pub fn subscribe_batched(&self) -> BatchedMapSubscriber<K, V> {
+        let stream = BroadcastStream::new(self.sender.subscribe());
+        BatchedMapSubscriber::new(stream, self.values.clone())
+    }
+
+    /// Perform multiple mutations and notify subscribers of all of them at
+    /// once.
+    ///
+    /// See [`ObservableVector::transaction`][crate::ObservableVector::transaction].
+    pub fn transaction<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        if self.pending.is_some() {
+            return f(self);
+        }
+        self.pending = Some(Vec::new());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(self)));
+        let diffs = self.pending.take().unwrap_or_default();
+        self.broadcast_diffs(diffs);
+        result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+    }
+
+    /// Obtain a new subscriber for the value of the given `key`.
+    ///
+    /// The first item the subscriber yields is the value at the time of
+    /// subscribing, or `None` if there is no entry for `key`. After that, it
+    /// only yields items when the entry for `key` is inserted, updated or
+    /// removed.
+    pub fn subscribe_to_key(&self, key: &K) -> MapKeySubscriber<K, V> {
+        MapKeySubscriber { inner: self.subscribe(), key: key.clone(), present: None }
+    }
+
+    /// Insert a key-value pair, notify subscribers and return the previous
+    /// value for that key, if any.
+    ///
+    /// Subscribers see a [`MapDiff::Insert`] if the key was not present
+    /// before, and a [`MapDiff::Update`] otherwise.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let old_value = self.values.insert(key.clone(), value.clone());
+        let diff = match old_value {
+            Some(_) => MapDiff::Update { key, value },
+            None => MapDiff::Insert { key, value },
+        };
+        self.broadcast_diff(diff);
+        old_value
+    }
+
+    /// Remove the entry for the given key, notify subscribers and return its
+    /// value.
+    ///
+    /// If there is no entry for the key, subscribers will not be notified and
+    /// this method will return `None`.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let value = self.values.remove(key);
+        if value.is_some() {
+            self.broadcast_diff(MapDiff::Remove { key: key.clone() });
+        }
+        value
+    }
+
+    /// Clear out all of the entries in this map and notify subscribers.
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.broadcast_diff(MapDiff::Clear);
+    }
+
+    /// Get the entry for the given key for in-place manipulation.
+    pub fn entry(&mut self, key: K) -> ObservableMapEntry<'_, K, V> {
+        if self.values.contains_key(&key) {
+            ObservableMapEntry::Occupied(ObservableMapOccupiedEntry { map: self, key })
+        } else {
+            ObservableMapEntry::Vacant(ObservableMapVacantEntry { map: self, key })
+        }
+    }
+
+    fn broadcast_diff(&mut self, diff: MapDiff<K, V>) {
+        match &mut self.pending {
+            Some(pending) => pending.push(diff),
+            None => self.broadcast_diffs(vec![diff]),
+        }
+    }
+
+    fn broadcast_diffs(&self, diffs: Vec<MapDiff<K, V>>) {
+        if !diffs.is_empty() && self.sender.receiver_count() != 0 {
+            let msg = BroadcastMessage { diffs, state: self.values.clone() };
+            let _num_receivers = self.sender.send(msg).unwrap_or(0);
+            #[cfg(feature = "tracing")]
+            tracing::debug!("New observable value broadcast to {_num_receivers} receivers");
+        }
+    }
+}
+
+impl<K, V> Default for ObservableMap<K, V>
+where
+    K: Clone + Hash + Eq + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> fmt::Debug for ObservableMap<K, V>
+where
+    K: Clone + Hash + Eq + fmt::Debug,
+    V: Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservableMap").field("values", &self.values).finish_non_exhaustive()
+    }
+}
+
+// Note: No DerefMut because all mutating must go through inherent methods that
+// notify subscribers
+impl<K: Clone + Hash + Eq, V: Clone> ops::Deref for ObservableMap<K, V> {
+    type Target = HashMap<K, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.values
+    }
+}
+
+/// A view into a single entry of an [`ObservableMap`].
+///
+/// Created by [`ObservableMap::entry`].
+pub enum ObservableMapEntry<'a, K: Clone + Hash + Eq, V: Clone> {
+    /// An entry for a key that is present in the map.
+    Occupied(ObservableMapOccupiedEntry<'a, K, V>),
+    /// An entry for a key that is not present in the map.
+    Vacant(ObservableMapVacantEntry<'a, K, V>),
+}
+
+impl<'a, K, V> ObservableMapEntry<'a, K, V>
+where
+    K: Clone + Hash + Eq + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    /// Get the key of this entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Self::Occupied(entry) => entry.key(),
+            Self::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Insert `default` if the entry is vacant, notifying subscribers, and
+    /// return a reference to the value.
+    pub fn or_insert(self, default: V) -> &'a V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Insert the result of `default` if the entry is vacant, notifying
+    /// subscribers, and return a reference to the value.
+    pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'a V {
+        match self {
+            Self::Occupied(entry) => entry.into_ref(),
+            Self::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Update the value in place if the entry is occupied, notifying
+    /// subscribers.
+    pub fn and_modify(self, f: impl FnOnce(&mut V)) -> Self {
+        match self {
+            Self::Occupied(mut entry) => {
+                entry.update(f);
+                Self::Occupied(entry)
+            }
+            Self::Vacant(entry) => Self::Vacant(entry),
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for ObservableMapEntry<'_, K, V>
+where
+    K: Clone + Hash + Eq + fmt::Debug,
+    V: Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Occupied(entry) => f.debug_tuple("Occupied").field(entry).finish(),
+            Self::Vacant(entry) => f.debug_tuple("Vacant").field(entry).finish(),
+        }
+    }
+}
+
+/// An entry for a key that is present in an [`ObservableMap`].
+pub struct ObservableMapOccupiedEntry<'a, K: Clone + Hash + Eq, V: Clone> {
+    map: &'a mut ObservableMap<K, V>,
+    key: K,
+}
+
+impl<'a, K, V> ObservableMapOccupiedEntry<'a, K, V>
+where
+    K: Clone + Hash + Eq + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    /// Get the key of this entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Get a reference to the value of this entry.
+    pub fn get(&self) -> &V {
+        &self.map.values[&self.key]
+    }
+
+    /// Replace the value of this entry, notify subscribers and return the
+    /// previous value.
+    pub fn insert(&mut self, value: V) -> V {
+        let old_value = self.map.values.insert(self.key.clone(), value.clone());
+        self.map.broadcast_diff(MapDiff::Update { key: self.key.clone(), value });
+        old_value.expect("occupied entry must have a value")
+    }
+
+    /// Update the value of this entry in place and notify subscribers.
+    pub fn update(&mut self, f: impl FnOnce(&mut V)) {
+        let value = self.map.values.get_mut(&self.key).expect("occupied entry must have a value");
+        f(value);
+        let value = value.clone();
+        self.map.broadcast_diff(MapDiff::Update { key: self.key.clone(), value });
+    }
+
+    /// Remove this entry from the map, notify subscribers and return its value.
+    pub fn remove(self) -> V {
+        let value = self.map.values.remove(&self.key).expect("occupied entry must have a value");
+        self.map.broadcast_diff(MapDiff::Remove { key: self.key });
+        value
+    }
+
+    fn into_ref(self) -> &'a V {
+        &self.map.values[&self.key]
+    }
+}
+
+impl<K, V> fmt::Debug for ObservableMapOccupiedEntry<'_, K, V>
+where
+    K: Clone + Hash + Eq + fmt::Debug,
+    V: Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservableMapOccupiedEntry")
+            .field("key", &self.key)
+            .field("value", &self.map.values[&self.key])
+            .finish()
+    }
+}
+
+/// An entry for a key that is not present in an [`ObservableMap`].
+pub struct ObservableMapVacantEntry<'a, K: Clone + Hash + Eq, V: Clone> {
+    map: &'a mut ObservableMap<K, V>,
+    key: K,
+}
+
+impl<'a, K, V> ObservableMapVacantEntry<'a, K, V>
+where
+    K: Clone + Hash + Eq + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    /// Get the key of this entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Insert a value for this entry's key, notify subscribers and return a
+    /// reference to the value.
+    pub fn insert(self, value: V) -> &'a V {
+        self.map.values.insert(self.key.clone(), value.clone());
+        self.map.broadcast_diff(MapDiff::Insert { key: self.key.clone(), value });
+        &self.map.values[&self.key]
+    }
+}
+
+impl<K, V> fmt::Debug for ObservableMapVacantEntry<'_, K, V>
+where
+    K: Clone + Hash + Eq + fmt::Debug,
+    V: Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservableMapVacantEntry").field("key", &self.key).finish()
+    }
+}
+
+#[derive(Clone)]
+struct BroadcastMessage<K: Clone + Hash + Eq, V: Clone> {
+    diffs: Vec<MapDiff<K, V>>,
+    state: HashMap<K, V>,
+}
+
+/// A subscriber for updates of an [`ObservableMap`].
+///
+/// Use its [`Stream`] implementation to interact with it (futures-util and
+/// other futures-related crates have extension traits with convenience
+/// methods).
+#[derive(Debug)]
+pub struct MapSubscriber<K: Clone + Hash + Eq, V: Clone> {
+    inner: BatchedMapSubscriber<K, V>,
+    /// The remaining diffs of the last batch.
+    queued: VecDeque<MapDiff<K, V>>,
+}
+
+impl<K: Clone + Hash + Eq, V: Clone> MapSubscriber<K, V> {
+    const fn new(inner: BatchedMapSubscriber<K, V>) -> Self {
+        Self { inner, queued: VecDeque::new() }
+    }
+}
+
+// No structural pinning
+impl<K: Clone + Hash + Eq, V: Clone> Unpin for MapSubscriber<K, V> {}
+
+impl<K, V> Stream for MapSubscriber<K, V>
+where
+    K: Clone + Hash + Eq + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    type Item = MapDiff<K, V>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(diff) = self.queued.pop_front() {
+                return Poll::Ready(Some(diff));
+            }
+            let Some(diffs) = ready!(Pin::new(&mut self.inner).poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+            self.queued.extend(diffs);
+        }
+    }
+}
+
+/// A subscriber for batches of updates of an [`ObservableMap`].
+///
+/// Created by [`ObservableMap::subscribe_batched`].
+#[derive(Debug)]
+pub struct BatchedMapSubscriber<K: Clone + Hash + Eq, V: Clone> {
+    inner: BroadcastStream<BroadcastMessage<K, V>>,
+    initial: Option<HashMap<K, V>>,
+    must_reset: bool,
+}
+
+impl<K: Clone + Hash + Eq, V: Clone> BatchedMapSubscriber<K, V> {
+    const fn new(inner: BroadcastStream<BroadcastMessage<K, V>>, values: HashMap<K, V>) -> Self {
+        Self { inner, initial: Some(values), must_reset: false }
+    }
+}
+
+// No structural pinning, the snapshot is just moved out on the first poll.
+impl<K: Clone + Hash + Eq, V: Clone> Unpin for BatchedMapSubscriber<K, V> {}
+
+impl<K, V> Stream for BatchedMapSubscriber<K, V>
+where
+    K: Clone + Hash + Eq + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    type Item = Vec<MapDiff<K, V>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(values) = self.initial.take() {
+            return Poll::Ready(Some(vec![MapDiff::Reset { values }]));
+        }
+
+        loop {
+            let poll = match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(msg))) => {
+                    let diffs = if self.must_reset {
+                        self.must_reset = false;
+                        vec![MapDiff::Reset { values: msg.state }]
+                    } else {
+                        msg.diffs
+                    };
+                    Poll::Ready(Some(diffs))
+                }
+                Poll::Ready(None) => Poll::Ready(None),
+                Poll::Ready(Some(Err(BroadcastStreamRecvError::Lagged(_)))) => {
+                    self.must_reset = true;
+                    continue;
+                }
+                Poll::Pending => Poll::Pending,
+            };
+
+            return poll;
+        }
+    }
+}
+
+/// A subscriber for updates of the value of a single key of an
+/// [`ObservableMap`].
+///
+/// Created by [`ObservableMap::subscribe_to_key`].
+#[derive(Debug)]
+pub struct MapKeySubscriber<K: Clone + Hash + Eq, V: Clone> {
+    inner: MapSubscriber<K, V>,
+    key: K,
+    /// Whether the last yielded item was `Some(_)`, `None` before the first
+    /// one.
+    present: Option<bool>,
+}
+
+// No structural pinning
+impl<K: Clone + Hash + Eq, V: Clone> Unpin for MapKeySubscriber<K, V> {}
+
+impl<K, V> Stream for MapKeySubscriber<K, V>
+where
+    K: Clone + Hash + Eq + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    type Item = Option<V>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            let Some(diff) = ready!(Pin::new(&mut this.inner).poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+
+            let value = match diff {
+                MapDiff::Insert { key, value } | MapDiff::Update { key, value }
+                    if key == this.key =>
+                {
+                    Some(value)
+                }
+                MapDiff::Remove { key } if key == this.key => None,
+                MapDiff::Clear if this.present == Some(true) => None,
+                MapDiff::Reset { values } => {
+                    let value = values.get(&this.key).cloned();
+                    // Without `V: PartialEq`, it's unknown whether an existing
+                    // value changed, but a missing one can be skipped
+                    if value.is_none() && this.present == Some(false) {
+                        continue;
+                    }
+                    value
+                }
+                _ => continue,
+            };
+
+            this.present = Some(value.is_some());
+            return Poll::Ready(Some(value));
+        }
+    }
+}
+
+/// A change to an [`ObservableMap`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MapDiff<K: Clone + Hash + Eq, V: Clone> {
+    /// An entry was inserted for a key that was not present before.
+    Insert {
+        /// The key of the new entry.
+        key: K,
+        /// The value of the new entry.
+        value: V,
+    },
+    /// The value of an existing entry was replaced or modified.
+    Update {
+        /// The key of the entry.
+        key: K,
+        /// The new value.
+        value: V,
+    },
+    /// An entry was removed.
+    Remove {
+        /// The key of the removed entry.
+        key: K,
+    },
+    /// The map was cleared.
+    Clear,
+    /// A full snapshot of the map.
+    ///
+    /// This is the first item yielded by every subscriber, and is also sent
+    /// when the subscriber lagged too far behind and the next update that
+    /// should have been received has already been discarded from the internal
+    /// buffer.
+    Reset {
+        /// All of the entries.
+        values: HashMap<K, V>,
+    },
+}
@@ -0,0 +1,347 @@
+use std::{
+    borrow::Borrow,
+    collections::VecDeque,
+    fmt,
+    hash::Hash,
+    ops,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_core::Stream;
+use im::HashSet;
+use tokio::sync::broadcast::{self, Sender};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+
+/// A hash set that broadcasts any changes made to it.
+pub struct ObservableSet<T: Clone + Hash + Eq> {
+    values: HashSet<T>,
+    sender: Sender<BroadcastMessage<T>>,
+    /// The diffs of the ongoing [`transaction`][Self::transaction], if any.
+    pending: Option<Vec<SetDiff<T>>>,
+}
+
+impl<T: Clone + Hash + Eq + Send + Sync + 'static> ObservableSet<T> {
+    /// Create a new `ObservableSet`.
+    ///
+    /// As of the time of writing, this is equivalent to
+    /// `ObservableSet::with_capacity(16)`, but the internal buffer capacity
+    /// is subject to change in non-breaking releases.
+    ///
+    /// See [`with_capacity`][Self::with_capacity] for details about the buffer
+    /// capacity.
+    pub fn new() -> Self {
+        Self::with_capacity(16)
+    }
+
+    /// Create a new `ObservableSet` with the given capacity for the inner
+    /// buffer.
+    ///
+    /// Up to `capacity` updates that have not been received by all of the
+    /// subscribers yet will be retained in the inner buffer. If an update
+    /// happens while the buffer is at capacity, the oldest update is discarded
+    /// from it and all subscribers that have not yet received it will instead
+    /// see [`SetDiff::Reset`] as the next update.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is `0`, or larger than `usize::MAX / 2`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { values: HashSet::new(), sender, pending: None }
+    }
+
+    /// Turn the `ObservableSet` back into a regular `HashSet`.
+    pub fn into_inner(self) -> HashSet<T> {
+        self.values
+    }
+
+    /// Get the number of elements in the `HashSet`.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if the `HashSet` contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns `true` if the `HashSet` contains the given `value`.
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.values.contains(value)
+    }
+
+    /// Obtain a new subscriber.
+    ///
+    /// The first item the subscriber yields is always a [`SetDiff::Reset`]
+    /// with a snapshot of the elements at the time of subscribing.
+    pub fn subscribe(&self) -> SetSubscriber<T> {
+        SetSubscriber::new(self.subscribe_batched())
+    }
+
+    /// Obtain a new subscriber that yields all diffs of a
+    /// [`transaction`][Self::transaction] as a single item.
+    ///
+    /// See [`ObservableVector::subscribe_batched`][crate::ObservableVector::subscribe_batched].
+    pub fn subscribe_batched(&self) -> BatchedSetSubscriber<T> {
+        let stream = BroadcastStream::new(self.sender.subscribe());
+        BatchedSetSubscriber::new(stream, self.values.clone())
+    }
+
+    /// Perform multiple mutations and notify subscribers of all of them at
+    /// once.
+    ///
+    /// See [`ObservableVector::transaction`][crate::ObservableVector::transaction].
+    pub fn transaction<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        if self.pending.is_some() {
+            return f(self);
+        }
+        self.pending = Some(Vec::new());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(self)));
+        let diffs = self.pending.take().unwrap_or_default();
+        self.broadcast_diffs(diffs);
+        result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+    }
+
+    /// Insert a value and notify subscribers.
+    ///
+    /// Returns `false` without notifying subscribers if the value was already
+    /// present.
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.values.contains(&value) {
+            return false;
+        }
+        self.values.insert(value.clone());
+        self.broadcast_diff(SetDiff::Insert { value });
+        true
+    }
+
+    /// Insert all of the given values and notify subscribers once.
+    ///
+    /// Subscribers see a single [`SetDiff::Extend`] with the values that were
+    /// not present before, or nothing if there are none.
+    pub fn extend(&mut self, values: impl IntoIterator<Item = T>) {
+        let mut inserted = HashSet::new();
+        for value in values {
+            if self.values.insert(value.clone()).is_none() {
+                inserted.insert(value);
+            }
+        }
+        if !inserted.is_empty() {
+            self.broadcast_diff(SetDiff::Extend { values: inserted });
+        }
+    }
+
+    /// Remove the given value, notify subscribers and return it.
+    ///
+    /// If the value is not present, subscribers will not be notified and this
+    /// method will return `None`.
+    pub fn remove<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let value = self.values.remove(value)?;
+        self.broadcast_diff(SetDiff::Remove { value: value.clone() });
+        Some(value)
+    }
+
+    /// Retain only the values for which `f` returns `true` and notify
+    /// subscribers once.
+    ///
+    /// Subscribers see a single [`SetDiff::RemoveMany`] with the values that
+    /// were removed, or nothing if there are none.
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        let mut removed = HashSet::new();
+        self.values.retain(|value| {
+            let keep = f(value);
+            if !keep {
+                removed.insert(value.clone());
+            }
+            keep
+        });
+        if !removed.is_empty() {
+            self.broadcast_diff(SetDiff::RemoveMany { values: removed });
+        }
+    }
+
+    /// Clear out all of the elements in this set and notify subscribers.
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.broadcast_diff(SetDiff::Clear);
+    }
+
+    fn broadcast_diff(&mut self, diff: SetDiff<T>) {
+        match &mut self.pending {
+            Some(pending) => pending.push(diff),
+            None => self.broadcast_diffs(vec![diff]),
+        }
+    }
+
+    fn broadcast_diffs(&self, diffs: Vec<SetDiff<T>>) {
+        if !diffs.is_empty() && self.sender.receiver_count() != 0 {
+            let msg = BroadcastMessage { diffs, state: self.values.clone() };
+            let _num_receivers = self.sender.send(msg).unwrap_or(0);
+            #[cfg(feature = "tracing")]
+            tracing::debug!("New observable value broadcast to {_num_receivers} receivers");
+        }
+    }
+}
+
+impl<T: Clone + Hash + Eq + Send + Sync + 'static> Default for ObservableSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone + Hash + Eq + fmt::Debug> fmt::Debug for ObservableSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservableSet").field("values", &self.values).finish_non_exhaustive()
+    }
+}
+
+// Note: No DerefMut because all mutating must go through inherent methods that
+// notify subscribers
+impl<T: Clone + Hash + Eq> ops::Deref for ObservableSet<T> {
+    type Target = HashSet<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.values
+    }
+}
+
+#[derive(Clone)]
+struct BroadcastMessage<T: Clone + Hash + Eq> {
+    diffs: Vec<SetDiff<T>>,
+    state: HashSet<T>,
+}
+
+/// A subscriber for updates of an [`ObservableSet`].
+///
+/// Use its [`Stream`] implementation to interact with it (futures-util and
+/// other futures-related crates have extension traits with convenience
+/// methods).
+#[derive(Debug)]
+pub struct SetSubscriber<T: Clone + Hash + Eq> {
+    inner: BatchedSetSubscriber<T>,
+    /// The remaining diffs of the last batch.
+    queued: VecDeque<SetDiff<T>>,
+}
+
+impl<T: Clone + Hash + Eq> SetSubscriber<T> {
+    const fn new(inner: BatchedSetSubscriber<T>) -> Self {
+        Self { inner, queued: VecDeque::new() }
+    }
+}
+
+// No structural pinning
+impl<T: Clone + Hash + Eq> Unpin for SetSubscriber<T> {}
+
+impl<T: Clone + Hash + Eq + Send + Sync + 'static> Stream for SetSubscriber<T> {
+    type Item = SetDiff<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(diff) = self.queued.pop_front() {
+                return Poll::Ready(Some(diff));
+            }
+            let Some(diffs) = ready!(Pin::new(&mut self.inner).poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+            self.queued.extend(diffs);
+        }
+    }
+}
+
+/// A subscriber for batches of updates of an [`ObservableSet`].
+///
+/// Created by [`ObservableSet::subscribe_batched`].
+#[derive(Debug)]
+pub struct BatchedSetSubscriber<T: Clone + Hash + Eq> {
+    inner: BroadcastStream<BroadcastMessage<T>>,
+    initial: Option<HashSet<T>>,
+    must_reset: bool,
+}
+
+impl<T: Clone + Hash + Eq> BatchedSetSubscriber<T> {
+    const fn new(inner: BroadcastStream<BroadcastMessage<T>>, values: HashSet<T>) -> Self {
+        Self { inner, initial: Some(values), must_reset: false }
+    }
+}
+
+// No structural pinning, the snapshot is just moved out on the first poll.
+impl<T: Clone + Hash + Eq> Unpin for BatchedSetSubscriber<T> {}
+
+impl<T: Clone + Hash + Eq + Send + Sync + 'static> Stream for BatchedSetSubscriber<T> {
+    type Item = Vec<SetDiff<T>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(values) = self.initial.take() {
+            return Poll::Ready(Some(vec![SetDiff::Reset { values }]));
+        }
+
+        loop {
+            let poll = match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(msg))) => {
+                    let diffs = if self.must_reset {
+                        self.must_reset = false;
+                        vec![SetDiff::Reset { values: msg.state }]
+                    } else {
+                        msg.diffs
+                    };
+                    Poll::Ready(Some(diffs))
+                }
+                Poll::Ready(None) => Poll::Ready(None),
+                Poll::Ready(Some(Err(BroadcastStreamRecvError::Lagged(_)))) => {
+                    self.must_reset = true;
+                    continue;
+                }
+                Poll::Pending => Poll::Pending,
+            };
+
+            return poll;
+        }
+    }
+}
+
+/// A change to an [`ObservableSet`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SetDiff<T: Clone + Hash + Eq> {
+    /// A value was inserted.
+    Insert {
+        /// The inserted value.
+        value: T,
+    },
+    /// Multiple values were inserted at once.
+    Extend {
+        /// The inserted values, excluding ones that were already present.
+        values: HashSet<T>,
+    },
+    /// A value was removed.
+    Remove {
+        /// The removed value.
+        value: T,
+    },
+    /// Multiple values were removed at once.
+    RemoveMany {
+        /// The removed values.
+        values: HashSet<T>,
+    },
+    /// The set was cleared.
+    Clear,
+    /// A full snapshot of the set.
+    ///
+    /// This is the first item yielded by every subscriber, and is also sent
+    /// when the subscriber lagged too far behind and the next update that
+    /// should have been received has already been discarded from the internal
+    /// buffer.
+    Reset {
+        /// All of the elements.
+        values: HashSet<T>,
+    },
+}
@@ -1,7 +1,11 @@
 use std::{
-    fmt, ops,
+    cmp::Ordering,
+    collections::VecDeque,
+    fmt,
+    ops::{self, Range},
+    panic::{self, AssertUnwindSafe},
     pin::Pin,
-    task::{Context, Poll},
+    task::{ready, Context, Poll},
 };
 
 use futures_core::Stream;
@@ -9,10 +13,21 @@ use im::Vector;
 use tokio::sync::broadcast::{self, Sender};
 use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
 
+mod filter;
+mod sort;
+mod window;
+
+pub use self::{
+    filter::FilteredVectorSubscriber, sort::SortedVectorSubscriber,
+    window::WindowedVectorSubscriber,
+};
+
 /// An ordered list of elements that broadcasts any changes made to it.
 pub struct ObservableVector<T: Clone> {
     values: Vector<T>,
     sender: Sender<BroadcastMessage<T>>,
+    /// The diffs of the ongoing [`transaction`][Self::transaction], if any.
+    pending: Option<Vec<VectorDiff<T>>>,
 }
 
 impl<T: Clone + Send + Sync + 'static> ObservableVector<T> {
@@ -42,7 +57,7 @@ impl<T: Clone + Send + Sync + 'static> ObservableVector<T> {
     /// Panics if the capacity is `0`, or larger than `usize::MAX / 2`.
     pub fn with_capacity(capacity: usize) -> Self {
         let (sender, _) = broadcast::channel(capacity);
-        Self { values: Vector::new(), sender }
+        Self { values: Vector::new(), sender, pending: None }
     }
 
     /// Turn the `ObservableVector` back into a regular `Vector`.
@@ -50,10 +65,101 @@ impl<T: Clone + Send + Sync + 'static> ObservableVector<T> {
         self.values
     }
 
+    /// Get the number of elements in the `Vector`.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if the `Vector` contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Get a reference to the element at the given `index`, without cloning
+    /// the `Vector`.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.values.get(index)
+    }
+
+    /// Returns `true` if the `Vector` contains an element equal to `value`.
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.values.contains(value)
+    }
+
     /// Obtain a new subscriber.
+    ///
+    /// The first item the subscriber yields is always a [`VectorDiff::Reset`]
+    /// with a snapshot of the elements at the time of subscribing.
     pub fn subscribe(&self) -> VectorSubscriber<T> {
+        VectorSubscriber::new(self.subscribe_batched())
+    }
+
+    /// Obtain a new subscriber that yields all diffs of a
+    /// [`transaction`][Self::transaction] as a single item.
+    ///
+    /// Mutations outside of a transaction are yielded as items with a single
+    /// diff. The first item the subscriber yields is always a
+    /// [`VectorDiff::Reset`] with a snapshot of the elements at the time of
+    /// subscribing, as is the replacement for all diffs missed because the
+    /// subscriber lagged behind.
+    pub fn subscribe_batched(&self) -> BatchedVectorSubscriber<T> {
         let stream = BroadcastStream::new(self.sender.subscribe());
-        VectorSubscriber::new(stream)
+        BatchedVectorSubscriber::new(stream, self.values.clone())
+    }
+
+    /// Perform multiple mutations and notify subscribers of all of them at
+    /// once.
+    ///
+    /// The diffs of all mutations made by `f` are sent as one update, so
+    /// subscribers obtained through [`subscribe_batched`][Self::subscribe_batched]
+    /// receive them as a single item and can apply them atomically, for
+    /// example to avoid rendering intermediate states. Regular subscribers
+    /// still yield them one by one. Nested transactions are merged into the
+    /// outermost one.
+    ///
+    /// If `f` panics, subscribers are still notified of the mutations it made
+    /// before the panic is propagated.
+    pub fn transaction<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        if self.pending.is_some() {
+            return f(self);
+        }
+        self.pending = Some(Vec::new());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(self)));
+        let diffs = self.pending.take().unwrap_or_default();
+        self.broadcast_diffs(diffs);
+        result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+    }
+
+    /// Obtain a new subscriber for the elements that satisfy `predicate`.
+    ///
+    /// The subscriber yields diffs of the filtered view rather than of the
+    /// whole `Vector`, with indices that refer to the view, starting with a
+    /// [`VectorDiff::Reset`] like a regular subscriber. Updates that don't
+    /// affect the view are skipped.
+    pub fn subscribe_filtered<F>(&self, predicate: F) -> FilteredVectorSubscriber<T, F>
+    where
+        F: Fn(&T) -> bool,
+    {
+        FilteredVectorSubscriber::new(self.subscribe(), predicate)
+    }
+
+    /// Obtain a new subscriber for the elements sorted by `compare`.
+    ///
+    /// The subscriber yields diffs of the sorted view rather than of the
+    /// `Vector`, with indices that refer to the view, starting with a
+    /// [`VectorDiff::Reset`] like a regular subscriber. Elements that compare
+    /// equal keep their relative order. Since elements are added to the view
+    /// one by one, [`VectorDiff::Append`] is translated to one
+    /// [`VectorDiff::Insert`] per element, and an element that moves because
+    /// of a [`VectorDiff::Set`] is removed and inserted again.
+    pub fn subscribe_sorted_by<F>(&self, compare: F) -> SortedVectorSubscriber<T, F>
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        SortedVectorSubscriber::new(self.subscribe(), compare)
     }
 
     /// Append the given elements at the end of the `Vector` and notify
@@ -157,9 +263,16 @@ impl<T: Clone + Send + Sync + 'static> ObservableVector<T> {
         }
     }
 
-    fn broadcast_diff(&self, diff: VectorDiff<T>) {
-        if self.sender.receiver_count() != 0 {
-            let msg = BroadcastMessage { diff, state: self.values.clone() };
+    fn broadcast_diff(&mut self, diff: VectorDiff<T>) {
+        match &mut self.pending {
+            Some(pending) => pending.push(diff),
+            None => self.broadcast_diffs(vec![diff]),
+        }
+    }
+
+    fn broadcast_diffs(&self, diffs: Vec<VectorDiff<T>>) {
+        if !diffs.is_empty() && self.sender.receiver_count() != 0 {
+            let msg = BroadcastMessage { diffs, state: self.values.clone() };
             let _num_receivers = self.sender.send(msg).unwrap_or(0);
             #[cfg(feature = "tracing")]
             tracing::debug!("New observable value broadcast to {_num_receivers} receivers");
@@ -202,7 +315,7 @@ impl<T: Clone + Send + Sync + 'static> From<Vector<T>> for ObservableVector<T> {
 
 #[derive(Clone)]
 struct BroadcastMessage<T: Clone> {
-    diff: VectorDiff<T>,
+    diffs: Vec<VectorDiff<T>>,
     state: Vector<T>,
 }
 
@@ -213,30 +326,93 @@ struct BroadcastMessage<T: Clone> {
 /// methods).
 #[derive(Debug)]
 pub struct VectorSubscriber<T: Clone> {
-    inner: BroadcastStream<BroadcastMessage<T>>,
-    must_reset: bool,
+    inner: BatchedVectorSubscriber<T>,
+    /// The remaining diffs of the last batch.
+    queued: VecDeque<VectorDiff<T>>,
 }
 
 impl<T: Clone> VectorSubscriber<T> {
-    const fn new(inner: BroadcastStream<BroadcastMessage<T>>) -> Self {
-        Self { inner, must_reset: false }
+    const fn new(inner: BatchedVectorSubscriber<T>) -> Self {
+        Self { inner, queued: VecDeque::new() }
+    }
+
+    /// Only observe the elements within `range`, which is replaced by every
+    /// item of `ranges`.
+    ///
+    /// The returned subscriber yields diffs of the elements within the range,
+    /// with indices relative to its start. Elements that are shifted across
+    /// the edges of the range by insertions and removals before or within it
+    /// are removed or added accordingly. When the end of the range changes,
+    /// the elements that enter or leave it are appended or removed, and when
+    /// its start changes, a [`VectorDiff::Reset`] with the new elements is
+    /// yielded. Once `ranges` ends, the last range is kept.
+    ///
+    /// This is useful for virtualized lists that only render the visible
+    /// part of a long list.
+    pub fn window<S>(self, range: Range<usize>, ranges: S) -> WindowedVectorSubscriber<T, S>
+    where
+        S: Stream<Item = Range<usize>> + Unpin,
+    {
+        WindowedVectorSubscriber::new(self, range, ranges)
     }
 }
 
+// No structural pinning
+impl<T: Clone> Unpin for VectorSubscriber<T> {}
+
 impl<T: Clone + Send + Sync + 'static> Stream for VectorSubscriber<T> {
     type Item = VectorDiff<T>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(diff) = self.queued.pop_front() {
+                return Poll::Ready(Some(diff));
+            }
+            let Some(diffs) = ready!(Pin::new(&mut self.inner).poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+            self.queued.extend(diffs);
+        }
+    }
+}
+
+/// A subscriber for batches of updates of a [`Vector`].
+///
+/// Created by [`ObservableVector::subscribe_batched`].
+#[derive(Debug)]
+pub struct BatchedVectorSubscriber<T: Clone> {
+    inner: BroadcastStream<BroadcastMessage<T>>,
+    initial: Option<Vector<T>>,
+    must_reset: bool,
+}
+
+impl<T: Clone> BatchedVectorSubscriber<T> {
+    const fn new(inner: BroadcastStream<BroadcastMessage<T>>, values: Vector<T>) -> Self {
+        Self { inner, initial: Some(values), must_reset: false }
+    }
+}
+
+// No structural pinning, the snapshot is just moved out on the first poll.
+impl<T: Clone> Unpin for BatchedVectorSubscriber<T> {}
+
+impl<T: Clone + Send + Sync + 'static> Stream for BatchedVectorSubscriber<T> {
+    type Item = Vec<VectorDiff<T>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(values) = self.initial.take() {
+            return Poll::Ready(Some(vec![VectorDiff::Reset { values }]));
+        }
+
         loop {
             let poll = match Pin::new(&mut self.inner).poll_next(cx) {
                 Poll::Ready(Some(Ok(msg))) => {
-                    let diff = if self.must_reset {
+                    let diffs = if self.must_reset {
                         self.must_reset = false;
-                        VectorDiff::Reset { values: msg.state }
+                        vec![VectorDiff::Reset { values: msg.state }]
                     } else {
-                        msg.diff
+                        msg.diffs
                     };
-                    Poll::Ready(Some(diff))
+                    Poll::Ready(Some(diffs))
                 }
                 Poll::Ready(None) => Poll::Ready(None),
                 Poll::Ready(Some(Err(BroadcastStreamRecvError::Lagged(_)))) => {
@@ -298,8 +474,12 @@ pub enum VectorDiff<T: Clone> {
         /// The index that the removed element had.
         index: usize,
     },
-    /// The subscriber lagged too far behind, and the next update that should
-    /// have been received has already been discarded from the internal buffer.
+    /// The full list of elements.
+    ///
+    /// This is the first item yielded by every subscriber, and is also sent
+    /// when the subscriber lagged too far behind and the next update that
+    /// should have been received has already been discarded from the internal
+    /// buffer.
     Reset {
         /// The full list of elements.
         values: Vector<T>,
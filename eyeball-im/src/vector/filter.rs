@@ -0,0 +1,135 @@
+use std::{
+    fmt,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_core::Stream;
+use im::Vector;
+
+use super::{VectorDiff, VectorSubscriber};
+
+/// A subscriber for a live view of the elements of an [`ObservableVector`]
+/// that satisfy a predicate.
+///
+/// Created by [`ObservableVector::subscribe_filtered`].
+///
+/// [`ObservableVector`]: super::ObservableVector
+/// [`ObservableVector::subscribe_filtered`]: super::ObservableVector::subscribe_filtered
+pub struct FilteredVectorSubscriber<T: Clone, F> {
+    inner: VectorSubscriber<T>,
+    predicate: F,
+    /// Whether each element of the source vector is part of the view.
+    mask: Vec<bool>,
+}
+
+impl<T: Clone, F> FilteredVectorSubscriber<T, F> {
+    pub(super) fn new(inner: VectorSubscriber<T>, predicate: F) -> Self {
+        Self { inner, predicate, mask: Vec::new() }
+    }
+}
+
+impl<T, F> FilteredVectorSubscriber<T, F>
+where
+    T: Clone,
+    F: Fn(&T) -> bool,
+{
+    /// The index in the view of the element at `index` in the source vector,
+    /// or the one it would have if it was part of the view.
+    fn view_index(&self, index: usize) -> usize {
+        self.mask[..index].iter().filter(|&&kept| kept).count()
+    }
+
+    fn filter(&mut self, values: Vector<T>) -> Vector<T> {
+        values
+            .into_iter()
+            .filter(|value| {
+                let kept = (self.predicate)(value);
+                self.mask.push(kept);
+                kept
+            })
+            .collect()
+    }
+
+    /// Translate a diff of the source vector into one of the view, or `None`
+    /// if the view is unaffected.
+    fn translate(&mut self, diff: VectorDiff<T>) -> Option<VectorDiff<T>> {
+        match diff {
+            VectorDiff::Append { values } => {
+                let values = self.filter(values);
+                (!values.is_empty()).then_some(VectorDiff::Append { values })
+            }
+            VectorDiff::Clear => {
+                self.mask.clear();
+                Some(VectorDiff::Clear)
+            }
+            VectorDiff::PushFront { value } => {
+                let kept = (self.predicate)(&value);
+                self.mask.insert(0, kept);
+                kept.then_some(VectorDiff::PushFront { value })
+            }
+            VectorDiff::PushBack { value } => {
+                let kept = (self.predicate)(&value);
+                self.mask.push(kept);
+                kept.then_some(VectorDiff::PushBack { value })
+            }
+            VectorDiff::PopFront => self.mask.remove(0).then_some(VectorDiff::PopFront),
+            VectorDiff::PopBack => self.mask.pop()?.then_some(VectorDiff::PopBack),
+            VectorDiff::Insert { index, value } => {
+                let kept = (self.predicate)(&value);
+                let view_index = self.view_index(index);
+                self.mask.insert(index, kept);
+                kept.then_some(VectorDiff::Insert { index: view_index, value })
+            }
+            VectorDiff::Set { index, value } => {
+                let kept = (self.predicate)(&value);
+                let view_index = self.view_index(index);
+                let was_kept = std::mem::replace(&mut self.mask[index], kept);
+                match (was_kept, kept) {
+                    (true, true) => Some(VectorDiff::Set { index: view_index, value }),
+                    (true, false) => Some(VectorDiff::Remove { index: view_index }),
+                    (false, true) => Some(VectorDiff::Insert { index: view_index, value }),
+                    (false, false) => None,
+                }
+            }
+            VectorDiff::Remove { index } => {
+                let view_index = self.view_index(index);
+                self.mask.remove(index).then_some(VectorDiff::Remove { index: view_index })
+            }
+            VectorDiff::Reset { values } => {
+                self.mask.clear();
+                Some(VectorDiff::Reset { values: self.filter(values) })
+            }
+        }
+    }
+}
+
+// No structural pinning
+impl<T: Clone, F> Unpin for FilteredVectorSubscriber<T, F> {}
+
+impl<T: Clone + fmt::Debug, F> fmt::Debug for FilteredVectorSubscriber<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FilteredVectorSubscriber")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, F> Stream for FilteredVectorSubscriber<T, F>
+where
+    T: Clone + Send + Sync + 'static,
+    F: Fn(&T) -> bool,
+{
+    type Item = VectorDiff<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let Some(diff) = ready!(Pin::new(&mut self.inner).poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+            if let Some(diff) = self.translate(diff) {
+                return Poll::Ready(Some(diff));
+            }
+        }
+    }
+}
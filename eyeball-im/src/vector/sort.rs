@@ -0,0 +1,169 @@
+use std::{
+    cmp::Ordering,
+    collections::VecDeque,
+    fmt,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_core::Stream;
+use im::Vector;
+
+use super::{VectorDiff, VectorSubscriber};
+
+/// A subscriber for a live view of the elements of an [`ObservableVector`]
+/// sorted by a comparison function.
+///
+/// Created by [`ObservableVector::subscribe_sorted_by`].
+///
+/// [`ObservableVector`]: super::ObservableVector
+/// [`ObservableVector::subscribe_sorted_by`]: super::ObservableVector::subscribe_sorted_by
+pub struct SortedVectorSubscriber<T: Clone, F> {
+    inner: VectorSubscriber<T>,
+    compare: F,
+    /// The elements of the view along with their index in the source vector.
+    ///
+    /// Elements that compare equal are ordered by their index in the source
+    /// vector, so the sort is stable.
+    sorted: Vec<(usize, T)>,
+    /// Diffs of the view that are ready to be yielded, for diffs of the source
+    /// vector that translate to more than one.
+    pending: VecDeque<VectorDiff<T>>,
+}
+
+impl<T: Clone, F> SortedVectorSubscriber<T, F> {
+    pub(super) fn new(inner: VectorSubscriber<T>, compare: F) -> Self {
+        Self { inner, compare, sorted: Vec::new(), pending: VecDeque::new() }
+    }
+}
+
+impl<T, F> SortedVectorSubscriber<T, F>
+where
+    T: Clone,
+    F: Fn(&T, &T) -> Ordering,
+{
+    /// Insert an element that has the given `index` in the source vector,
+    /// after shifting the indices of the ones at or after it.
+    fn insert(&mut self, index: usize, value: T) -> VectorDiff<T> {
+        for (source_index, _) in &mut self.sorted {
+            if *source_index >= index {
+                *source_index += 1;
+            }
+        }
+        self.insert_shifted(index, value)
+    }
+
+    /// Insert an element whose `index` in the source vector is not taken.
+    fn insert_shifted(&mut self, index: usize, value: T) -> VectorDiff<T> {
+        let position = self.sorted.partition_point(|(source_index, other)| {
+            match (self.compare)(other, &value) {
+                Ordering::Less => true,
+                Ordering::Equal => *source_index < index,
+                Ordering::Greater => false,
+            }
+        });
+        self.sorted.insert(position, (index, value.clone()));
+        VectorDiff::Insert { index: position, value }
+    }
+
+    /// Remove the element that has the given `index` in the source vector,
+    /// without shifting the indices of the ones after it.
+    fn remove_unshifted(&mut self, index: usize) -> usize {
+        let position = self
+            .sorted
+            .iter()
+            .position(|(source_index, _)| *source_index == index)
+            .expect("source vector diffs are consistent with its length");
+        self.sorted.remove(position);
+        position
+    }
+
+    /// Remove the element that has the given `index` in the source vector,
+    /// and shift the indices of the ones after it.
+    fn remove(&mut self, index: usize) -> VectorDiff<T> {
+        let position = self.remove_unshifted(index);
+        for (source_index, _) in &mut self.sorted {
+            if *source_index > index {
+                *source_index -= 1;
+            }
+        }
+        VectorDiff::Remove { index: position }
+    }
+
+    fn reset(&mut self, values: Vector<T>) -> VectorDiff<T> {
+        self.sorted = values.into_iter().enumerate().collect();
+        let compare = &self.compare;
+        self.sorted.sort_by(|(_, a), (_, b)| compare(a, b));
+        VectorDiff::Reset { values: self.sorted.iter().map(|(_, value)| value.clone()).collect() }
+    }
+
+    /// Translate a diff of the source vector into diffs of the view, which
+    /// are added to `pending`.
+    fn translate(&mut self, diff: VectorDiff<T>) {
+        let diff = match diff {
+            VectorDiff::Append { values } => {
+                for value in values {
+                    let diff = self.insert_shifted(self.sorted.len(), value);
+                    self.pending.push_back(diff);
+                }
+                return;
+            }
+            VectorDiff::Clear => {
+                self.sorted.clear();
+                VectorDiff::Clear
+            }
+            VectorDiff::PushFront { value } => self.insert(0, value),
+            VectorDiff::PushBack { value } => self.insert_shifted(self.sorted.len(), value),
+            VectorDiff::PopFront => self.remove(0),
+            VectorDiff::PopBack => {
+                let Some(last) = self.sorted.len().checked_sub(1) else { return };
+                VectorDiff::Remove { index: self.remove_unshifted(last) }
+            }
+            VectorDiff::Insert { index, value } => self.insert(index, value),
+            VectorDiff::Set { index, value } => {
+                let old_position = self.remove_unshifted(index);
+                match self.insert_shifted(index, value) {
+                    VectorDiff::Insert { index: position, value } if position == old_position => {
+                        VectorDiff::Set { index: position, value }
+                    }
+                    insert => {
+                        self.pending.push_back(VectorDiff::Remove { index: old_position });
+                        insert
+                    }
+                }
+            }
+            VectorDiff::Remove { index } => self.remove(index),
+            VectorDiff::Reset { values } => self.reset(values),
+        };
+        self.pending.push_back(diff);
+    }
+}
+
+// No structural pinning
+impl<T: Clone, F> Unpin for SortedVectorSubscriber<T, F> {}
+
+impl<T: Clone + fmt::Debug, F> fmt::Debug for SortedVectorSubscriber<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SortedVectorSubscriber").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+impl<T, F> Stream for SortedVectorSubscriber<T, F>
+where
+    T: Clone + Send + Sync + 'static,
+    F: Fn(&T, &T) -> Ordering,
+{
+    type Item = VectorDiff<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(diff) = self.pending.pop_front() {
+                return Poll::Ready(Some(diff));
+            }
+            let Some(diff) = ready!(Pin::new(&mut self.inner).poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+            self.translate(diff);
+        }
+    }
+}
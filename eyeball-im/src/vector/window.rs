@@ -0,0 +1,221 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    ops::Range,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use im::Vector;
+
+use super::{VectorDiff, VectorSubscriber};
+
+/// A subscriber for a live view of the elements of an [`ObservableVector`]
+/// within a range of indices that can change over time.
+///
+/// Created by [`VectorSubscriber::window`].
+///
+/// [`ObservableVector`]: super::ObservableVector
+pub struct WindowedVectorSubscriber<T: Clone, S> {
+    inner: VectorSubscriber<T>,
+    ranges: Option<S>,
+    range: Range<usize>,
+    /// The elements of the source vector, or `None` before its initial
+    /// [`VectorDiff::Reset`] was received.
+    values: Option<Vector<T>>,
+    /// Diffs of the view that are ready to be yielded, for changes that
+    /// translate to more than one.
+    pending: VecDeque<VectorDiff<T>>,
+}
+
+impl<T: Clone, S> WindowedVectorSubscriber<T, S> {
+    pub(super) fn new(inner: VectorSubscriber<T>, range: Range<usize>, ranges: S) -> Self {
+        Self {
+            inner,
+            ranges: Some(ranges),
+            range: normalize(range),
+            values: None,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<T: Clone, S> WindowedVectorSubscriber<T, S> {
+    fn view_len(&self, values: &Vector<T>) -> usize {
+        values.len().min(self.range.end).saturating_sub(self.range.start)
+    }
+
+    fn view(&self, values: &Vector<T>) -> Vector<T> {
+        let end = values.len().min(self.range.end);
+        let start = self.range.start.min(end);
+        values.clone().slice(start..end)
+    }
+
+    fn set_range(&mut self, range: Range<usize>) {
+        let range = normalize(range);
+        let Some(values) = self.values.take() else {
+            self.range = range;
+            return;
+        };
+
+        let old_view_len = self.view_len(&values);
+        let old_start = self.range.start;
+        self.range = range;
+        let view_len = self.view_len(&values);
+
+        if self.range.start != old_start {
+            self.pending.push_back(VectorDiff::Reset { values: self.view(&values) });
+        } else if view_len > old_view_len {
+            let start = self.range.start;
+            let values = values.clone().slice(start + old_view_len..start + view_len);
+            self.pending.push_back(VectorDiff::Append { values });
+        } else {
+            for _ in view_len..old_view_len {
+                self.pending.push_back(VectorDiff::PopBack);
+            }
+        }
+        self.values = Some(values);
+    }
+
+    /// Insert an element into the source vector and translate that into diffs
+    /// of the view.
+    fn insert(&mut self, values: &mut Vector<T>, index: usize, value: T) {
+        let Range { start, end } = self.range;
+        let was_full = start < end && self.view_len(values) == end - start;
+        values.insert(index, value.clone());
+
+        if index >= end || start == end {
+            return;
+        }
+        if was_full {
+            self.pending.push_back(VectorDiff::PopBack);
+        }
+        if index >= start {
+            self.pending.push_back(VectorDiff::Insert { index: index - start, value });
+        } else if let Some(value) = values.get(start) {
+            // The element before the window was shifted into it
+            self.pending.push_back(VectorDiff::PushFront { value: value.clone() });
+        }
+    }
+
+    /// Remove an element from the source vector and translate that into diffs
+    /// of the view.
+    fn remove(&mut self, values: &mut Vector<T>, index: usize) {
+        let Range { start, end } = self.range;
+        let old_view_len = self.view_len(values);
+        values.remove(index);
+
+        if index >= end || start == end {
+            return;
+        }
+        if index >= start {
+            self.pending.push_back(VectorDiff::Remove { index: index - start });
+        } else if old_view_len > 0 {
+            // The first element of the window was shifted out of it
+            self.pending.push_back(VectorDiff::PopFront);
+        }
+        if let Some(value) = values.get(end - 1) {
+            // The element after the window was shifted into it
+            self.pending.push_back(VectorDiff::PushBack { value: value.clone() });
+        }
+    }
+
+    /// Apply a diff to the source vector and translate it into diffs of the
+    /// view, which are added to `pending`.
+    fn translate(&mut self, diff: VectorDiff<T>) {
+        let mut values = self.values.take().unwrap_or_default();
+        match diff {
+            VectorDiff::Append { values: appended } => {
+                let old_len = values.len();
+                values.append(appended);
+                let end = values.len().min(self.range.end);
+                let start = old_len.max(self.range.start);
+                if start < end {
+                    let values = values.clone().slice(start..end);
+                    self.pending.push_back(VectorDiff::Append { values });
+                }
+            }
+            VectorDiff::Clear => {
+                if self.view_len(&values) > 0 {
+                    self.pending.push_back(VectorDiff::Clear);
+                }
+                values.clear();
+            }
+            VectorDiff::PushFront { value } => self.insert(&mut values, 0, value),
+            VectorDiff::PushBack { value } => {
+                let index = values.len();
+                self.insert(&mut values, index, value);
+            }
+            VectorDiff::PopFront => self.remove(&mut values, 0),
+            VectorDiff::PopBack => {
+                let index = values.len() - 1;
+                self.remove(&mut values, index);
+            }
+            VectorDiff::Insert { index, value } => self.insert(&mut values, index, value),
+            VectorDiff::Set { index, value } => {
+                values.set(index, value.clone());
+                if self.range.contains(&index) {
+                    self.pending
+                        .push_back(VectorDiff::Set { index: index - self.range.start, value });
+                }
+            }
+            VectorDiff::Remove { index } => self.remove(&mut values, index),
+            VectorDiff::Reset { values: new_values } => {
+                values = new_values;
+                self.pending.push_back(VectorDiff::Reset { values: self.view(&values) });
+            }
+        }
+        self.values = Some(values);
+    }
+}
+
+fn normalize(range: Range<usize>) -> Range<usize> {
+    range.start..range.end.max(range.start)
+}
+
+// No structural pinning
+impl<T: Clone, S> Unpin for WindowedVectorSubscriber<T, S> {}
+
+impl<T: Clone + fmt::Debug, S> fmt::Debug for WindowedVectorSubscriber<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WindowedVectorSubscriber")
+            .field("inner", &self.inner)
+            .field("range", &self.range)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, S> Stream for WindowedVectorSubscriber<T, S>
+where
+    T: Clone + Send + Sync + 'static,
+    S: Stream<Item = Range<usize>> + Unpin,
+{
+    type Item = VectorDiff<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(diff) = self.pending.pop_front() {
+                return Poll::Ready(Some(diff));
+            }
+
+            // Once the stream of ranges ends, the last range is kept
+            while let Some(ranges) = &mut self.ranges {
+                match Pin::new(ranges).poll_next(cx) {
+                    Poll::Ready(Some(range)) => self.set_range(range),
+                    Poll::Ready(None) => self.ranges = None,
+                    Poll::Pending => break,
+                }
+            }
+            if !self.pending.is_empty() {
+                continue;
+            }
+
+            match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(diff)) => self.translate(diff),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
@@ -1,13 +1,16 @@
-use im::Vector;
+use futures_util::FutureExt as _;
+use im::{HashMap, HashSet, Vector};
 use tokio_stream::StreamExt as _;
 
-use eyeball_im::{ObservableVector, VectorDiff};
+use eyeball_im::{MapDiff, ObservableMap, ObservableSet, ObservableVector, SetDiff, VectorDiff};
 
 #[tokio::test]
 async fn lag() {
     let mut ob = ObservableVector::with_capacity(1);
     let mut rx1 = ob.subscribe();
     let mut rx2 = ob.subscribe();
+    assert_eq!(rx1.next().await, Some(VectorDiff::Reset { values: Vector::new() }));
+    assert_eq!(rx2.next().await, Some(VectorDiff::Reset { values: Vector::new() }));
 
     ob.push_back("hello".to_owned());
     assert_eq!(rx1.next().await, Some(VectorDiff::PushBack { value: "hello".to_owned() }));
@@ -21,3 +24,277 @@ async fn lag() {
         })
     );
 }
+
+#[tokio::test]
+async fn vector() {
+    let mut ob = ObservableVector::new();
+    ob.push_back('a');
+    let mut rx = ob.subscribe();
+    assert_eq!(rx.next().await, Some(VectorDiff::Reset { values: Vector::unit('a') }));
+
+    ob.insert(0, 'b');
+    assert_eq!(rx.next().await, Some(VectorDiff::Insert { index: 0, value: 'b' }));
+
+    ob.set(1, 'c');
+    assert_eq!(rx.next().await, Some(VectorDiff::Set { index: 1, value: 'c' }));
+
+    ob.remove(0);
+    assert_eq!(rx.next().await, Some(VectorDiff::Remove { index: 0 }));
+
+    ob.clear();
+    assert_eq!(rx.next().await, Some(VectorDiff::Clear));
+}
+
+/// Apply a diff yielded by a subscriber to a copy of the observed vector.
+fn apply(view: &mut Vector<u32>, diff: VectorDiff<u32>) {
+    match diff {
+        VectorDiff::Append { values } => view.append(values),
+        VectorDiff::Clear => view.clear(),
+        VectorDiff::PushFront { value } => view.push_front(value),
+        VectorDiff::PushBack { value } => view.push_back(value),
+        VectorDiff::PopFront => {
+            view.pop_front();
+        }
+        VectorDiff::PopBack => {
+            view.pop_back();
+        }
+        VectorDiff::Insert { index, value } => view.insert(index, value),
+        VectorDiff::Set { index, value } => {
+            view.set(index, value);
+        }
+        VectorDiff::Remove { index } => {
+            view.remove(index);
+        }
+        VectorDiff::Reset { values } => *view = values,
+    }
+}
+
+#[tokio::test]
+async fn transaction() {
+    let mut ob = ObservableVector::new();
+    let mut batched = ob.subscribe_batched();
+    let mut rx = ob.subscribe();
+    assert_eq!(batched.next().await, Some(vec![VectorDiff::Reset { values: Vector::new() }]));
+    assert_eq!(rx.next().await, Some(VectorDiff::Reset { values: Vector::new() }));
+
+    let len = ob.transaction(|ob| {
+        ob.push_back(1);
+        ob.transaction(|ob| ob.push_back(2));
+        ob.len()
+    });
+    assert_eq!(len, 2);
+    assert_eq!(
+        batched.next().await,
+        Some(vec![VectorDiff::PushBack { value: 1 }, VectorDiff::PushBack { value: 2 }])
+    );
+    assert_eq!(rx.next().await, Some(VectorDiff::PushBack { value: 1 }));
+    assert_eq!(rx.next().await, Some(VectorDiff::PushBack { value: 2 }));
+
+    ob.transaction(|_| {});
+    ob.pop_front();
+    assert_eq!(batched.next().await, Some(vec![VectorDiff::PopFront]));
+    assert_eq!(rx.next().await, Some(VectorDiff::PopFront));
+}
+
+#[tokio::test]
+async fn window() {
+    let mut ob = ObservableVector::new();
+    ob.append(Vector::from_iter(0..8));
+    let (ranges, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut windowed =
+        ob.subscribe().window(2..5, tokio_stream::wrappers::UnboundedReceiverStream::new(rx));
+    let mut view = Vector::new();
+    let mut range = 2..5;
+
+    let ops: [fn(&mut ObservableVector<u32>); 8] = [
+        |ob| ob.insert(1, 10),
+        |ob| ob.insert(3, 11),
+        |ob| ob.insert(8, 12),
+        |ob| {
+            ob.remove(0);
+        },
+        |ob| {
+            ob.remove(3);
+        },
+        |ob| {
+            ob.set(2, 13);
+        },
+        |ob| {
+            ob.pop_front();
+        },
+        |ob| ob.append(Vector::from_iter([14, 15])),
+    ];
+    for (i, op) in ops.into_iter().enumerate() {
+        op(&mut ob);
+        if i % 3 == 2 {
+            range = [1..6, 1..2, 1..20][i / 3].clone();
+            ranges.send(range.clone()).unwrap();
+        }
+        while let Some(Some(diff)) = windowed.next().now_or_never() {
+            apply(&mut view, diff);
+        }
+
+        let end = range.end.min(ob.len());
+        assert_eq!(view, (*ob).clone().slice(range.start.min(end)..end));
+    }
+
+    while ob.pop_back().is_some() {}
+    while let Some(Some(diff)) = windowed.next().now_or_never() {
+        apply(&mut view, diff);
+    }
+    assert!(view.is_empty());
+}
+
+#[tokio::test]
+async fn window_empty() {
+    let mut ob = ObservableVector::new();
+    ob.append(Vector::from_iter(0..10));
+    let mut windowed = ob.subscribe().window(5..5, futures_util::stream::pending());
+    assert_eq!(windowed.next().await, Some(VectorDiff::Reset { values: Vector::new() }));
+
+    ob.insert(0, 100);
+    ob.push_front(101);
+    ob.insert(5, 102);
+    ob.push_back(103);
+    assert_eq!(windowed.next().now_or_never(), None);
+}
+
+#[tokio::test]
+async fn filtered_sorted() {
+    let mut ob = ObservableVector::new();
+    ob.append(Vector::from_iter([5, 2, 8]));
+    let mut even = ob.subscribe_filtered(|value| value % 2 == 0);
+    let mut sorted = ob.subscribe_sorted_by(|a: &u32, b| a.cmp(b));
+    let (mut even_view, mut sorted_view) = (Vector::new(), Vector::new());
+
+    let ops: [fn(&mut ObservableVector<u32>); 10] = [
+        |ob| ob.append(Vector::from_iter([4, 7, 2])),
+        |ob| ob.push_front(6),
+        |ob| ob.insert(3, 1),
+        |ob| {
+            ob.set(0, 9);
+        },
+        |ob| {
+            ob.set(1, 0);
+        },
+        |ob| {
+            ob.remove(2);
+        },
+        |ob| {
+            ob.pop_front();
+        },
+        |ob| {
+            ob.pop_back();
+        },
+        |ob| ob.push_back(3),
+        |ob| ob.clear(),
+    ];
+    for op in [None].into_iter().chain(ops.map(Some)) {
+        if let Some(op) = op {
+            op(&mut ob);
+        }
+        while let Some(Some(diff)) = even.next().now_or_never() {
+            apply(&mut even_view, diff);
+        }
+        while let Some(Some(diff)) = sorted.next().now_or_never() {
+            apply(&mut sorted_view, diff);
+        }
+
+        let mut expected_sorted: Vec<_> = ob.iter().copied().collect();
+        expected_sorted.sort();
+        assert_eq!(even_view, ob.iter().copied().filter(|value| value % 2 == 0).collect());
+        assert_eq!(sorted_view, Vector::from(expected_sorted));
+    }
+}
+
+#[tokio::test]
+async fn map() {
+    let mut ob = ObservableMap::new();
+    ob.insert("a", 1);
+    let mut rx = ob.subscribe();
+    assert_eq!(rx.next().await, Some(MapDiff::Reset { values: HashMap::unit("a", 1) }));
+
+    ob.insert("b", 2);
+    assert_eq!(rx.next().await, Some(MapDiff::Insert { key: "b", value: 2 }));
+
+    ob.insert("a", 3);
+    assert_eq!(rx.next().await, Some(MapDiff::Update { key: "a", value: 3 }));
+
+    ob.entry("a").and_modify(|value| *value += 1);
+    assert_eq!(rx.next().await, Some(MapDiff::Update { key: "a", value: 4 }));
+
+    assert_eq!(*ob.entry("c").or_insert(5), 5);
+    assert_eq!(rx.next().await, Some(MapDiff::Insert { key: "c", value: 5 }));
+
+    assert_eq!(ob.remove(&"b"), Some(2));
+    assert_eq!(rx.next().await, Some(MapDiff::Remove { key: "b" }));
+
+    assert_eq!(ob.remove(&"b"), None);
+    ob.clear();
+    assert_eq!(rx.next().await, Some(MapDiff::Clear));
+}
+
+#[tokio::test]
+async fn set() {
+    let mut ob = ObservableSet::new();
+    ob.insert(1);
+    let mut rx = ob.subscribe();
+    assert_eq!(rx.next().await, Some(SetDiff::Reset { values: HashSet::unit(1) }));
+
+    assert!(!ob.insert(1));
+    assert!(ob.insert(2));
+    assert_eq!(rx.next().await, Some(SetDiff::Insert { value: 2 }));
+
+    ob.extend([2, 3, 4]);
+    assert_eq!(rx.next().await, Some(SetDiff::Extend { values: HashSet::from(vec![3, 4]) }));
+
+    ob.retain(|&n| n % 2 == 0);
+    assert_eq!(rx.next().await, Some(SetDiff::RemoveMany { values: HashSet::from(vec![1, 3]) }));
+
+    assert_eq!(ob.remove(&2), Some(2));
+    assert_eq!(rx.next().await, Some(SetDiff::Remove { value: 2 }));
+
+    ob.retain(|_| true);
+    ob.clear();
+    assert_eq!(rx.next().await, Some(SetDiff::Clear));
+    assert_eq!(rx.next().now_or_never(), None);
+}
+
+#[tokio::test]
+async fn subscribe_to_key() {
+    let mut ob = ObservableMap::new();
+    let mut rx = ob.subscribe_to_key(&"a");
+    assert_eq!(rx.next().await, Some(None));
+
+    ob.insert("b", 1);
+    ob.insert("a", 2);
+    assert_eq!(rx.next().await, Some(Some(2)));
+
+    ob.remove(&"b");
+    ob.clear();
+    assert_eq!(rx.next().await, Some(None));
+
+    ob.clear();
+    drop(ob);
+    assert_eq!(rx.next().await, None);
+}
+
+#[test]
+fn read_methods() {
+    let mut vector = ObservableVector::new();
+    assert!(vector.is_empty());
+    vector.push_back(1);
+    vector.push_back(2);
+    assert_eq!(vector.len(), 2);
+    assert_eq!(vector.get(1), Some(&2));
+    assert!(vector.contains(&1));
+    assert!(!vector.contains(&3));
+
+    let mut map = ObservableMap::new();
+    assert!(map.is_empty());
+    map.insert("a".to_owned(), 1);
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.get("a"), Some(&1));
+    assert!(map.contains_key("a"));
+    assert!(!map.contains_key("b"));
+}
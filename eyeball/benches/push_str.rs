@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use eyeball::Observable;
+
+const INITIAL_LEN: usize = 64 * 1024;
+const NUM_SUBSCRIBERS: usize = 8;
+
+fn push_str(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push_str");
+
+    group.bench_function("String", |b| {
+        b.iter_batched(
+            || {
+                let ob = Observable::new("x".repeat(INITIAL_LEN));
+                let subscribers: Vec<_> =
+                    (0..NUM_SUBSCRIBERS).map(|_| Observable::subscribe(&ob)).collect();
+                (ob, subscribers)
+            },
+            |(mut ob, mut subscribers)| {
+                Observable::push_str(&mut ob, "new line\n");
+                for subscriber in &mut subscribers {
+                    subscriber.collect_ready();
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.bench_function("Arc<str>", |b| {
+        b.iter_batched(
+            || {
+                let ob = Observable::new(Arc::<str>::from("x".repeat(INITIAL_LEN)));
+                let subscribers: Vec<_> =
+                    (0..NUM_SUBSCRIBERS).map(|_| Observable::subscribe(&ob)).collect();
+                (ob, subscribers)
+            },
+            |(mut ob, mut subscribers)| {
+                Observable::push_str_arc(&mut ob, "new line\n");
+                for subscriber in &mut subscribers {
+                    subscriber.collect_ready();
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, push_str);
+criterion_main!(benches);
@@ -0,0 +1,53 @@
+use std::{sync::Arc, thread};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use eyeball::{SharedObservable, SwapObservable};
+
+const NUM_READERS: usize = 4;
+const READS_PER_ITER: usize = 10_000;
+
+/// Read the value from several threads at once while it is being updated.
+fn contended_read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("contended_read");
+
+    group.bench_function("shared", |b| {
+        let ob = SharedObservable::new(vec![0_u8; 64]);
+        b.iter(|| {
+            thread::scope(|s| {
+                for _ in 0..NUM_READERS {
+                    s.spawn(|| {
+                        for _ in 0..READS_PER_ITER {
+                            criterion::black_box(SharedObservable::read(&ob)[0]);
+                        }
+                    });
+                }
+                SharedObservable::update(&ob, |value| value[0] += 1);
+            });
+        });
+    });
+
+    group.bench_function("swap", |b| {
+        let ob = Arc::new(SwapObservable::new(vec![0_u8; 64]));
+        b.iter(|| {
+            thread::scope(|s| {
+                for _ in 0..NUM_READERS {
+                    s.spawn(|| {
+                        for _ in 0..READS_PER_ITER {
+                            criterion::black_box(SwapObservable::read(&ob)[0]);
+                        }
+                    });
+                }
+                SwapObservable::update(&ob, |value| {
+                    let mut value = value.clone();
+                    value[0] += 1;
+                    value
+                });
+            });
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, contended_read);
+criterion_main!(benches);
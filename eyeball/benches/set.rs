@@ -0,0 +1,70 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use eyeball::Observable;
+
+const LARGE_LEN: usize = 64 * 1024;
+
+fn set(c: &mut Criterion) {
+    let mut group = c.benchmark_group("set");
+
+    for num_subscribers in [0, 1, 8] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_subscribers),
+            &num_subscribers,
+            |b, &num_subscribers| {
+                let mut ob = Observable::new(0_u64);
+                let _subscribers: Vec<_> =
+                    (0..num_subscribers).map(|_| Observable::subscribe(&ob)).collect();
+                let mut value = 0;
+                b.iter(|| {
+                    value += 1;
+                    Observable::set(&mut ob, value);
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn update_eq(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update_eq");
+
+    group.bench_function("small", |b| {
+        let mut ob = Observable::new(0_u64);
+        let _subscriber = Observable::subscribe(&ob);
+        b.iter(|| Observable::update_eq(&mut ob, |value| *value += 1));
+    });
+
+    group.bench_function("large", |b| {
+        b.iter_batched(
+            || {
+                let ob = Observable::new(vec![0_u8; LARGE_LEN]);
+                let subscriber = Observable::subscribe(&ob);
+                (ob, subscriber)
+            },
+            |(mut ob, subscriber)| {
+                Observable::update_eq(&mut ob, |value| value[0] += 1);
+                (ob, subscriber)
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.finish();
+}
+
+fn poll(c: &mut Criterion) {
+    c.bench_function("poll", |b| {
+        let mut ob = Observable::new(0_u64);
+        let mut subscriber = Observable::subscribe(&ob);
+        let mut value = 0;
+        b.iter(|| {
+            value += 1;
+            Observable::set(&mut ob, value);
+            subscriber.collect_ready()
+        });
+    });
+}
+
+criterion_group!(benches, set, update_eq, poll);
+criterion_main!(benches);
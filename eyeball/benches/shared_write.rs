@@ -0,0 +1,69 @@
+use std::{sync::RwLock, thread};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use eyeball::SharedObservable;
+
+const NUM_WRITERS: usize = 4;
+const WRITES_PER_ITER: usize = 1_000;
+
+/// Update the value from several threads at once, compared to the same
+/// updates of a bare `std::sync::RwLock` without any subscribers to notify.
+///
+/// Run with `--features parking_lot` to measure `SharedObservable` on top of
+/// `parking_lot::RwLock` instead, and to add a bare `parking_lot::RwLock` to
+/// the comparison.
+fn contended_write(c: &mut Criterion) {
+    let mut group = c.benchmark_group("contended_write");
+
+    group.bench_function("shared", |b| {
+        let ob = SharedObservable::new(0_u64);
+        let _subscriber = SharedObservable::subscribe(&ob);
+        b.iter(|| {
+            thread::scope(|s| {
+                for _ in 0..NUM_WRITERS {
+                    s.spawn(|| {
+                        for _ in 0..WRITES_PER_ITER {
+                            SharedObservable::update(&ob, |value| *value += 1);
+                        }
+                    });
+                }
+            });
+        });
+    });
+
+    group.bench_function("std_rwlock", |b| {
+        let lock = RwLock::new(0_u64);
+        b.iter(|| {
+            thread::scope(|s| {
+                for _ in 0..NUM_WRITERS {
+                    s.spawn(|| {
+                        for _ in 0..WRITES_PER_ITER {
+                            *lock.write().unwrap() += 1;
+                        }
+                    });
+                }
+            });
+        });
+    });
+
+    #[cfg(feature = "parking_lot")]
+    group.bench_function("parking_lot_rwlock", |b| {
+        let lock = parking_lot::RwLock::new(0_u64);
+        b.iter(|| {
+            thread::scope(|s| {
+                for _ in 0..NUM_WRITERS {
+                    s.spawn(|| {
+                        for _ in 0..WRITES_PER_ITER {
+                            *lock.write() += 1;
+                        }
+                    });
+                }
+            });
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, contended_write);
+criterion_main!(benches);
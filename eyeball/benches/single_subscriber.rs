@@ -0,0 +1,54 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use eyeball::Observable;
+
+const VALUE_LEN: usize = 64 * 1024;
+
+fn single_subscriber(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single_subscriber");
+
+    // Baseline: no subscribers, so the value is never cloned
+    group.bench_function("no subscribers", |b| {
+        b.iter_batched(
+            || Observable::new(vec![0u8; VALUE_LEN]),
+            |mut ob| Observable::set(&mut ob, vec![1u8; VALUE_LEN]),
+            BatchSize::SmallInput,
+        );
+    });
+
+    // The value is cloned into the channel and again when it is received
+    group.bench_function("one subscriber", |b| {
+        b.iter_batched(
+            || {
+                let ob = Observable::new(vec![0u8; VALUE_LEN]);
+                let subscriber = Observable::subscribe(&ob);
+                (ob, subscriber)
+            },
+            |(mut ob, mut subscriber)| {
+                Observable::set(&mut ob, vec![1u8; VALUE_LEN]);
+                subscriber.collect_ready()
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    // The value is cloned into the channel and moved out when it is received
+    group.bench_function("one subscriber taking values", |b| {
+        b.iter_batched(
+            || {
+                let ob = Observable::new(vec![0u8; VALUE_LEN]);
+                let subscriber = Observable::subscribe(&ob).take_values();
+                (ob, subscriber)
+            },
+            |(mut ob, mut subscriber)| {
+                Observable::set(&mut ob, vec![1u8; VALUE_LEN]);
+                subscriber.collect_ready()
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, single_subscriber);
+criterion_main!(benches);
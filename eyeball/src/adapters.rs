@@ -0,0 +1,1325 @@
+//! Stream adapters for [`Subscriber`].
+
+use alloc::{collections::VecDeque, vec::Vec};
+use core::{
+    fmt,
+    future::{poll_fn, Future, IntoFuture},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_core::Stream;
+
+use crate::{Observable, Subscriber, SubscriberError};
+
+/// A stream that yields the values of a [`Subscriber`] along with the time
+/// they were received.
+///
+/// Created by [`Subscriber::timestamped`].
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+#[derive(Debug)]
+pub struct Timestamped<T> {
+    inner: Subscriber<T>,
+}
+
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+impl<T> Timestamped<T> {
+    pub(crate) fn new(inner: Subscriber<T>) -> Self {
+        Self { inner }
+    }
+}
+
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+impl<T: Clone + Send + 'static> Stream for Timestamped<T> {
+    type Item = (std::time::Instant, T);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.inner)
+            .poll_next(cx)
+            .map(|opt| opt.map(|value| (std::time::Instant::now(), value)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// A stream that yields the values of a [`Subscriber`] along with their
+/// version.
+///
+/// Created by [`Subscriber::versioned`].
+#[derive(Debug)]
+pub struct Versioned<T> {
+    inner: Subscriber<T>,
+}
+
+impl<T> Versioned<T> {
+    pub(crate) fn new(inner: Subscriber<T>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T: Clone + Send + 'static> Stream for Versioned<T> {
+    type Item = (u64, T);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_msg(cx).map(|opt| opt.map(|msg| (msg.version, msg.value)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// A stream that yields the values of a [`Subscriber`] along with the number
+/// of updates that were skipped right before them.
+///
+/// Created by [`Subscriber::enumerate_lossy`].
+#[derive(Debug)]
+pub struct EnumerateLossy<T> {
+    inner: Subscriber<T>,
+}
+
+impl<T> EnumerateLossy<T> {
+    pub(crate) fn new(inner: Subscriber<T>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T: Clone + Send + 'static> Stream for EnumerateLossy<T> {
+    type Item = (u64, T);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut missed = 0;
+        loop {
+            match ready!(self.inner.poll_next_msg_lossless(cx)) {
+                Some(Ok(msg)) => return Poll::Ready(Some((missed, msg.value))),
+                // The message after the gap is ready right away
+                Some(Err(SubscriberError::Lagged { skipped })) => missed += skipped,
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// A stream that yields `()` for every value of a [`Subscriber`].
+///
+/// Created by [`Subscriber::into_change_pulses`].
+#[derive(Debug)]
+pub struct ChangePulses<T> {
+    inner: Subscriber<T>,
+}
+
+impl<T> ChangePulses<T> {
+    pub(crate) fn new(inner: Subscriber<T>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T: Clone + Send + 'static> Stream for ChangePulses<T> {
+    type Item = ();
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.inner).poll_next(cx).map(|opt| opt.map(drop))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// A stream that calls a closure on every value of a [`Subscriber`] before
+/// yielding it.
+///
+/// Created by [`Subscriber::inspect`].
+pub struct Inspect<T, F> {
+    inner: Subscriber<T>,
+    f: F,
+}
+
+impl<T, F> Inspect<T, F> {
+    pub(crate) fn new(inner: Subscriber<T>, f: F) -> Self {
+        Self { inner, f }
+    }
+}
+
+// No structural pinning
+impl<T, F> Unpin for Inspect<T, F> {}
+
+impl<T: fmt::Debug, F> fmt::Debug for Inspect<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Inspect").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+impl<T, F> Stream for Inspect<T, F>
+where
+    T: Clone + Send + 'static,
+    F: FnMut(&T),
+{
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let value = ready!(Pin::new(&mut this.inner).poll_next(cx));
+        if let Some(value) = &value {
+            (this.f)(value);
+        }
+        Poll::Ready(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// A stream that combines all values of a [`Subscriber`] that are ready at
+/// the same time into one.
+///
+/// Created by [`Subscriber::coalesce`].
+pub struct Coalesce<T, F> {
+    inner: Subscriber<T>,
+    combine: F,
+}
+
+impl<T, F> Coalesce<T, F> {
+    pub(crate) fn new(inner: Subscriber<T>, combine: F) -> Self {
+        Self { inner, combine }
+    }
+}
+
+// No structural pinning
+impl<T, F> Unpin for Coalesce<T, F> {}
+
+impl<T: fmt::Debug, F> fmt::Debug for Coalesce<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Coalesce").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+impl<T, F> Stream for Coalesce<T, F>
+where
+    T: Clone + Send + 'static,
+    F: FnMut(T, T) -> T,
+{
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let Some(mut acc) = ready!(Pin::new(&mut this.inner).poll_next(cx)) else {
+            return Poll::Ready(None);
+        };
+
+        // If the stream ends while coalescing, the accumulated value is still
+        // yielded and the end is reported by the next poll
+        while let Poll::Ready(Some(value)) = Pin::new(&mut this.inner).poll_next(cx) {
+            acc = (this.combine)(acc, value);
+        }
+
+        Poll::Ready(Some(acc))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// A stream that yields the latest value of a [`Subscriber`] once no new value
+/// was received for some time.
+///
+/// Created by [`Subscriber::debounce`].
+#[cfg(feature = "tokio-time")]
+pub struct Debounce<T> {
+    inner: Subscriber<T>,
+    duration: std::time::Duration,
+    pending: Option<T>,
+    sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+#[cfg(feature = "tokio-time")]
+impl<T> Debounce<T> {
+    pub(crate) fn new(inner: Subscriber<T>, duration: std::time::Duration) -> Self {
+        Self { inner, duration, pending: None, sleep: None }
+    }
+}
+
+// No structural pinning
+#[cfg(feature = "tokio-time")]
+impl<T> Unpin for Debounce<T> {}
+
+#[cfg(feature = "tokio-time")]
+impl<T: fmt::Debug> fmt::Debug for Debounce<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Debounce")
+            .field("inner", &self.inner)
+            .field("duration", &self.duration)
+            .field("pending", &self.pending)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "tokio-time")]
+impl<T: Clone + Send + 'static> Stream for Debounce<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(value)) => {
+                    this.pending = Some(value);
+                    let deadline = tokio::time::Instant::now() + this.duration;
+                    match &mut this.sleep {
+                        Some(sleep) => sleep.as_mut().reset(deadline),
+                        None => this.sleep = Some(Box::pin(tokio::time::sleep_until(deadline))),
+                    }
+                }
+                // The pending value is yielded right away, the end is
+                // reported by the next poll
+                Poll::Ready(None) => return Poll::Ready(this.pending.take()),
+                Poll::Pending => break,
+            }
+        }
+
+        match (&this.pending, &mut this.sleep) {
+            (Some(_), Some(sleep)) => {
+                ready!(sleep.as_mut().poll(cx));
+                Poll::Ready(this.pending.take())
+            }
+            _ => Poll::Pending,
+        }
+    }
+}
+
+/// A stream that yields the values of a [`Subscriber`] at most once per
+/// interval, ending every interval with the latest value.
+///
+/// Created by [`Subscriber::throttle`].
+#[cfg(feature = "tokio-time")]
+pub struct Throttle<T> {
+    inner: Subscriber<T>,
+    interval: std::time::Duration,
+    pending: Option<T>,
+    /// Set while values are held back, until the end of the interval.
+    cooldown: Option<Pin<Box<tokio::time::Sleep>>>,
+    terminated: bool,
+}
+
+#[cfg(feature = "tokio-time")]
+impl<T> Throttle<T> {
+    pub(crate) fn new(inner: Subscriber<T>, interval: std::time::Duration) -> Self {
+        Self { inner, interval, pending: None, cooldown: None, terminated: false }
+    }
+}
+
+// No structural pinning
+#[cfg(feature = "tokio-time")]
+impl<T> Unpin for Throttle<T> {}
+
+#[cfg(feature = "tokio-time")]
+impl<T: fmt::Debug> fmt::Debug for Throttle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Throttle")
+            .field("inner", &self.inner)
+            .field("interval", &self.interval)
+            .field("pending", &self.pending)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "tokio-time")]
+impl<T: Clone + Send + 'static> Stream for Throttle<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        while !this.terminated {
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(value)) => this.pending = Some(value),
+                Poll::Ready(None) => this.terminated = true,
+                Poll::Pending => break,
+            }
+        }
+
+        if let Some(cooldown) = &mut this.cooldown {
+            if cooldown.as_mut().poll(cx).is_pending() {
+                return if this.terminated && this.pending.is_none() {
+                    Poll::Ready(None)
+                } else {
+                    Poll::Pending
+                };
+            }
+            this.cooldown = None;
+        }
+
+        match this.pending.take() {
+            Some(value) => {
+                this.cooldown = Some(Box::pin(tokio::time::sleep(this.interval)));
+                Poll::Ready(Some(value))
+            }
+            None if this.terminated => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// A stream that maps the values of a [`Subscriber`], skipping results that
+/// are equal to the previous one.
+///
+/// Created by [`Subscriber::map`].
+pub struct Map<T, U, F> {
+    inner: Subscriber<T>,
+    f: F,
+    last: Option<U>,
+}
+
+impl<T, U, F> Map<T, U, F> {
+    pub(crate) fn new(inner: Subscriber<T>, f: F) -> Self {
+        Self { inner, f, last: None }
+    }
+}
+
+// No structural pinning
+impl<T, U, F> Unpin for Map<T, U, F> {}
+
+impl<T: fmt::Debug, U: fmt::Debug, F> fmt::Debug for Map<T, U, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Map")
+            .field("inner", &self.inner)
+            .field("last", &self.last)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, U, F> Stream for Map<T, U, F>
+where
+    T: Clone + Send + 'static,
+    U: Clone + PartialEq,
+    F: FnMut(T) -> U,
+{
+    type Item = U;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            let Some(value) = ready!(Pin::new(&mut this.inner).poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+            let mapped = (this.f)(value);
+            if this.last.as_ref() != Some(&mapped) {
+                this.last = Some(mapped.clone());
+                return Poll::Ready(Some(mapped));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+/// A stream that yields the values of a [`Subscriber`] that match a predicate,
+/// skipping values that are equal to the previously yielded one.
+///
+/// Created by [`Subscriber::filter`].
+pub struct Filter<T, F> {
+    inner: Subscriber<T>,
+    pred: F,
+    last: Option<T>,
+}
+
+impl<T, F> Filter<T, F> {
+    pub(crate) fn new(inner: Subscriber<T>, pred: F) -> Self {
+        Self { inner, pred, last: None }
+    }
+}
+
+// No structural pinning
+impl<T, F> Unpin for Filter<T, F> {}
+
+impl<T: fmt::Debug, F> fmt::Debug for Filter<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Filter")
+            .field("inner", &self.inner)
+            .field("last", &self.last)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, F> Stream for Filter<T, F>
+where
+    T: Clone + PartialEq + Send + 'static,
+    F: FnMut(&T) -> bool,
+{
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            let Some(value) = ready!(Pin::new(&mut this.inner).poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+            if (this.pred)(&value) && this.last.as_ref() != Some(&value) {
+                this.last = Some(value.clone());
+                return Poll::Ready(Some(value));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+/// A stream that yields the values of a [`Subscriber`], skipping values that
+/// are equal to the previously yielded one.
+///
+/// Created by [`Subscriber::dedup`].
+#[derive(Debug)]
+pub struct Dedup<T> {
+    inner: Subscriber<T>,
+    last: Option<T>,
+}
+
+impl<T> Dedup<T> {
+    pub(crate) fn new(inner: Subscriber<T>) -> Self {
+        Self { inner, last: None }
+    }
+}
+
+// No structural pinning
+impl<T> Unpin for Dedup<T> {}
+
+impl<T: Clone + PartialEq + Send + 'static> Stream for Dedup<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            let Some(value) = ready!(Pin::new(&mut this.inner).poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+            if this.last.as_ref() != Some(&value) {
+                this.last = Some(value.clone());
+                return Poll::Ready(Some(value));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+/// A stream that yields the values of a [`Subscriber`], skipping values whose
+/// key is equal to the key of the previously yielded one.
+///
+/// Created by [`Subscriber::dedup_by_key`].
+pub struct DedupByKey<T, K, F> {
+    inner: Subscriber<T>,
+    key: F,
+    last: Option<K>,
+}
+
+impl<T, K, F> DedupByKey<T, K, F> {
+    pub(crate) fn new(inner: Subscriber<T>, key: F) -> Self {
+        Self { inner, key, last: None }
+    }
+}
+
+// No structural pinning
+impl<T, K, F> Unpin for DedupByKey<T, K, F> {}
+
+impl<T: fmt::Debug, K: fmt::Debug, F> fmt::Debug for DedupByKey<T, K, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DedupByKey")
+            .field("inner", &self.inner)
+            .field("last", &self.last)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, K, F> Stream for DedupByKey<T, K, F>
+where
+    T: Clone + Send + 'static,
+    K: PartialEq,
+    F: FnMut(&T) -> K,
+{
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            let Some(value) = ready!(Pin::new(&mut this.inner).poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+            let key = (this.key)(&value);
+            if this.last.as_ref() != Some(&key) {
+                this.last = Some(key);
+                return Poll::Ready(Some(value));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+/// A stream that both filters and maps the values of a [`Subscriber`].
+///
+/// Created by [`Subscriber::filter_map`].
+pub struct FilterMap<T, F> {
+    inner: Subscriber<T>,
+    f: F,
+}
+
+impl<T, F> FilterMap<T, F> {
+    pub(crate) fn new(inner: Subscriber<T>, f: F) -> Self {
+        Self { inner, f }
+    }
+}
+
+// No structural pinning
+impl<T, F> Unpin for FilterMap<T, F> {}
+
+impl<T: fmt::Debug, F> fmt::Debug for FilterMap<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FilterMap").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+impl<T, U, F> Stream for FilterMap<T, F>
+where
+    T: Clone + Send + 'static,
+    F: FnMut(T) -> Option<U>,
+{
+    type Item = U;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            let Some(value) = ready!(Pin::new(&mut this.inner).poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+            if let Some(mapped) = (this.f)(value) {
+                return Poll::Ready(Some(mapped));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+/// A subscriber that yields an error for updates it missed, instead of
+/// skipping them.
+///
+/// Created by [`Observable::subscribe_lossless`].
+#[derive(Debug)]
+pub struct LosslessSubscriber<T> {
+    inner: Subscriber<T>,
+}
+
+impl<T> LosslessSubscriber<T> {
+    pub(crate) fn new(inner: Subscriber<T>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T: Clone + Send + 'static> Stream for LosslessSubscriber<T> {
+    type Item = Result<T, SubscriberError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_msg_lossless(cx).map(|opt| opt.map(|res| res.map(|msg| msg.value)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// A subscriber that allows looking at the next value without consuming it.
+///
+/// Created by [`Subscriber::peekable`].
+#[derive(Debug)]
+pub struct PeekableSubscriber<T> {
+    inner: Subscriber<T>,
+    peeked: Option<T>,
+}
+
+impl<T: Clone + Send + 'static> PeekableSubscriber<T> {
+    pub(crate) fn new(inner: Subscriber<T>) -> Self {
+        Self { inner, peeked: None }
+    }
+
+    /// Wait for the next value and return a reference to it, without
+    /// consuming it.
+    ///
+    /// The next poll of the stream yields the peeked value. Returns `None` if
+    /// the stream ended.
+    pub async fn peek(&mut self) -> Option<&T> {
+        poll_fn(|cx| self.poll_peek(cx).map(|opt| opt.is_some())).await;
+        self.peeked.as_ref()
+    }
+
+    /// Poll for the next value and return a reference to it, without
+    /// consuming it.
+    pub fn poll_peek(&mut self, cx: &mut Context<'_>) -> Poll<Option<&T>> {
+        if self.peeked.is_none() {
+            self.peeked = ready!(Pin::new(&mut self.inner).poll_next(cx));
+        }
+        Poll::Ready(self.peeked.as_ref())
+    }
+}
+
+// No structural pinning
+impl<T> Unpin for PeekableSubscriber<T> {}
+
+impl<T: Clone + Send + 'static> Stream for PeekableSubscriber<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.peeked.take() {
+            Some(value) => Poll::Ready(Some(value)),
+            None => Pin::new(&mut self.inner).poll_next(cx),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let peeked = usize::from(self.peeked.is_some());
+        let (_, upper) = self.inner.size_hint();
+        (peeked, upper.map(|upper| upper + peeked))
+    }
+}
+
+/// A stream that yields the values of a [`Subscriber`] for which a predicate
+/// changes its result.
+///
+/// Created by [`Subscriber::on_edge`].
+pub struct OnEdge<T, F> {
+    inner: Subscriber<T>,
+    pred: F,
+    inside: bool,
+}
+
+impl<T, F> OnEdge<T, F> {
+    pub(crate) fn new(inner: Subscriber<T>, pred: F) -> Self {
+        Self { inner, pred, inside: false }
+    }
+}
+
+// No structural pinning
+impl<T, F> Unpin for OnEdge<T, F> {}
+
+impl<T: fmt::Debug, F> fmt::Debug for OnEdge<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnEdge")
+            .field("inner", &self.inner)
+            .field("inside", &self.inside)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, F> Stream for OnEdge<T, F>
+where
+    T: Clone + Send + 'static,
+    F: FnMut(&T) -> bool,
+{
+    type Item = (bool, T);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            let Some(value) = ready!(Pin::new(&mut this.inner).poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+            let inside = (this.pred)(&value);
+            if inside != this.inside {
+                this.inside = inside;
+                return Poll::Ready(Some((inside, value)));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+/// A stream that yields the last values of a [`Subscriber`] after each new
+/// one.
+///
+/// Created by [`Subscriber::windowed`].
+#[derive(Debug)]
+pub struct Windowed<T> {
+    inner: Subscriber<T>,
+    window: VecDeque<T>,
+    size: usize,
+}
+
+impl<T> Windowed<T> {
+    pub(crate) fn new(inner: Subscriber<T>, size: usize) -> Self {
+        assert!(size > 0, "window size must be greater than zero");
+        Self { inner, window: VecDeque::with_capacity(size), size }
+    }
+}
+
+// No structural pinning
+impl<T> Unpin for Windowed<T> {}
+
+impl<T: Clone + Send + 'static> Stream for Windowed<T> {
+    type Item = Vec<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let Some(value) = ready!(Pin::new(&mut this.inner).poll_next(cx)) else {
+            return Poll::Ready(None);
+        };
+
+        if this.window.len() == this.size {
+            this.window.pop_front();
+        }
+        this.window.push_back(value);
+        Poll::Ready(Some(this.window.iter().cloned().collect()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// A stream that yields the values of a [`Subscriber`] only while another
+/// subscriber's latest value is `true`.
+///
+/// Created by [`Subscriber::gated_by`].
+#[derive(Debug)]
+pub struct GatedBy<T> {
+    inner: Subscriber<T>,
+    gate: Subscriber<bool>,
+    enabled: bool,
+    buffer_while_disabled: bool,
+    held: Option<T>,
+}
+
+impl<T> GatedBy<T> {
+    pub(crate) fn new(inner: Subscriber<T>, gate: Subscriber<bool>) -> Self {
+        Self { inner, gate, enabled: false, buffer_while_disabled: true, held: None }
+    }
+
+    /// Set whether values pass through before the gate subscriber has yielded
+    /// its first value.
+    ///
+    /// The default is `false`, because a gate subscriber only yields updates
+    /// made after it was created, so the initial state is unknown.
+    pub fn initially_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Set whether the latest value received while disabled is kept and
+    /// yielded once the gate is re-enabled.
+    ///
+    /// The default is `true`. If set to `false`, values received while
+    /// disabled are dropped.
+    pub fn buffer_while_disabled(mut self, buffer: bool) -> Self {
+        self.buffer_while_disabled = buffer;
+        self
+    }
+}
+
+// No structural pinning
+impl<T> Unpin for GatedBy<T> {}
+
+impl<T: Clone + Send + 'static> Stream for GatedBy<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        // Once the gate subscriber ends, its last value stays in effect
+        while let Poll::Ready(Some(enabled)) = Pin::new(&mut this.gate).poll_next(cx) {
+            this.enabled = enabled;
+        }
+
+        if this.enabled {
+            if let Some(value) = this.held.take() {
+                return Poll::Ready(Some(value));
+            }
+        }
+
+        loop {
+            let Some(value) = ready!(Pin::new(&mut this.inner).poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+
+            if this.enabled {
+                return Poll::Ready(Some(value));
+            }
+            if this.buffer_while_disabled {
+                this.held = Some(value);
+            }
+        }
+    }
+}
+
+/// A builder for a [`Merge`] stream.
+///
+/// Created by [`Subscriber::merge`].
+#[derive(Debug)]
+pub struct MergeBuilder<T> {
+    sources: Vec<Subscriber<T>>,
+    max_consecutive: Option<usize>,
+}
+
+impl<T> MergeBuilder<T> {
+    pub(crate) fn new(sources: Vec<Subscriber<T>>) -> Self {
+        Self { sources, max_consecutive: None }
+    }
+
+    /// Set how many values in a row the merged stream takes from one source
+    /// before it moves on to the next one.
+    ///
+    /// By default, the merged stream keeps taking values from a source for as
+    /// long as it has new ones, so a source that is updated more often than
+    /// it is polled starves all of the others.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub fn max_consecutive(mut self, n: usize) -> Self {
+        assert!(n > 0, "max_consecutive must be greater than zero");
+        self.max_consecutive = Some(n);
+        self
+    }
+
+    /// Create the merged stream.
+    pub fn build(self) -> Merge<T> {
+        Merge { sources: self.sources, max_consecutive: self.max_consecutive, current: 0, taken: 0 }
+    }
+}
+
+/// A stream that yields the values of multiple [`Subscriber`]s as they arrive.
+///
+/// Created by [`MergeBuilder::build`]. It ends once all of its sources ended.
+#[derive(Debug)]
+pub struct Merge<T> {
+    sources: Vec<Subscriber<T>>,
+    max_consecutive: Option<usize>,
+    /// The index of the source that is polled first.
+    current: usize,
+    /// The number of values taken from the current source in a row.
+    taken: usize,
+}
+
+impl<T> Merge<T> {
+    fn advance(&mut self) {
+        self.current += 1;
+        self.taken = 0;
+    }
+}
+
+// No structural pinning
+impl<T> Unpin for Merge<T> {}
+
+impl<T: Clone + Send + 'static> Stream for Merge<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        // Every iteration either ends, or increments `num_pending`, or removes
+        // a source, so this terminates
+        let mut num_pending = 0;
+        while num_pending < this.sources.len() {
+            if this.current >= this.sources.len() {
+                this.current = 0;
+            }
+
+            match Pin::new(&mut this.sources[this.current]).poll_next(cx) {
+                Poll::Ready(Some(value)) => {
+                    this.taken += 1;
+                    if this.max_consecutive.is_some_and(|max| this.taken >= max) {
+                        this.advance();
+                    }
+                    return Poll::Ready(Some(value));
+                }
+                Poll::Ready(None) => {
+                    // The next source moves to the current index
+                    this.sources.remove(this.current);
+                    this.taken = 0;
+                }
+                Poll::Pending => {
+                    num_pending += 1;
+                    this.advance();
+                }
+            }
+        }
+
+        if this.sources.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// A source of a [`ZipLatest`] or [`CombineLatest`] stream, along with its
+/// latest value.
+#[derive(Debug)]
+struct LatestSource<T> {
+    /// `None` once the subscriber has ended.
+    subscriber: Option<Subscriber<T>>,
+    latest: Option<T>,
+}
+
+impl<T: Clone + Send + 'static> LatestSource<T> {
+    fn new(subscriber: Subscriber<T>) -> Self {
+        Self { subscriber: Some(subscriber), latest: None }
+    }
+
+    /// Receive all values that are ready and return whether there were any.
+    fn poll_latest(&mut self, cx: &mut Context<'_>) -> bool {
+        let mut changed = false;
+        while let Some(subscriber) = &mut self.subscriber {
+            match Pin::new(subscriber).poll_next(cx) {
+                Poll::Ready(Some(value)) => {
+                    self.latest = Some(value);
+                    changed = true;
+                }
+                Poll::Ready(None) => self.subscriber = None,
+                Poll::Pending => break,
+            }
+        }
+        changed
+    }
+
+    fn has_ended(&self) -> bool {
+        self.subscriber.is_none()
+    }
+
+    /// Whether the source ended without ever yielding a value.
+    fn is_empty(&self) -> bool {
+        self.has_ended() && self.latest.is_none()
+    }
+}
+
+/// A stream that yields the latest values of two subscribers whenever either
+/// of them is updated.
+///
+/// Created by [`Subscriber::zip_latest`].
+#[derive(Debug)]
+pub struct ZipLatest<T, U> {
+    a: LatestSource<T>,
+    b: LatestSource<U>,
+}
+
+impl<T: Clone + Send + 'static, U: Clone + Send + 'static> ZipLatest<T, U> {
+    pub(crate) fn new(a: Subscriber<T>, b: Subscriber<U>) -> Self {
+        Self { a: LatestSource::new(a), b: LatestSource::new(b) }
+    }
+}
+
+// No structural pinning
+impl<T, U> Unpin for ZipLatest<T, U> {}
+
+impl<T: Clone + Send + 'static, U: Clone + Send + 'static> Stream for ZipLatest<T, U> {
+    type Item = (T, U);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        // Poll both, so both register the waker
+        let changed = this.a.poll_latest(cx) | this.b.poll_latest(cx);
+        if let (true, Some(a), Some(b)) = (changed, &this.a.latest, &this.b.latest) {
+            return Poll::Ready(Some((a.clone(), b.clone())));
+        }
+
+        if (this.a.has_ended() && this.b.has_ended()) || this.a.is_empty() || this.b.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// A stream that yields the latest values of multiple subscribers whenever
+/// any of them is updated.
+///
+/// Created by [`Subscriber::combine_latest`].
+#[derive(Debug)]
+pub struct CombineLatest<T> {
+    sources: Vec<LatestSource<T>>,
+}
+
+impl<T: Clone + Send + 'static> CombineLatest<T> {
+    pub(crate) fn new(sources: Vec<Subscriber<T>>) -> Self {
+        Self { sources: sources.into_iter().map(LatestSource::new).collect() }
+    }
+}
+
+// No structural pinning
+impl<T> Unpin for CombineLatest<T> {}
+
+impl<T: Clone + Send + 'static> Stream for CombineLatest<T> {
+    type Item = Vec<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        // Poll all of them, so all of them register the waker
+        let changed =
+            this.sources.iter_mut().fold(false, |changed, source| source.poll_latest(cx) | changed);
+        if changed {
+            let values: Option<Vec<T>> =
+                this.sources.iter().map(|source| source.latest.clone()).collect();
+            if let Some(values) = values {
+                return Poll::Ready(Some(values));
+            }
+        }
+
+        if this.sources.iter().all(LatestSource::has_ended)
+            || this.sources.iter().any(LatestSource::is_empty)
+        {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// A stream that yields a projection of the current value of an
+/// [`Observable`] and then of every update.
+///
+/// Created by [`Observable::subscribe_map`].
+pub struct SubscribeMap<T, U, F> {
+    inner: Subscriber<T>,
+    initial: Option<U>,
+    f: F,
+    #[cfg(feature = "graph")]
+    _node: crate::graph::Node,
+}
+
+impl<T, U, F> SubscribeMap<T, U, F>
+where
+    T: Clone + Send + 'static,
+    F: FnMut(&T) -> U,
+{
+    pub(crate) fn new(observable: &Observable<T>, mut f: F) -> Self {
+        let inner = Observable::subscribe(observable);
+        let initial = Some(f(observable));
+        Self {
+            inner,
+            initial,
+            f,
+            #[cfg(feature = "graph")]
+            _node: crate::graph::Node::new("SubscribeMap".to_owned(), Some(&observable.node)),
+        }
+    }
+}
+
+// No structural pinning
+impl<T, U, F> Unpin for SubscribeMap<T, U, F> {}
+
+impl<T: fmt::Debug, U: fmt::Debug, F> fmt::Debug for SubscribeMap<T, U, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubscribeMap").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+impl<T, U, F> Stream for SubscribeMap<T, U, F>
+where
+    T: Clone + Send + 'static,
+    F: FnMut(&T) -> U,
+{
+    type Item = U;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(initial) = self.initial.take() {
+            return Poll::Ready(Some(initial));
+        }
+
+        let this = &mut *self;
+        Pin::new(&mut this.inner).poll_next(cx).map(|opt| opt.map(|value| (this.f)(&value)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.inner.size_hint();
+        let initial = usize::from(self.initial.is_some());
+        (lower + initial, upper.map(|upper| upper + initial))
+    }
+}
+
+/// A stream that yields a projection of the current value of an
+/// [`Observable`] and then of every update that changes the projection.
+///
+/// Created by [`Observable::subscribe_map_eq`].
+pub struct SubscribeMapEq<T, U, F> {
+    inner: Subscriber<T>,
+    last: U,
+    initial_pending: bool,
+    f: F,
+    #[cfg(feature = "graph")]
+    _node: crate::graph::Node,
+}
+
+impl<T, U, F> SubscribeMapEq<T, U, F>
+where
+    T: Clone + Send + 'static,
+    F: FnMut(&T) -> U,
+{
+    pub(crate) fn new(observable: &Observable<T>, mut f: F) -> Self {
+        let inner = Observable::subscribe(observable);
+        let last = f(observable);
+        Self {
+            inner,
+            last,
+            initial_pending: true,
+            f,
+            #[cfg(feature = "graph")]
+            _node: crate::graph::Node::new("SubscribeMapEq".to_owned(), Some(&observable.node)),
+        }
+    }
+}
+
+// No structural pinning
+impl<T, U, F> Unpin for SubscribeMapEq<T, U, F> {}
+
+impl<T: fmt::Debug, U: fmt::Debug, F> fmt::Debug for SubscribeMapEq<T, U, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubscribeMapEq").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+impl<T, U, F> Stream for SubscribeMapEq<T, U, F>
+where
+    T: Clone + Send + 'static,
+    U: Clone + PartialEq,
+    F: FnMut(&T) -> U,
+{
+    type Item = U;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        if this.initial_pending {
+            this.initial_pending = false;
+            return Poll::Ready(Some(this.last.clone()));
+        }
+
+        loop {
+            let Some(value) = ready!(Pin::new(&mut this.inner).poll_next(cx)) else {
+                return Poll::Ready(None);
+            };
+
+            let projected = (this.f)(&value);
+            if projected != this.last {
+                this.last = projected.clone();
+                return Poll::Ready(Some(projected));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Updates may be skipped, so only the upper bound is meaningful
+        let (_, upper) = self.inner.size_hint();
+        let initial = usize::from(self.initial_pending);
+        (initial, upper.map(|upper| upper + initial))
+    }
+}
+
+/// A stream that re-subscribes whenever its current [`Subscriber`] ends.
+///
+/// Created by [`Subscriber::reconnecting`].
+pub struct Reconnecting<T, F> {
+    current: Option<Subscriber<T>>,
+    resubscribe: F,
+    terminated: bool,
+}
+
+impl<T, F> Reconnecting<T, F> {
+    pub(crate) fn new(resubscribe: F) -> Self {
+        Self { current: None, resubscribe, terminated: false }
+    }
+}
+
+// No structural pinning
+impl<T, F> Unpin for Reconnecting<T, F> {}
+
+impl<T: fmt::Debug, F> fmt::Debug for Reconnecting<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reconnecting")
+            .field("current", &self.current)
+            .field("terminated", &self.terminated)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, F> Stream for Reconnecting<T, F>
+where
+    T: Clone + Send + 'static,
+    F: FnMut() -> Option<Subscriber<T>>,
+{
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if this.terminated {
+                return Poll::Ready(None);
+            }
+
+            let current = match &mut this.current {
+                Some(current) => current,
+                None => match (this.resubscribe)() {
+                    Some(subscriber) => this.current.insert(subscriber),
+                    None => {
+                        this.terminated = true;
+                        continue;
+                    }
+                },
+            };
+
+            match ready!(Pin::new(current).poll_next(cx)) {
+                Some(value) => return Poll::Ready(Some(value)),
+                None => this.current = None,
+            }
+        }
+    }
+}
+
+/// A future that resolves to the next value of a [`Subscriber`].
+///
+/// Created by awaiting a `&mut Subscriber`, see [`Subscriber::next`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Next<'a, T> {
+    inner: &'a mut Subscriber<T>,
+}
+
+impl<'a, T> Next<'a, T> {
+    pub(crate) fn new(inner: &'a mut Subscriber<T>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T: Clone + Send + 'static> Future for Next<'_, T> {
+    type Output = Option<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.inner).poll_next(cx)
+    }
+}
+
+impl<'a, T: Clone + Send + 'static> IntoFuture for &'a mut Subscriber<T> {
+    type Output = Option<T>;
+    type IntoFuture = Next<'a, T>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Next::new(self)
+    }
+}
@@ -0,0 +1,186 @@
+use alloc::boxed::Box;
+use core::{
+    any::{self, Any},
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+
+use crate::{Observable, Subscriber};
+
+/// A type-erased [`Observable`].
+///
+/// This allows storing observables of different types in one collection,
+/// like a registry of settings that is keyed by name. The typed `Observable`
+/// can be recovered with [`downcast_ref`][Self::downcast_ref] and
+/// [`downcast_mut`][Self::downcast_mut], and typed subscribers can be
+/// obtained with [`downcast_subscribe`][Self::downcast_subscribe]. Code that
+/// doesn't know the type can still subscribe through
+/// [`subscribe`][Self::subscribe], which yields type-erased values.
+///
+/// Like `Observable`, it uses associated functions instead of methods.
+pub struct AnyObservable {
+    inner: Box<dyn ErasedObservable>,
+}
+
+impl AnyObservable {
+    /// Erase the type of the given `Observable`.
+    pub fn new<T: Clone + Send + Sync + 'static>(observable: Observable<T>) -> Self {
+        Self { inner: Box::new(observable) }
+    }
+
+    /// Get the name of the type of the inner value, for diagnostics.
+    pub fn type_name(this: &Self) -> &'static str {
+        this.inner.type_name()
+    }
+
+    /// Returns `true` if the inner value is of type `T`.
+    pub fn is<T: 'static>(this: &Self) -> bool {
+        this.inner.as_any().is::<Observable<T>>()
+    }
+
+    /// Get a reference to the `Observable` if its inner value is of type `T`.
+    pub fn downcast_ref<T: 'static>(this: &Self) -> Option<&Observable<T>> {
+        this.inner.as_any().downcast_ref()
+    }
+
+    /// Get a mutable reference to the `Observable` if its inner value is of
+    /// type `T`, for updating it.
+    pub fn downcast_mut<T: 'static>(this: &mut Self) -> Option<&mut Observable<T>> {
+        this.inner.as_any_mut().downcast_mut()
+    }
+
+    /// Get the `Observable` back if its inner value is of type `T`.
+    pub fn downcast<T: 'static>(this: Self) -> Result<Observable<T>, Self> {
+        if Self::is::<T>(&this) {
+            let inner = this.inner.into_any().downcast().expect("type was checked");
+            Ok(*inner)
+        } else {
+            Err(this)
+        }
+    }
+
+    /// Obtain a new subscriber if the inner value is of type `T`.
+    pub fn downcast_subscribe<T: Clone + Send + 'static>(this: &Self) -> Option<Subscriber<T>> {
+        Self::downcast_ref(this).map(Observable::subscribe)
+    }
+
+    /// Obtain a new type-erased subscriber.
+    pub fn subscribe(this: &Self) -> DynSubscriber {
+        this.inner.subscribe()
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> From<Observable<T>> for AnyObservable {
+    fn from(observable: Observable<T>) -> Self {
+        Self::new(observable)
+    }
+}
+
+impl fmt::Debug for AnyObservable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyObservable")
+            .field("type_name", &self.inner.type_name())
+            .finish_non_exhaustive()
+    }
+}
+
+trait ErasedObservable: Send + Sync {
+    fn type_name(&self) -> &'static str;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+    fn subscribe(&self) -> DynSubscriber;
+}
+
+impl<T: Clone + Send + Sync + 'static> ErasedObservable for Observable<T> {
+    fn type_name(&self) -> &'static str {
+        any::type_name::<T>()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn subscribe(&self) -> DynSubscriber {
+        DynSubscriber { inner: Box::new(Observable::subscribe(self)) }
+    }
+}
+
+/// A type-erased [`Subscriber`].
+///
+/// Created by [`AnyObservable::subscribe`]. It yields the values as
+/// `Box<dyn Any + Send>`, which can be downcast to the type of the inner
+/// value. Use [`downcast`][Self::downcast] to turn it into a typed
+/// `Subscriber` instead.
+pub struct DynSubscriber {
+    inner: Box<dyn ErasedSubscriber>,
+}
+
+impl DynSubscriber {
+    /// Get the name of the type of the values, for diagnostics.
+    pub fn type_name(&self) -> &'static str {
+        self.inner.type_name()
+    }
+
+    /// Get the typed `Subscriber` back if the values are of type `T`.
+    pub fn downcast<T: 'static>(self) -> Result<Subscriber<T>, Self> {
+        if self.inner.as_any().is::<Subscriber<T>>() {
+            let inner = self.inner.into_any().downcast().expect("type was checked");
+            Ok(*inner)
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl Stream for DynSubscriber {
+    type Item = Box<dyn Any + Send>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_any(cx)
+    }
+}
+
+impl fmt::Debug for DynSubscriber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynSubscriber")
+            .field("type_name", &self.inner.type_name())
+            .finish_non_exhaustive()
+    }
+}
+
+trait ErasedSubscriber: Send {
+    fn type_name(&self) -> &'static str;
+    fn as_any(&self) -> &dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+    fn poll_next_any(&mut self, cx: &mut Context<'_>) -> Poll<Option<Box<dyn Any + Send>>>;
+}
+
+impl<T: Clone + Send + 'static> ErasedSubscriber for Subscriber<T> {
+    fn type_name(&self) -> &'static str {
+        any::type_name::<T>()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn poll_next_any(&mut self, cx: &mut Context<'_>) -> Poll<Option<Box<dyn Any + Send>>> {
+        Pin::new(self).poll_next(cx).map(|value| value.map(|value| Box::new(value) as _))
+    }
+}
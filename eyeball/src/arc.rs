@@ -0,0 +1,86 @@
+use alloc::sync::Arc;
+use core::{fmt, ops};
+
+use crate::{Observable, Subscriber};
+
+/// A variant of [`Observable`] for values that are expensive or impossible to
+/// clone.
+///
+/// The value is stored in an [`Arc`], and subscribers receive `Arc<T>`s, so
+/// broadcasting an update only clones the pointer. `T` doesn't have to
+/// implement `Clone`, except for [`update`][Self::update].
+///
+/// Like `Observable`, it dereferences to `T` and uses associated functions
+/// instead of methods.
+pub struct ArcObservable<T> {
+    inner: Observable<Arc<T>>,
+}
+
+impl<T: Send + Sync + 'static> ArcObservable<T> {
+    /// Create a new `ArcObservable` with the given initial value.
+    pub fn new(value: T) -> Self {
+        Self::from(Observable::new(Arc::new(value)))
+    }
+
+    /// Obtain a new subscriber.
+    pub fn subscribe(this: &Self) -> Subscriber<Arc<T>> {
+        Observable::subscribe(&this.inner)
+    }
+
+    /// Get a new reference to the inner value.
+    pub fn get(this: &Self) -> Arc<T> {
+        Observable::get(&this.inner).clone()
+    }
+
+    /// Set the inner value to the given `value` and notify subscribers.
+    pub fn set(this: &mut Self, value: T) {
+        Observable::set(&mut this.inner, Arc::new(value));
+    }
+
+    /// Set the inner value to the given `value`, notify subscribers and return
+    /// the previous value.
+    pub fn replace(this: &mut Self, value: T) -> Arc<T> {
+        Observable::replace(&mut this.inner, Arc::new(value))
+    }
+
+    /// Update the inner value and notify subscribers.
+    ///
+    /// The value is cloned if it is shared, see [`Arc::make_mut`]. Since the
+    /// channel keeps the last broadcast `Arc` for [`Subscriber::get`], this is
+    /// the case for every update after the first one that was broadcast, even
+    /// if all subscribers have received it. The value is only updated in place
+    /// again once there are no subscribers. [`set`][Self::set] a new value
+    /// instead to avoid the clone.
+    pub fn update(this: &mut Self, f: impl FnOnce(&mut T))
+    where
+        T: Clone,
+    {
+        Observable::update(&mut this.inner, |value| f(Arc::make_mut(value)));
+    }
+
+    /// Get the underlying `Observable`, keeping its subscribers.
+    pub fn into_inner(this: Self) -> Observable<Arc<T>> {
+        this.inner
+    }
+}
+
+/// Wrap an existing `Observable`, keeping its configuration and subscribers.
+impl<T> From<Observable<Arc<T>>> for ArcObservable<T> {
+    fn from(inner: Observable<Arc<T>>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T> ops::Deref for ArcObservable<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T> fmt::Debug for ArcObservable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArcObservable").field("inner", &self.inner).finish()
+    }
+}
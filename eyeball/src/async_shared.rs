@@ -0,0 +1,167 @@
+use std::{fmt, ops, sync::Arc};
+
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::{observable::WriteState, Observable, Subscriber};
+
+/// A variant of [`SharedObservable`][crate::SharedObservable] that is
+/// protected by an async lock.
+///
+/// Waiting for the lock suspends the current task instead of blocking the
+/// executor thread, which matters when there are many concurrent writers.
+/// The lock is fair, so writers can't be starved by a steady stream of
+/// readers. Subscribers are regular [`Subscriber`]s and don't need the lock.
+///
+/// # Deadlocks
+///
+/// Like with any lock, awaiting a mutation while holding a guard for the
+/// same observable waits forever. To rule this out for a single handle,
+/// [`read`][Self::read] and [`write`][Self::write] take it by `&mut`, so the
+/// compiler rejects calls like [`set`][Self::set] on it while a guard is
+/// alive. This doesn't extend to other clones of the handle, so don't await
+/// mutations through those while holding a guard either.
+///
+/// Like `SharedObservable`, it uses associated functions instead of methods.
+///
+/// This requires the `async-lock` Cargo feature. It only uses the
+/// synchronization primitives of tokio, so it works with any async runtime.
+pub struct AsyncSharedObservable<T> {
+    inner: Arc<RwLock<Observable<T>>>,
+}
+
+impl<T: Clone + Send + 'static> AsyncSharedObservable<T> {
+    /// Create a new `AsyncSharedObservable` with the given initial value.
+    pub fn new(value: T) -> Self {
+        Self::from(Observable::new(value))
+    }
+
+    /// Obtain a new subscriber.
+    pub async fn subscribe(this: &Self) -> Subscriber<T> {
+        Observable::subscribe(&*this.inner.read().await)
+    }
+
+    /// Get a clone of the inner value.
+    pub async fn get(this: &Self) -> T {
+        this.inner.read().await.clone()
+    }
+
+    /// Lock the inner value for reading.
+    ///
+    /// Updates through other clones wait until the returned guard is dropped.
+    pub async fn read(this: &mut Self) -> AsyncSharedReadGuard<'_, T> {
+        AsyncSharedReadGuard { guard: this.inner.read().await }
+    }
+
+    /// Lock the inner value for writing.
+    ///
+    /// Subscribers are notified when the returned guard is dropped, if it was
+    /// mutably dereferenced, like for [`Observable::write`].
+    pub async fn write(this: &mut Self) -> AsyncSharedWriteGuard<'_, T> {
+        let mut guard = this.inner.write().await;
+        let state = WriteState::new(&mut guard);
+        AsyncSharedWriteGuard { guard, state }
+    }
+
+    /// Set the inner value to the given `value` and notify subscribers.
+    pub async fn set(this: &Self, value: T) {
+        Observable::set(&mut *this.inner.write().await, value);
+    }
+
+    /// Set the inner value to the given `value`, notify subscribers and return
+    /// the previous value.
+    pub async fn replace(this: &Self, value: T) -> T {
+        Observable::replace(&mut *this.inner.write().await, value)
+    }
+
+    /// Update the inner value and notify subscribers.
+    ///
+    /// The write lock is held while `f` runs, so the update is atomic with
+    /// respect to updates through other clones.
+    pub async fn update(this: &Self, f: impl FnOnce(&mut T)) {
+        Observable::update(&mut *this.inner.write().await, f);
+    }
+
+    /// Update the inner value and notify subscribers if the updated value does
+    /// not equal the previous value.
+    pub async fn update_eq(this: &Self, f: impl FnOnce(&mut T))
+    where
+        T: PartialEq,
+    {
+        Observable::update_eq(&mut *this.inner.write().await, f);
+    }
+}
+
+impl<T> Clone for AsyncSharedObservable<T> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone() }
+    }
+}
+
+/// Share an existing `Observable`, keeping its configuration and subscribers.
+impl<T> From<Observable<T>> for AsyncSharedObservable<T> {
+    fn from(observable: Observable<T>) -> Self {
+        Self { inner: Arc::new(RwLock::new(observable)) }
+    }
+}
+
+impl<T> fmt::Debug for AsyncSharedObservable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncSharedObservable").finish_non_exhaustive()
+    }
+}
+
+/// A read guard for the inner value of an [`AsyncSharedObservable`].
+///
+/// Created by [`AsyncSharedObservable::read`].
+pub struct AsyncSharedReadGuard<'a, T> {
+    guard: RwLockReadGuard<'a, Observable<T>>,
+}
+
+impl<T> ops::Deref for AsyncSharedReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for AsyncSharedReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+/// A write guard for the inner value of an [`AsyncSharedObservable`], which
+/// notifies subscribers when it is dropped.
+///
+/// Created by [`AsyncSharedObservable::write`].
+pub struct AsyncSharedWriteGuard<'a, T: Clone + Send + 'static> {
+    guard: RwLockWriteGuard<'a, Observable<T>>,
+    state: WriteState<T>,
+}
+
+impl<T: Clone + Send + 'static> ops::Deref for AsyncSharedWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.state.value(&self.guard)
+    }
+}
+
+impl<T: Clone + Send + 'static> ops::DerefMut for AsyncSharedWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.state.value_mut(&mut self.guard)
+    }
+}
+
+impl<T: Clone + Send + 'static> Drop for AsyncSharedWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.state.finish(&mut self.guard);
+    }
+}
+
+impl<T: Clone + Send + fmt::Debug + 'static> fmt::Debug for AsyncSharedWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        T::fmt(self, f)
+    }
+}
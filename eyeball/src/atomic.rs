@@ -0,0 +1,189 @@
+use core::{
+    fmt,
+    sync::atomic::{self, Ordering},
+};
+
+use crate::{channel::VersionedSender, Subscriber};
+
+/// An [`Observable`][crate::Observable] for small `Copy` types that can be set
+/// through a shared reference.
+///
+/// The value is stored in an atomic, so [`get`][Self::get] never blocks,
+/// which makes this a cheap alternative to a
+/// [`SharedObservable`][crate::SharedObservable] for flags, counters,
+/// generation numbers and the like. Subscribers are
+/// regular [`Subscriber`]s.
+///
+/// Each call to [`set`][Self::set] or [`replace`][Self::replace] is atomic.
+/// Setters briefly exclude each other to broadcast concurrent calls in the
+/// order they took effect, but [`get`][Self::get] never waits for them.
+/// Read-modify-write sequences of `get` and `set` are **not** atomic, since
+/// another thread can set a new value in between; use [`update`][Self::update]
+/// or, for integers, `fetch_add` and `fetch_sub` instead.
+///
+/// Only types implementing [`AtomicValue`] can be stored, which is
+/// implemented for `bool` and the primitive integer types. Small enums can be
+/// stored by implementing it for them in terms of an integer atomic.
+///
+/// Unlike `Observable`, it can't dereference to `T`, but like `Observable`, it
+/// uses associated functions instead of methods.
+pub struct AtomicObservable<T: AtomicValue> {
+    value: T::Atomic,
+    sender: VersionedSender<T>,
+}
+
+impl<T: AtomicValue> AtomicObservable<T> {
+    /// Create a new `AtomicObservable` with the given initial value.
+    pub fn new(value: T) -> Self {
+        Self { value: T::new_atomic(value), sender: VersionedSender::new() }
+    }
+
+    /// Obtain a new subscriber.
+    pub fn subscribe(this: &Self) -> Subscriber<T> {
+        Subscriber::new(this.sender.sender())
+    }
+
+    /// Get the inner value.
+    pub fn get(this: &Self) -> T {
+        T::load(&this.value)
+    }
+
+    /// Set the inner value to the given `value` and notify subscribers.
+    pub fn set(this: &Self, value: T) {
+        Self::replace(this, value);
+    }
+
+    /// Set the inner value to the given `value`, notify subscribers and return
+    /// the previous value.
+    pub fn replace(this: &Self, value: T) -> T {
+        let guard = this.sender.lock();
+        let result = T::swap(&this.value, value);
+        guard.send(value);
+        result
+    }
+
+    /// Set the inner value to the result of calling `f` with it, notify
+    /// subscribers and return the previous value.
+    ///
+    /// This is atomic with respect to other updates, since setters exclude
+    /// each other anyway. Readers don't wait for `f` though. If `f` panics,
+    /// the value is left unchanged.
+    pub fn update(this: &Self, f: impl FnOnce(T) -> T) -> T {
+        let guard = this.sender.lock();
+        let value = f(T::load(&this.value));
+        let result = T::swap(&this.value, value);
+        guard.send(value);
+        result
+    }
+}
+
+impl<T: AtomicInteger> AtomicObservable<T> {
+    /// Add `n` to the inner value, wrapping around on overflow, notify
+    /// subscribers and return the previous value.
+    pub fn fetch_add(this: &Self, n: T) -> T {
+        Self::update(this, |value| value.wrapping_add(n))
+    }
+
+    /// Subtract `n` from the inner value, wrapping around on overflow, notify
+    /// subscribers and return the previous value.
+    pub fn fetch_sub(this: &Self, n: T) -> T {
+        Self::update(this, |value| value.wrapping_sub(n))
+    }
+}
+
+impl<T: AtomicValue + fmt::Debug> fmt::Debug for AtomicObservable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicObservable")
+            .field("value", &T::load(&self.value))
+            .finish_non_exhaustive()
+    }
+}
+
+/// A type that can be stored in an [`AtomicObservable`].
+///
+/// This associates a `Copy` type with an atomic type that can hold it, like
+/// `u32` with [`AtomicU32`][atomic::AtomicU32].
+pub trait AtomicValue: Copy + Send + Sync + 'static {
+    /// The atomic type the value is stored in.
+    type Atomic: Send + Sync;
+
+    /// Create a new atomic holding `self`.
+    fn new_atomic(self) -> Self::Atomic;
+
+    /// Load the value stored in `atomic`.
+    fn load(atomic: &Self::Atomic) -> Self;
+
+    /// Store `value` in `atomic` and return the previous value.
+    fn swap(atomic: &Self::Atomic, value: Self) -> Self;
+}
+
+macro_rules! impl_atomic_value {
+    ($($ty:ty => $atomic:ident),* $(,)?) => {
+        $(
+            impl AtomicValue for $ty {
+                type Atomic = atomic::$atomic;
+
+                fn new_atomic(self) -> Self::Atomic {
+                    atomic::$atomic::new(self)
+                }
+
+                fn load(atomic: &Self::Atomic) -> Self {
+                    atomic.load(Ordering::Acquire)
+                }
+
+                fn swap(atomic: &Self::Atomic, value: Self) -> Self {
+                    atomic.swap(value, Ordering::AcqRel)
+                }
+            }
+        )*
+    };
+}
+
+/// An integer type that can be stored in an [`AtomicObservable`], enabling
+/// [`fetch_add`][AtomicObservable::fetch_add] and
+/// [`fetch_sub`][AtomicObservable::fetch_sub].
+pub trait AtomicInteger: AtomicValue {
+    /// Add `n` to `self`, wrapping around on overflow.
+    fn wrapping_add(self, n: Self) -> Self;
+
+    /// Subtract `n` from `self`, wrapping around on overflow.
+    fn wrapping_sub(self, n: Self) -> Self;
+}
+
+macro_rules! impl_atomic_integer {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl AtomicInteger for $ty {
+                fn wrapping_add(self, n: Self) -> Self {
+                    <$ty>::wrapping_add(self, n)
+                }
+
+                fn wrapping_sub(self, n: Self) -> Self {
+                    <$ty>::wrapping_sub(self, n)
+                }
+            }
+        )*
+    };
+}
+
+impl_atomic_integer!(u8, u16, u32, usize, i8, i16, i32, isize);
+// Some targets, especially embedded ones, have no 64-bit atomics
+#[cfg(target_has_atomic = "64")]
+impl_atomic_integer!(u64, i64);
+
+impl_atomic_value! {
+    bool => AtomicBool,
+    u8 => AtomicU8,
+    u16 => AtomicU16,
+    u32 => AtomicU32,
+    usize => AtomicUsize,
+    i8 => AtomicI8,
+    i16 => AtomicI16,
+    i32 => AtomicI32,
+    isize => AtomicIsize,
+}
+#[cfg(target_has_atomic = "64")]
+impl_atomic_value! {
+    u64 => AtomicU64,
+    i64 => AtomicI64,
+}
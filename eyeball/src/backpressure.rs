@@ -0,0 +1,219 @@
+use alloc::{collections::VecDeque, sync::Arc, vec::Vec};
+use core::{
+    fmt,
+    future::poll_fn,
+    mem, ops,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use futures_core::Stream;
+
+use crate::lock::{Mutex, MutexGuard};
+
+/// A lossless variant of [`Observable`][crate::Observable] that makes the
+/// producer wait for slow subscribers.
+///
+/// Every subscriber has its own bounded buffer. Instead of dropping values
+/// when a subscriber lags behind, [`set_async`][Self::set_async] only
+/// completes once every subscriber's buffer had room for the new value. This
+/// means subscribers see every value, in order.
+///
+/// # Deadlocks
+///
+/// A subscriber that is never polled (but also not dropped) will make
+/// `set_async` wait forever once its buffer is full. Be careful not to await
+/// `set_async` from the same task that is supposed to poll one of the
+/// subscribers.
+///
+/// Like `Observable`, it dereferences to `T` and uses associated functions
+/// instead of methods.
+pub struct BackpressuredObservable<T> {
+    value: T,
+    capacity: usize,
+    buffers: Mutex<Vec<Arc<Buffer<T>>>>,
+}
+
+impl<T: Clone + Send + 'static> BackpressuredObservable<T> {
+    /// Create a new `BackpressuredObservable` with the given initial value,
+    /// giving each subscriber a buffer with room for `capacity` values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(value: T, capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be greater than zero");
+        Self { value, capacity, buffers: Mutex::new(Vec::new()) }
+    }
+
+    /// Obtain a new subscriber.
+    pub fn subscribe(this: &Self) -> BackpressuredSubscriber<T> {
+        let buffer = Arc::new(Buffer::new(this.capacity));
+        this.buffers.lock().push(buffer.clone());
+        BackpressuredSubscriber { buffer }
+    }
+
+    /// Get a reference to the inner value.
+    pub fn get(this: &Self) -> &T {
+        &this.value
+    }
+
+    /// Set the inner value to the given `value` and notify subscribers,
+    /// waiting until every subscriber has room for it.
+    pub async fn set_async(this: &mut Self, value: T) {
+        Self::replace_async(this, value).await;
+    }
+
+    /// Set the inner value to the given `value`, notify subscribers and return
+    /// the previous value, waiting until every subscriber has room for it.
+    pub async fn replace_async(this: &mut Self, value: T) -> T {
+        let result = mem::replace(&mut this.value, value);
+
+        let buffers = {
+            let mut buffers = this.buffers.lock();
+            buffers.retain(|buffer| !buffer.lock().closed);
+            buffers.clone()
+        };
+        for buffer in buffers {
+            let mut value = Some(this.value.clone());
+            poll_fn(|cx| buffer.poll_push(&mut value, cx)).await;
+        }
+
+        result
+    }
+}
+
+impl<T> ops::Deref for BackpressuredObservable<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> Drop for BackpressuredObservable<T> {
+    fn drop(&mut self) {
+        let buffers = self.buffers.get_mut();
+        for buffer in buffers.drain(..) {
+            buffer.close();
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for BackpressuredObservable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BackpressuredObservable")
+            .field("value", &self.value)
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A subscriber for updates of a [`BackpressuredObservable`].
+///
+/// Unlike [`Subscriber`][crate::Subscriber], this yields every value that was
+/// set after it was created, not just the latest one.
+pub struct BackpressuredSubscriber<T> {
+    buffer: Arc<Buffer<T>>,
+}
+
+impl<T> Stream for BackpressuredSubscriber<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.buffer.poll_pop(cx)
+    }
+}
+
+impl<T> Drop for BackpressuredSubscriber<T> {
+    fn drop(&mut self) {
+        self.buffer.close();
+    }
+}
+
+impl<T> fmt::Debug for BackpressuredSubscriber<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BackpressuredSubscriber").finish_non_exhaustive()
+    }
+}
+
+/// The bounded buffer of a single subscriber.
+struct Buffer<T> {
+    state: Mutex<BufferState<T>>,
+}
+
+struct BufferState<T> {
+    values: VecDeque<T>,
+    capacity: usize,
+    /// Set once either side is dropped.
+    closed: bool,
+    /// The waker of the subscriber, if it is waiting for a value.
+    pop_waker: Option<Waker>,
+    /// The waker of the observable, if it is waiting for room.
+    push_waker: Option<Waker>,
+}
+
+impl<T> Buffer<T> {
+    fn new(capacity: usize) -> Self {
+        let state = BufferState {
+            values: VecDeque::with_capacity(capacity),
+            capacity,
+            closed: false,
+            pop_waker: None,
+            push_waker: None,
+        };
+        Self { state: Mutex::new(state) }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BufferState<T>> {
+        self.state.lock()
+    }
+
+    /// Push the value out of `value` once there is room for it.
+    ///
+    /// Completes without pushing if the buffer is closed.
+    fn poll_push(&self, value: &mut Option<T>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.lock();
+        if state.closed {
+            return Poll::Ready(());
+        }
+        if state.values.len() == state.capacity {
+            state.push_waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        state.values.extend(value.take());
+        let waker = state.pop_waker.take();
+        drop(state);
+        waker.into_iter().for_each(Waker::wake);
+        Poll::Ready(())
+    }
+
+    fn poll_pop(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut state = self.lock();
+        if let Some(value) = state.values.pop_front() {
+            let waker = state.push_waker.take();
+            drop(state);
+            waker.into_iter().for_each(Waker::wake);
+            return Poll::Ready(Some(value));
+        }
+        if state.closed {
+            return Poll::Ready(None);
+        }
+
+        state.pop_waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    /// Close the buffer, waking the other side.
+    ///
+    /// Values that are already buffered can still be received.
+    fn close(&self) {
+        let wakers = {
+            let mut state = self.lock();
+            state.closed = true;
+            [state.pop_waker.take(), state.push_waker.take()]
+        };
+        wakers.into_iter().flatten().for_each(Waker::wake);
+    }
+}
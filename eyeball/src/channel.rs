@@ -0,0 +1,583 @@
+//! The channel that delivers updates from an observable to its subscribers.
+//!
+//! Subscribers only ever need the latest value, so instead of a queue, the
+//! channel holds the latest message along with a sequence number that is
+//! incremented for every message, and a list of wakers. A receiver remembers
+//! the sequence number of the last message it has seen: if the channel's is
+//! higher, it clones the latest message, otherwise it registers its waker.
+//! By default, there is no buffering, so a receiver that is polled less often
+//! than messages are sent simply skips the ones in between. A channel can
+//! also keep a history of the last few messages, which receivers created with
+//! [`Sender::subscribe_buffered`] receive one by one.
+
+#[cfg(any(feature = "registry", feature = "tokio-time"))]
+use alloc::sync::Weak;
+use alloc::{collections::VecDeque, sync::Arc, vec::Vec};
+use core::{
+    fmt, mem, ops,
+    task::{Context, Poll, Waker},
+};
+
+use crate::{
+    instrumentation::{Instrumentation, Instrumenter},
+    lock::{Mutex, MutexGuard},
+    observable::BroadcastMessage,
+};
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+}
+
+struct State<T> {
+    /// The last `capacity` messages, oldest first.
+    history: VecDeque<BroadcastMessage<T>>,
+    capacity: usize,
+    /// The number of messages sent so far.
+    seq: u64,
+    /// Waker slots of the receivers, indexed by their `slot`.
+    wakers: Vec<Option<Waker>>,
+    /// The sequence number of the last message received by each receiver,
+    /// indexed by their `slot`. `u64::MAX` for unused slots.
+    seen: Vec<u64>,
+    /// Wakers to wake once the last receiver is dropped.
+    idle_wakers: Vec<Waker>,
+    /// Wakers to wake once a receiver subscribes.
+    subscribe_wakers: Vec<Waker>,
+    /// Wakers to wake once a receiver received a message or was dropped.
+    received_wakers: Vec<Waker>,
+    /// Indices of unused entries in `wakers`.
+    free_slots: Vec<usize>,
+    /// Set once the sender is closed or dropped.
+    closed: bool,
+    instrumenter: Instrumenter,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock()
+    }
+}
+
+impl<T> State<T> {
+    fn receiver_count(&self) -> usize {
+        self.wakers.len() - self.free_slots.len()
+    }
+
+    /// The sequence number of the first message in `history`.
+    fn oldest_seq(&self) -> u64 {
+        self.seq + 1 - self.history.len() as u64
+    }
+
+    fn take_wakers(&mut self) -> Vec<Waker> {
+        self.wakers.iter_mut().filter_map(Option::take).collect()
+    }
+}
+
+/// The sending half of a channel, owned by an observable.
+///
+/// Dropping it ends the streams of all receivers, after they have received
+/// the last message if they haven't already.
+pub(crate) struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    pub(crate) fn new() -> Self {
+        Self::with_capacity(1)
+    }
+
+    /// Create a channel that keeps the last `capacity` messages for buffered
+    /// receivers.
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        debug_assert!(capacity > 0);
+        let state = State {
+            history: VecDeque::with_capacity(capacity),
+            capacity,
+            seq: 0,
+            wakers: Vec::new(),
+            seen: Vec::new(),
+            idle_wakers: Vec::new(),
+            subscribe_wakers: Vec::new(),
+            received_wakers: Vec::new(),
+            free_slots: Vec::new(),
+            closed: false,
+            instrumenter: Instrumenter::default(),
+        };
+        Self { shared: Arc::new(Shared { state: Mutex::new(state) }) }
+    }
+
+    /// Create a receiver that receives all messages sent after this call.
+    pub(crate) fn subscribe(&self) -> Receiver<T> {
+        Receiver::subscribe(&self.shared, false)
+    }
+
+    /// Create a receiver that receives all messages sent after this call one
+    /// by one, as long as it doesn't fall behind by more than the capacity of
+    /// the channel.
+    pub(crate) fn subscribe_buffered(&self) -> Receiver<T> {
+        Receiver::subscribe(&self.shared, true)
+    }
+
+    /// Create a receiver that only subscribes when it is polled for the first
+    /// time, and doesn't count towards the receiver count before that.
+    pub(crate) fn subscribe_lazy(&self) -> Receiver<T> {
+        Receiver { shared: self.shared.clone(), subscription: None, buffered: false, take: false }
+    }
+
+    /// Create a new channel with the same capacity and instrumentation.
+    pub(crate) fn renew(&self) -> Self {
+        let state = self.shared.lock();
+        let this = Self::with_capacity(state.capacity);
+        this.shared.lock().instrumenter = state.instrumenter.clone();
+        this
+    }
+
+    /// Set the observable name that is passed to the instrumentation.
+    pub(crate) fn set_name(&self, name: &'static str) {
+        self.shared.lock().instrumenter.name = Some(name);
+    }
+
+    /// Set the instrumentation to use instead of the global one.
+    pub(crate) fn set_instrumentation(&self, instrumentation: Arc<dyn Instrumentation>) {
+        self.shared.lock().instrumenter.local = Some(instrumentation);
+    }
+
+    #[cfg(feature = "tokio-time")]
+    pub(crate) fn downgrade(&self) -> WeakSender<T> {
+        WeakSender { shared: Arc::downgrade(&self.shared) }
+    }
+
+    pub(crate) fn receiver_count(&self) -> usize {
+        self.shared.lock().receiver_count()
+    }
+
+    /// Poll until there are no receivers.
+    pub(crate) fn poll_no_receivers(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.shared.lock();
+        if state.receiver_count() == 0 {
+            return Poll::Ready(());
+        }
+        if !state.idle_wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.idle_wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+
+    /// Poll until there is at least one receiver.
+    pub(crate) fn poll_receivers(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.shared.lock();
+        if state.receiver_count() > 0 {
+            return Poll::Ready(());
+        }
+        if !state.subscribe_wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.subscribe_wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+
+    /// The sequence number of the latest message.
+    pub(crate) fn seq(&self) -> u64 {
+        self.shared.lock().seq
+    }
+
+    /// Poll until every receiver has received the message with sequence
+    /// number `seq` (or a later one), or was dropped.
+    ///
+    /// Receivers that subscribed after the message was sent are ignored.
+    pub(crate) fn poll_received(&self, seq: u64, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.shared.lock();
+        if state.seen.iter().all(|&seen| seen >= seq) {
+            return Poll::Ready(());
+        }
+        if !state.received_wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.received_wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+
+    /// Returns whether there are any receivers to send a message to.
+    ///
+    /// If there are none, the stored messages are discarded, since the caller
+    /// is about to make them outdated without sending a new one. A closed
+    /// channel has no receivers to send to, but keeps its messages for the
+    /// receivers that haven't received them yet.
+    pub(crate) fn has_receivers(&self) -> bool {
+        let mut state = self.shared.lock();
+        if state.closed {
+            false
+        } else if state.receiver_count() == 0 {
+            state.history.clear();
+            false
+        } else {
+            true
+        }
+    }
+
+    pub(crate) fn same_channel(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.shared, &other.shared)
+    }
+
+    /// Store a new latest message, dropping the oldest one if the history is
+    /// full, and wake all receivers.
+    ///
+    /// Returns the number of receivers, or an error if there are none or the
+    /// channel is closed.
+    pub(crate) fn send(&self, msg: BroadcastMessage<T>) -> Result<usize, SendError> {
+        send(&self.shared, msg)
+    }
+
+    /// End the streams of all receivers, after they have received the latest
+    /// message if they haven't already.
+    ///
+    /// Messages can't be sent anymore afterwards.
+    pub(crate) fn close(&self) {
+        let wakers = {
+            let mut state = self.shared.lock();
+            state.closed = true;
+            state.take_wakers()
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.shared.lock().closed
+    }
+}
+
+/// A sender for observables whose value is set through a shared reference.
+///
+/// Setters [`lock`][Self::lock] it before storing a new value and send the
+/// value through the returned guard, so concurrent updates are broadcast in
+/// the order they took effect and the last message receivers get is always
+/// the current value. Readers of the value are not affected by the lock.
+pub(crate) struct VersionedSender<T> {
+    sender: Sender<T>,
+    /// The version of the latest update.
+    version: Mutex<u64>,
+}
+
+impl<T> VersionedSender<T> {
+    pub(crate) fn new() -> Self {
+        Self { sender: Sender::new(), version: Mutex::new(0) }
+    }
+
+    pub(crate) fn sender(&self) -> &Sender<T> {
+        &self.sender
+    }
+
+    /// Exclude other setters until the returned guard is dropped.
+    pub(crate) fn lock(&self) -> VersionedSendGuard<'_, T> {
+        // The lock only guards the version, which a panicking setter can't
+        // leave in an inconsistent state, so poisoning can be ignored
+        let version = self.version.lock();
+        VersionedSendGuard { sender: &self.sender, version }
+    }
+}
+
+/// A lock on a [`VersionedSender`].
+pub(crate) struct VersionedSendGuard<'a, T> {
+    sender: &'a Sender<T>,
+    version: MutexGuard<'a, u64>,
+}
+
+impl<T> VersionedSendGuard<'_, T> {
+    /// Send the new value to all receivers and release the lock.
+    pub(crate) fn send(mut self, value: T) {
+        *self.version += 1;
+        if self.sender.has_receivers() {
+            // Sending only fails if all receivers were dropped in the meantime
+            let _ = self.sender.send(BroadcastMessage { version: *self.version, value });
+        }
+    }
+}
+
+#[cfg(feature = "registry")]
+impl<T: Send + 'static> Sender<T> {
+    /// Get a handle for reading the receiver count that doesn't keep the
+    /// channel alive.
+    pub(crate) fn receiver_count_handle(&self) -> Weak<dyn ReceiverCount> {
+        Arc::downgrade(&self.shared) as _
+    }
+}
+
+/// Type-erased access to the receiver count of a channel.
+#[cfg(feature = "registry")]
+pub(crate) trait ReceiverCount: Send + Sync {
+    fn receiver_count(&self) -> usize;
+}
+
+#[cfg(feature = "registry")]
+impl<T: Send> ReceiverCount for Shared<T> {
+    fn receiver_count(&self) -> usize {
+        self.lock().receiver_count()
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// A sender that doesn't keep the channel open.
+#[cfg(feature = "tokio-time")]
+pub(crate) struct WeakSender<T> {
+    shared: Weak<Shared<T>>,
+}
+
+#[cfg(feature = "tokio-time")]
+impl<T> WeakSender<T> {
+    /// Like [`Sender::send`], but also fails if the sender was dropped (which
+    /// closes the channel).
+    pub(crate) fn send(&self, msg: BroadcastMessage<T>) -> Result<usize, SendError> {
+        match self.shared.upgrade() {
+            Some(shared) => send(&shared, msg),
+            None => Err(SendError),
+        }
+    }
+}
+
+fn send<T>(shared: &Shared<T>, msg: BroadcastMessage<T>) -> Result<usize, SendError> {
+    let (num_receivers, wakers, hook) = {
+        let mut state = shared.lock();
+        let num_receivers = state.receiver_count();
+        if num_receivers == 0 || state.closed {
+            return Err(SendError);
+        }
+        if state.history.len() == state.capacity {
+            state.history.pop_front();
+        }
+        state.history.push_back(msg);
+        state.seq += 1;
+        (num_receivers, state.take_wakers(), state.instrumenter.hook())
+    };
+    wakers.into_iter().for_each(Waker::wake);
+    if let Some(hook) = hook {
+        hook.on_update(num_receivers);
+    }
+    Ok(num_receivers)
+}
+
+/// The error returned by [`Sender::send`] if there are no receivers or the
+/// channel is closed.
+#[derive(Debug)]
+pub(crate) struct SendError;
+
+/// The receiving half of a channel.
+pub(crate) struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    /// `None` for lazy receivers that were not polled yet.
+    subscription: Option<Subscription>,
+    /// Whether to receive the stored messages one by one instead of only the
+    /// latest one.
+    buffered: bool,
+    /// Whether to move received messages out of the channel instead of
+    /// cloning them while this is the only receiver.
+    take: bool,
+}
+
+struct Subscription {
+    /// This receiver's index in `State::wakers`.
+    slot: usize,
+    /// The sequence number of the last message received.
+    seen: u64,
+}
+
+impl<T> Receiver<T> {
+    fn subscribe(shared: &Arc<Shared<T>>, buffered: bool) -> Self {
+        let subscription = Subscription::new(shared);
+        Self { shared: shared.clone(), subscription: Some(subscription), buffered, take: false }
+    }
+
+    /// Create a new receiver for the same channel, which receives all messages
+    /// sent after this call.
+    ///
+    /// The new receiver is buffered if this one is.
+    pub(crate) fn resubscribe(&self) -> Self {
+        Self::subscribe(&self.shared, self.buffered)
+    }
+
+    /// Move received messages out of the channel instead of cloning them
+    /// while this is the only receiver.
+    ///
+    /// They are no longer available through [`latest`][Self::latest] and
+    /// [`read_latest`][Self::read_latest] then.
+    pub(crate) fn take_received(&mut self) {
+        self.take = true;
+    }
+
+    /// Get the value of the latest message, whether this receiver has
+    /// received it or not.
+    pub(crate) fn latest(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.shared.lock().history.back().map(|msg| msg.value.clone())
+    }
+
+    /// Whether the sender was closed or dropped.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.shared.lock().closed
+    }
+
+    /// Lock the channel to access the value of the latest message without
+    /// cloning it.
+    ///
+    /// No messages can be sent while the returned guard is alive.
+    pub(crate) fn read_latest(&self) -> Option<LatestGuard<'_, T>> {
+        let state = self.shared.lock();
+        (!state.history.is_empty()).then_some(LatestGuard { state })
+    }
+
+    /// Poll for a message that was sent after the last one this receiver
+    /// received.
+    ///
+    /// Returns the number of messages that were skipped because they were
+    /// replaced before this receiver was polled, along with the message. For
+    /// buffered receivers, this is the oldest stored message that wasn't
+    /// received yet, and only messages that were dropped from the history are
+    /// skipped.
+    /// Returns `None` once the sender is closed or dropped and the latest
+    /// message has been received.
+    pub(crate) fn poll_recv(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<(u64, BroadcastMessage<T>)>>
+    where
+        T: Clone,
+    {
+        let subscription = self.subscription.get_or_insert_with(|| Subscription::new(&self.shared));
+        let mut state = self.shared.lock();
+
+        if state.seq > subscription.seen {
+            let oldest_seq = state.oldest_seq();
+            let seq = if self.buffered { oldest_seq.max(subscription.seen + 1) } else { state.seq };
+            let skipped = seq - subscription.seen - 1;
+            subscription.seen = seq;
+            state.seen[subscription.slot] = seq;
+            let index = (seq - oldest_seq) as usize;
+            let msg = if self.take && state.receiver_count() == 1 {
+                // No other receiver needs this message or the ones before it,
+                // and receivers created later start after them
+                state.history.drain(..index);
+                state.history.pop_front().expect("index is in bounds")
+            } else {
+                state.history[index].clone()
+            };
+            let received_wakers = mem::take(&mut state.received_wakers);
+            let hook = if skipped > 0 { state.instrumenter.hook() } else { None };
+            drop(state);
+            received_wakers.into_iter().for_each(Waker::wake);
+            if let Some(hook) = hook {
+                hook.on_lag(skipped);
+            }
+            return Poll::Ready(Some((skipped, msg)));
+        }
+
+        if state.closed {
+            return Poll::Ready(None);
+        }
+
+        match &mut state.wakers[subscription.slot] {
+            Some(waker) if waker.will_wake(cx.waker()) => {}
+            slot => *slot = Some(cx.waker().clone()),
+        }
+        Poll::Pending
+    }
+}
+
+/// A lock on a channel that dereferences to the value of its latest message.
+///
+/// Created by [`Receiver::read_latest`].
+pub(crate) struct LatestGuard<'a, T> {
+    state: MutexGuard<'a, State<T>>,
+}
+
+impl<T> ops::Deref for LatestGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state.history.back().expect("checked in read_latest").value
+    }
+}
+
+/// Cloning a receiver creates a new one at the same position, which receives
+/// the same messages from there on.
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        let subscription = self
+            .subscription
+            .as_ref()
+            .map(|subscription| Subscription::new_at(&self.shared, Some(subscription.seen)));
+        Self { shared: self.shared.clone(), subscription, buffered: self.buffered, take: self.take }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        if let Some(subscription) = &self.subscription {
+            let (idle_wakers, received_wakers, hook, _history) = {
+                let mut state = self.shared.lock();
+                state.wakers[subscription.slot] = None;
+                state.seen[subscription.slot] = u64::MAX;
+                state.free_slots.push(subscription.slot);
+                let (idle_wakers, history) = if state.receiver_count() == 0 {
+                    // Like in `Sender::has_receivers`, nobody is left to
+                    // receive the stored messages, unless the channel is
+                    // closed. Dropping them releases references to the values
+                    // early. They are dropped after unlocking.
+                    let history =
+                        if state.closed { VecDeque::new() } else { mem::take(&mut state.history) };
+                    (mem::take(&mut state.idle_wakers), history)
+                } else {
+                    (Vec::new(), VecDeque::new())
+                };
+                let hook = state.instrumenter.hook().map(|hook| (hook, state.receiver_count()));
+                (idle_wakers, mem::take(&mut state.received_wakers), hook, history)
+            };
+            idle_wakers.into_iter().chain(received_wakers).for_each(Waker::wake);
+            if let Some((hook, receiver_count)) = hook {
+                hook.on_unsubscribe(receiver_count);
+            }
+        }
+    }
+}
+
+impl Subscription {
+    fn new<T>(shared: &Shared<T>) -> Self {
+        Self::new_at(shared, None)
+    }
+
+    /// Create a subscription that has seen the messages up to `seen`, or all
+    /// messages sent so far if it is `None`.
+    fn new_at<T>(shared: &Shared<T>, seen: Option<u64>) -> Self {
+        let (subscription, subscribe_wakers, hook) = {
+            let mut state = shared.lock();
+            let seen = seen.unwrap_or(state.seq);
+            let slot = match state.free_slots.pop() {
+                Some(slot) => {
+                    state.seen[slot] = seen;
+                    slot
+                }
+                None => {
+                    state.wakers.push(None);
+                    state.seen.push(seen);
+                    state.wakers.len() - 1
+                }
+            };
+            let hook = state.instrumenter.hook().map(|hook| (hook, state.receiver_count()));
+            (Self { slot, seen }, mem::take(&mut state.subscribe_wakers), hook)
+        };
+        subscribe_wakers.into_iter().for_each(Waker::wake);
+        if let Some((hook, receiver_count)) = hook {
+            hook.on_subscribe(receiver_count);
+        }
+        subscription
+    }
+}
@@ -0,0 +1,50 @@
+use core::{error::Error, fmt};
+
+/// An error that can occur when subscribing to an
+/// [`Observable`][crate::Observable].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SubscribeError {
+    /// The observable already has the maximum number of subscribers it was
+    /// configured with.
+    TooManySubscribers {
+        /// The maximum number of subscribers.
+        max: usize,
+    },
+}
+
+impl fmt::Display for SubscribeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManySubscribers { max } => {
+                write!(f, "observable already has the maximum number of subscribers ({max})")
+            }
+        }
+    }
+}
+
+impl Error for SubscribeError {}
+
+/// An error that can occur when receiving values from a subscriber.
+///
+/// Regular subscribers never yield errors, but skip values they missed.
+/// This is for the subscribers that report such gaps instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SubscriberError {
+    /// The subscriber lagged too far behind, so updates were skipped.
+    Lagged {
+        /// The number of skipped updates.
+        skipped: u64,
+    },
+}
+
+impl fmt::Display for SubscriberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lagged { skipped } => write!(f, "subscriber lagged behind by {skipped} updates"),
+        }
+    }
+}
+
+impl Error for SubscriberError {}
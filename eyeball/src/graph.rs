@@ -0,0 +1,66 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static NODES: Mutex<BTreeMap<u64, NodeInfo>> = Mutex::new(BTreeMap::new());
+
+struct NodeInfo {
+    label: String,
+    source: Option<u64>,
+}
+
+/// A node in the global dependency graph, removed from it when dropped.
+#[derive(Debug)]
+pub(crate) struct Node {
+    id: u64,
+}
+
+impl Node {
+    pub(crate) fn new(label: String, source: Option<&Node>) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let info = NodeInfo { label, source: source.map(|node| node.id) };
+        NODES.lock().unwrap().insert(id, info);
+        Self { id }
+    }
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        NODES.lock().unwrap().remove(&self.id);
+    }
+}
+
+/// Dump the current graph of observables and the streams derived from them
+/// in the [DOT] format of Graphviz.
+///
+/// Every [`Observable`][crate::Observable] and every derived stream (for
+/// example from [`Observable::subscribe_map`][crate::Observable::subscribe_map])
+/// that is currently alive is a node, with edges pointing from sources to the
+/// streams derived from them.
+///
+/// This requires the `graph` Cargo feature.
+///
+/// [DOT]: https://graphviz.org/doc/info/lang.html
+pub fn dump_graph() -> String {
+    let nodes = NODES.lock().unwrap();
+
+    let mut out = String::from("digraph eyeball {\n");
+    for (id, info) in nodes.iter() {
+        let label = info.label.replace('\\', "\\\\").replace('"', "\\\"");
+        writeln!(out, "    n{id} [label=\"{label}\"];").unwrap();
+    }
+    for (id, info) in nodes.iter() {
+        // The source may already have been dropped
+        if let Some(source) = info.source.filter(|source| nodes.contains_key(source)) {
+            writeln!(out, "    n{source} -> n{id};").unwrap();
+        }
+    }
+    out.push_str("}\n");
+    out
+}
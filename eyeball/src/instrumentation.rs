@@ -0,0 +1,103 @@
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+
+/// Callbacks for collecting metrics about observables.
+///
+/// Install an implementation for a single `Observable` with
+/// [`Observable::with_instrumentation`][crate::Observable::with_instrumentation],
+/// or for all observables that don't have their own with
+/// `set_global_instrumentation` (which requires the `std` feature). This allows exporting the update rate or
+/// the number of subscribers of observables to a metrics system.
+///
+/// Every callback receives the name of the observable, if it was created with
+/// [`Observable::with_name`][crate::Observable::with_name]. The callbacks are
+/// called synchronously from the code that caused the event, but without
+/// holding any locks, so they should be cheap, like incrementing a counter.
+/// All of them do nothing by default.
+pub trait Instrumentation: Send + Sync {
+    /// Called after an update was broadcast to `subscriber_count`
+    /// subscribers.
+    ///
+    /// Updates are not broadcast if there are no subscribers, so this isn't
+    /// called for those.
+    fn on_update(&self, name: Option<&'static str>, subscriber_count: usize) {
+        let _ = (name, subscriber_count);
+    }
+
+    /// Called after a subscriber was created, with the new number of
+    /// subscribers.
+    ///
+    /// [Lazy subscribers][crate::Observable::subscribe_lazy] are only counted
+    /// once they are polled for the first time.
+    fn on_subscribe(&self, name: Option<&'static str>, subscriber_count: usize) {
+        let _ = (name, subscriber_count);
+    }
+
+    /// Called after a subscriber was dropped, with the new number of
+    /// subscribers.
+    fn on_unsubscribe(&self, name: Option<&'static str>, subscriber_count: usize) {
+        let _ = (name, subscriber_count);
+    }
+
+    /// Called when a subscriber skipped `skipped` updates because newer ones
+    /// were sent before it was polled.
+    fn on_lag(&self, name: Option<&'static str>, skipped: u64) {
+        let _ = (name, skipped);
+    }
+}
+
+#[cfg(feature = "std")]
+static GLOBAL: OnceLock<Arc<dyn Instrumentation>> = OnceLock::new();
+
+/// Install `instrumentation` for all observables that don't have their own.
+///
+/// This can only be done once. Returns `false` if a global instrumentation
+/// was already installed, in which case it is kept.
+#[cfg(feature = "std")]
+pub fn set_global_instrumentation(instrumentation: Arc<dyn Instrumentation>) -> bool {
+    GLOBAL.set(instrumentation).is_ok()
+}
+
+/// The instrumentation settings of a channel.
+#[derive(Clone, Default)]
+pub(crate) struct Instrumenter {
+    pub(crate) name: Option<&'static str>,
+    pub(crate) local: Option<Arc<dyn Instrumentation>>,
+}
+
+impl Instrumenter {
+    /// Get the instrumentation to notify of an event, if any.
+    pub(crate) fn hook(&self) -> Option<Hook> {
+        #[cfg(feature = "std")]
+        let inner = self.local.as_ref().or_else(|| GLOBAL.get())?;
+        #[cfg(not(feature = "std"))]
+        let inner = self.local.as_ref()?;
+        Some(Hook { inner: inner.clone(), name: self.name })
+    }
+}
+
+/// An instrumentation to notify of an event, obtained while the channel is
+/// locked and called after unlocking it.
+pub(crate) struct Hook {
+    inner: Arc<dyn Instrumentation>,
+    name: Option<&'static str>,
+}
+
+impl Hook {
+    pub(crate) fn on_update(&self, subscriber_count: usize) {
+        self.inner.on_update(self.name, subscriber_count);
+    }
+
+    pub(crate) fn on_subscribe(&self, subscriber_count: usize) {
+        self.inner.on_subscribe(self.name, subscriber_count);
+    }
+
+    pub(crate) fn on_unsubscribe(&self, subscriber_count: usize) {
+        self.inner.on_unsubscribe(self.name, subscriber_count);
+    }
+
+    pub(crate) fn on_lag(&self, skipped: u64) {
+        self.inner.on_lag(self.name, skipped);
+    }
+}
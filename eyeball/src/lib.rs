@@ -1,11 +1,155 @@
 //! Add observability to your Rust types!
 //!
+//! Subscribers are plain [`Stream`][futures_core::Stream]s that don't depend
+//! on a particular async runtime, so they can be driven by any executor (for
+//! example async-std or smol) without a tokio runtime being present. Unless
+//! the `tokio-time` feature is enabled, tokio isn't a dependency at all.
+//!
+//! This also makes the crate usable on `wasm32-unknown-unknown`. That target
+//! has no clock and can't block the main thread, so
+//! [`Observable::last_changed`] always returns `None` there, and
+//! `Subscriber::timestamped`, `Subscriber::next_blocking` and
+//! `Subscriber::wait_until_blocking` are not available.
+//!
+//! Without the default `std` feature, the crate is `no_std` and only requires
+//! `alloc`, so [`Observable`] and [`Subscriber`] can be used in embedded
+//! environments. The state that an observable shares with its subscribers is
+//! protected by a spin lock then, so an observable must not be used from an
+//! interrupt handler that can preempt other code using the same observable.
+//! The target also needs atomic compare-and-swap operations, like `Arc` does.
+//!
 //! Cargo features:
 //!
+//! - `std` (enabled by default): Use the locks of the standard library, and
+//!   enable everything that needs a clock or threads, like
+//!   [`Observable::last_changed`] and [`set_global_instrumentation`]. All
+//!   other features except `log` and `futures-sink` require it
 //! - `tracing`: Emit [tracing] events when updates are sent out
+//! - `log`: Emit [log] records when updates are sent out, like `tracing`
+//! - `arc-swap`: Enable [`SwapObservable`], whose value can be read without
+//!   taking a lock
+//! - `async-lock`: Enable [`AsyncSharedObservable`], a variant of
+//!   [`SharedObservable`] whose lock can be awaited
+//! - `futures-sink`: Implement `Sink` for [`ObservableSink`], to feed an
+//!   observable from a stream with `StreamExt::forward`
+//! - `graph`: Record the dependency graph of observables and derived streams,
+//!   see [`dump_graph`]
+//! - `im`: Add helper functions for observables of [`im::Vector`] and
+//!   [`im::HashMap`], whose clones are cheap because of structural sharing
+//! - `parking_lot`: Use `parking_lot::RwLock` instead of `std::sync::RwLock`
+//!   for [`SharedObservable`]
+//! - `registry`: Keep track of all observables created with
+//!   [`Observable::with_name`], see [`registry::snapshot`]
+//! - `serde`: Implement `Serialize` and `Deserialize` for [`Observable`] by
+//!   (de)serializing only the inner value
+//! - `test-util`: Enable the [`test_util`] module with helpers for testing code
+//!   that uses eyeball
+//! - `tokio-interop`: Enable [`Observable::from_watch`] and
+//!   [`Observable::subscribe_watch`] for converting from and to
+//!   `tokio::sync::watch` channels
+//! - `tokio-time`: Enable [`Subscriber::next_timeout`] and
+//!   [`Observable::with_min_interval`], which require a tokio runtime
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_debug_implementations, missing_docs)]
 #![allow(clippy::new_without_default)]
 
+extern crate alloc;
+
+/// Emit a warning through `tracing` and / or `log`, depending on the enabled
+/// Cargo features.
+macro_rules! warn_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::warn!($($arg)*);
+        #[cfg(feature = "log")]
+        log::warn!($($arg)*);
+    };
+}
+
+pub mod adapters;
+mod any;
+mod arc;
+#[cfg(feature = "async-lock")]
+mod async_shared;
+mod atomic;
+mod backpressure;
+mod channel;
+mod error;
+#[cfg(feature = "graph")]
+mod graph;
+mod instrumentation;
+mod local;
+mod lock;
+mod maybe;
+mod notifier;
 mod observable;
+#[cfg(feature = "im")]
+mod persistent;
+pub mod prelude;
+#[cfg(feature = "registry")]
+pub mod registry;
+mod shared;
+#[cfg(feature = "futures-sink")]
+mod sink;
+mod strategy;
+#[cfg(feature = "arc-swap")]
+mod swap;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
+pub use any::{AnyObservable, DynSubscriber};
+pub use arc::ArcObservable;
+#[cfg(feature = "async-lock")]
+pub use async_shared::{AsyncSharedObservable, AsyncSharedReadGuard, AsyncSharedWriteGuard};
+pub use atomic::{AtomicInteger, AtomicObservable, AtomicValue};
+pub use backpressure::{BackpressuredObservable, BackpressuredSubscriber};
+pub use error::{SubscribeError, SubscriberError};
+#[cfg(feature = "graph")]
+pub use graph::dump_graph;
+#[cfg(feature = "std")]
+pub use instrumentation::set_global_instrumentation;
+pub use instrumentation::Instrumentation;
+pub use local::{LocalObservable, LocalSubscriber};
+pub use maybe::{MaybeObservable, MaybeSubscriber};
+pub use notifier::{Notifier, NotifierSubscriber};
+pub use observable::{
+    ApproxEq, DriverHandle, FrozenPolicy, MappedRef, Observable, ObservableMut,
+    ObservableReadGuard, ObservableWriteGuard, ObserveEq, SendErrorPolicy, SilenceGuard,
+    Subscriber,
+};
+pub use shared::{SharedObservable, SharedReadGuard, WeakObservable};
+#[cfg(feature = "futures-sink")]
+pub use sink::ObservableSink;
+pub use strategy::{AlwaysNotify, ChangeStrategy, NotifyOnEq, NotifyOnHash};
+#[cfg(feature = "arc-swap")]
+pub use swap::{SwapObservable, SwapReadGuard};
 
-pub use observable::{Observable, Subscriber};
+/// Turn a [`Subscriber`] of an enum into a stream of the payloads of one of its
+/// tuple variants.
+///
+/// `variant_subscriber!(subscriber, Enum::Variant)` is shorthand for
+/// [`subscriber.filter_map`][Subscriber::filter_map] with a closure that
+/// matches `Enum::Variant(payload)` and skips all other variants.
+///
+/// ```
+/// use eyeball::{variant_subscriber, Observable};
+///
+/// #[derive(Clone, Debug)]
+/// enum Connection {
+///     Disconnected,
+///     Connected(u32),
+/// }
+///
+/// let ob = Observable::new(Connection::Disconnected);
+/// let sessions = variant_subscriber!(Observable::subscribe(&ob), Connection::Connected);
+/// ```
+#[macro_export]
+macro_rules! variant_subscriber {
+    ($subscriber:expr, $variant:path $(,)?) => {
+        $crate::Subscriber::filter_map($subscriber, |value| match value {
+            $variant(payload) => ::core::option::Option::Some(payload),
+            #[allow(unreachable_patterns)]
+            _ => ::core::option::Option::None,
+        })
+    };
+}
@@ -0,0 +1,163 @@
+use alloc::{rc::Rc, vec::Vec};
+use core::{
+    cell::RefCell,
+    fmt, mem, ops,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use futures_core::Stream;
+
+/// A single-threaded variant of [`Observable`][crate::Observable].
+///
+/// Unlike `Observable`, this does not require `T: Send` or `T: 'static`, which
+/// makes it usable for state that can't leave the thread it was created on,
+/// such as GUI objects. Neither the `LocalObservable` nor its subscribers can
+/// be sent to other threads.
+///
+/// Like `Observable`, it dereferences to `T` and uses associated functions
+/// instead of methods.
+pub struct LocalObservable<T> {
+    value: T,
+    shared: Rc<RefCell<LocalState<T>>>,
+}
+
+impl<T: Clone> LocalObservable<T> {
+    /// Create a new `LocalObservable` with the given initial value.
+    pub fn new(value: T) -> Self {
+        let state = LocalState { latest: None, version: 0, wakers: Vec::new(), closed: false };
+        Self { value, shared: Rc::new(RefCell::new(state)) }
+    }
+
+    /// Obtain a new subscriber.
+    pub fn subscribe(this: &Self) -> LocalSubscriber<T> {
+        let observed_version = this.shared.borrow().version;
+        LocalSubscriber { shared: this.shared.clone(), observed_version }
+    }
+
+    /// Get a reference to the inner value.
+    pub fn get(this: &Self) -> &T {
+        &this.value
+    }
+
+    /// Set the inner value to the given `value` and notify subscribers.
+    pub fn set(this: &mut Self, value: T) {
+        Self::replace(this, value);
+    }
+
+    /// Set the inner value to the given `value`, notify subscribers and return
+    /// the previous value.
+    pub fn replace(this: &mut Self, value: T) -> T {
+        let result = mem::replace(&mut this.value, value);
+        Self::broadcast_update(this);
+        result
+    }
+
+    /// Update the inner value and notify subscribers.
+    pub fn update(this: &mut Self, f: impl FnOnce(&mut T)) {
+        f(&mut this.value);
+        Self::broadcast_update(this);
+    }
+
+    /// Update the inner value and notify subscribers if the updated value does
+    /// not equal the previous value.
+    pub fn update_eq(this: &mut Self, f: impl FnOnce(&mut T))
+    where
+        T: PartialEq,
+    {
+        let prev = this.value.clone();
+        f(&mut this.value);
+        if this.value != prev {
+            Self::broadcast_update(this);
+        }
+    }
+
+    fn broadcast_update(this: &mut Self) {
+        let mut state = this.shared.borrow_mut();
+        state.version += 1;
+        // Only the observable itself holds a reference if there are no
+        // subscribers, in which case there is no need to clone the value
+        if Rc::strong_count(&this.shared) > 1 {
+            state.latest = Some(this.value.clone());
+            state.wake_all();
+        }
+    }
+}
+
+impl<T> ops::Deref for LocalObservable<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> Drop for LocalObservable<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.borrow_mut();
+        state.closed = true;
+        state.wake_all();
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LocalObservable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalObservable").field("value", &self.value).finish_non_exhaustive()
+    }
+}
+
+struct LocalState<T> {
+    latest: Option<T>,
+    version: u64,
+    wakers: Vec<Waker>,
+    closed: bool,
+}
+
+impl<T> LocalState<T> {
+    fn wake_all(&mut self) {
+        for waker in self.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+/// A subscriber for updates of a [`LocalObservable`].
+///
+/// Like [`Subscriber`][crate::Subscriber], it only yields the latest value
+/// when multiple updates happened since it was last polled.
+pub struct LocalSubscriber<T> {
+    shared: Rc<RefCell<LocalState<T>>>,
+    observed_version: u64,
+}
+
+impl<T: Clone> Stream for LocalSubscriber<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let mut state = this.shared.borrow_mut();
+        if state.version > this.observed_version {
+            if let Some(value) = &state.latest {
+                this.observed_version = state.version;
+                return Poll::Ready(Some(value.clone()));
+            }
+        }
+
+        if state.closed {
+            return Poll::Ready(None);
+        }
+
+        if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+impl<T> fmt::Debug for LocalSubscriber<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalSubscriber")
+            .field("observed_version", &self.observed_version)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,333 @@
+//! The locks used by this crate.
+//!
+//! With the `std` feature, these wrap the locks from `std::sync` (or
+//! `parking_lot::RwLock` with the `parking_lot` feature). Without it, they are
+//! spin locks, so the core types can be used in `no_std` environments. None
+//! of them poison: a panic while a lock is held leaves the wrapped value
+//! usable, with whatever value it had at that point.
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::sync::{self, PoisonError};
+
+#[cfg(feature = "std")]
+pub(crate) type MutexGuard<'a, T> = sync::MutexGuard<'a, T>;
+#[cfg(not(feature = "std"))]
+pub(crate) use self::spin::MutexGuard;
+
+#[cfg(all(feature = "std", not(feature = "parking_lot")))]
+pub(crate) type RwLockReadGuard<'a, T> = sync::RwLockReadGuard<'a, T>;
+#[cfg(all(feature = "std", not(feature = "parking_lot")))]
+pub(crate) type RwLockWriteGuard<'a, T> = sync::RwLockWriteGuard<'a, T>;
+#[cfg(feature = "parking_lot")]
+pub(crate) type RwLockReadGuard<'a, T> = parking_lot::RwLockReadGuard<'a, T>;
+#[cfg(feature = "parking_lot")]
+pub(crate) type RwLockWriteGuard<'a, T> = parking_lot::RwLockWriteGuard<'a, T>;
+#[cfg(not(feature = "std"))]
+pub(crate) use self::spin::{RwLockReadGuard, RwLockWriteGuard};
+
+/// The lock behind the channel between an observable and its subscribers.
+pub(crate) struct Mutex<T> {
+    #[cfg(feature = "std")]
+    inner: sync::Mutex<T>,
+    #[cfg(not(feature = "std"))]
+    inner: spin::Mutex<T>,
+}
+
+impl<T> Mutex<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        #[cfg(feature = "std")]
+        return Self { inner: sync::Mutex::new(value) };
+        #[cfg(not(feature = "std"))]
+        return Self { inner: spin::Mutex::new(value) };
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
+        #[cfg(feature = "std")]
+        return self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        #[cfg(not(feature = "std"))]
+        return self.inner.lock();
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        #[cfg(feature = "std")]
+        return self.inner.get_mut().unwrap_or_else(PoisonError::into_inner);
+        #[cfg(not(feature = "std"))]
+        return self.inner.get_mut();
+    }
+}
+
+// Like `std::sync::Mutex`, so that the lock implementation doesn't change the
+// auto traits of `Observable`
+#[cfg(not(feature = "std"))]
+impl<T> core::panic::UnwindSafe for Mutex<T> {}
+#[cfg(not(feature = "std"))]
+impl<T> core::panic::RefUnwindSafe for Mutex<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+/// The lock behind [`SharedObservable`][crate::SharedObservable].
+///
+/// All locking of shared observables goes through this type, so the lock
+/// implementation can be swapped out in one place: it wraps
+/// `std::sync::RwLock` by default, and `parking_lot::RwLock` if the
+/// `parking_lot` feature is enabled.
+pub(crate) struct RwLock<T> {
+    #[cfg(all(feature = "std", not(feature = "parking_lot")))]
+    inner: sync::RwLock<T>,
+    #[cfg(feature = "parking_lot")]
+    inner: parking_lot::RwLock<T>,
+    #[cfg(not(feature = "std"))]
+    inner: spin::RwLock<T>,
+}
+
+#[cfg(all(feature = "std", not(feature = "parking_lot")))]
+impl<T> RwLock<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Self { inner: sync::RwLock::new(value) }
+    }
+
+    pub(crate) fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(any(feature = "parking_lot", not(feature = "std")))]
+impl<T> RwLock<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        #[cfg(feature = "parking_lot")]
+        return Self { inner: parking_lot::const_rwlock(value) };
+        #[cfg(not(feature = "std"))]
+        return Self { inner: spin::RwLock::new(value) };
+    }
+
+    pub(crate) fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.inner.read()
+    }
+
+    pub(crate) fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.inner.write()
+    }
+}
+
+// Like `std::sync::RwLock`, so that the lock implementation doesn't change the
+// auto traits of `SharedObservable`
+#[cfg(any(feature = "parking_lot", not(feature = "std")))]
+impl<T> core::panic::UnwindSafe for RwLock<T> {}
+#[cfg(any(feature = "parking_lot", not(feature = "std")))]
+impl<T> core::panic::RefUnwindSafe for RwLock<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+/// Spin locks for `no_std` environments.
+///
+/// Waiting for one of these busy-loops instead of blocking the thread, which
+/// is fine for the short critical sections of this crate as long as the
+/// holder of the lock can't be preempted by the waiter. In particular, an
+/// observable must not be updated or subscribed to from an interrupt handler
+/// that can preempt code that also uses it.
+#[cfg(not(feature = "std"))]
+mod spin {
+    use core::{
+        cell::UnsafeCell,
+        fmt,
+        hint::spin_loop,
+        marker::PhantomData,
+        ops,
+        sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    };
+
+    pub(crate) struct Mutex<T> {
+        locked: AtomicBool,
+        value: UnsafeCell<T>,
+    }
+
+    // SAFETY: The value is only accessed through a guard, of which there is
+    // at most one at a time.
+    unsafe impl<T: Send> Send for Mutex<T> {}
+    unsafe impl<T: Send> Sync for Mutex<T> {}
+
+    impl<T> Mutex<T> {
+        pub(crate) const fn new(value: T) -> Self {
+            Self { locked: AtomicBool::new(false), value: UnsafeCell::new(value) }
+        }
+
+        pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
+            while self
+                .locked
+                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                while self.locked.load(Ordering::Relaxed) {
+                    spin_loop();
+                }
+            }
+            MutexGuard { lock: self, _not_send: PhantomData }
+        }
+
+        pub(crate) fn get_mut(&mut self) -> &mut T {
+            self.value.get_mut()
+        }
+    }
+
+    impl<T> fmt::Debug for Mutex<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Mutex").finish_non_exhaustive()
+        }
+    }
+
+    pub(crate) struct MutexGuard<'a, T> {
+        lock: &'a Mutex<T>,
+        /// Like `std::sync::MutexGuard`, this can't be sent to another thread.
+        _not_send: PhantomData<*const ()>,
+    }
+
+    // SAFETY: A shared reference to the guard only gives out shared references
+    // to the value.
+    unsafe impl<T: Sync> Sync for MutexGuard<'_, T> {}
+
+    impl<T> ops::Deref for MutexGuard<'_, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            // SAFETY: The lock is held.
+            unsafe { &*self.lock.value.get() }
+        }
+    }
+
+    impl<T> ops::DerefMut for MutexGuard<'_, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            // SAFETY: The lock is held.
+            unsafe { &mut *self.lock.value.get() }
+        }
+    }
+
+    impl<T> Drop for MutexGuard<'_, T> {
+        fn drop(&mut self) {
+            self.lock.locked.store(false, Ordering::Release);
+        }
+    }
+
+    /// The state of a [`RwLock`] that is held by a writer.
+    const WRITER: usize = usize::MAX;
+
+    pub(crate) struct RwLock<T> {
+        /// The number of readers holding the lock, or [`WRITER`].
+        state: AtomicUsize,
+        value: UnsafeCell<T>,
+    }
+
+    // SAFETY: Like `std::sync::RwLock`, readers on several threads share the
+    // value, so it must be `Sync` as well.
+    unsafe impl<T: Send> Send for RwLock<T> {}
+    unsafe impl<T: Send + Sync> Sync for RwLock<T> {}
+
+    impl<T> RwLock<T> {
+        pub(crate) const fn new(value: T) -> Self {
+            Self { state: AtomicUsize::new(0), value: UnsafeCell::new(value) }
+        }
+
+        pub(crate) fn read(&self) -> RwLockReadGuard<'_, T> {
+            loop {
+                let state = self.state.load(Ordering::Relaxed);
+                if state != WRITER
+                    && self
+                        .state
+                        .compare_exchange_weak(
+                            state,
+                            state + 1,
+                            Ordering::Acquire,
+                            Ordering::Relaxed,
+                        )
+                        .is_ok()
+                {
+                    return RwLockReadGuard { lock: self, _not_send: PhantomData };
+                }
+                spin_loop();
+            }
+        }
+
+        pub(crate) fn write(&self) -> RwLockWriteGuard<'_, T> {
+            while self
+                .state
+                .compare_exchange_weak(0, WRITER, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                spin_loop();
+            }
+            RwLockWriteGuard { lock: self, _not_send: PhantomData }
+        }
+    }
+
+    impl<T> fmt::Debug for RwLock<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("RwLock").finish_non_exhaustive()
+        }
+    }
+
+    pub(crate) struct RwLockReadGuard<'a, T> {
+        lock: &'a RwLock<T>,
+        _not_send: PhantomData<*const ()>,
+    }
+
+    // SAFETY: The guard only gives out shared references to the value.
+    unsafe impl<T: Sync> Sync for RwLockReadGuard<'_, T> {}
+
+    impl<T> ops::Deref for RwLockReadGuard<'_, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            // SAFETY: The lock is held for reading.
+            unsafe { &*self.lock.value.get() }
+        }
+    }
+
+    impl<T> Drop for RwLockReadGuard<'_, T> {
+        fn drop(&mut self) {
+            self.lock.state.fetch_sub(1, Ordering::Release);
+        }
+    }
+
+    pub(crate) struct RwLockWriteGuard<'a, T> {
+        lock: &'a RwLock<T>,
+        _not_send: PhantomData<*const ()>,
+    }
+
+    // SAFETY: A shared reference to the guard only gives out shared references
+    // to the value.
+    unsafe impl<T: Sync> Sync for RwLockWriteGuard<'_, T> {}
+
+    impl<T> ops::Deref for RwLockWriteGuard<'_, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            // SAFETY: The lock is held for writing.
+            unsafe { &*self.lock.value.get() }
+        }
+    }
+
+    impl<T> ops::DerefMut for RwLockWriteGuard<'_, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            // SAFETY: The lock is held for writing.
+            unsafe { &mut *self.lock.value.get() }
+        }
+    }
+
+    impl<T> Drop for RwLockWriteGuard<'_, T> {
+        fn drop(&mut self) {
+            self.lock.state.store(0, Ordering::Release);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use core::{
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_core::Stream;
+
+use crate::{Observable, Subscriber};
+
+/// An [`Observable`] that starts out without a value.
+///
+/// This models state that is initialized lazily or asynchronously, without
+/// having to come up with a sentinel initial value or deal with the `None` of
+/// an `Observable<Option<T>>` everywhere. Subscribers only yield actual values,
+/// so ones that are created before the value is first set receive it once it
+/// is.
+///
+/// Like `Observable`, it uses associated functions instead of methods.
+#[derive(Debug)]
+pub struct MaybeObservable<T> {
+    inner: Observable<Option<T>>,
+}
+
+impl<T: Clone + Send + 'static> MaybeObservable<T> {
+    /// Create a new `MaybeObservable` that does not have a value yet.
+    pub fn new() -> Self {
+        Self { inner: Observable::new(None) }
+    }
+
+    /// Obtain a new subscriber.
+    pub fn subscribe(this: &Self) -> MaybeSubscriber<T> {
+        MaybeSubscriber { inner: Observable::subscribe(&this.inner) }
+    }
+
+    /// Get a reference to the inner value, if it was set already.
+    pub fn get(this: &Self) -> Option<&T> {
+        this.inner.as_ref()
+    }
+
+    /// Set the inner value to the given `value` and notify subscribers.
+    pub fn set(this: &mut Self, value: T) {
+        Observable::set(&mut this.inner, Some(value));
+    }
+
+    /// Update the inner value and notify subscribers, if it was set already.
+    ///
+    /// Returns whether the value was set and thus updated.
+    pub fn update(this: &mut Self, f: impl FnOnce(&mut T)) -> bool {
+        if this.inner.is_none() {
+            return false;
+        }
+
+        Observable::update(&mut this.inner, |value| f(value.as_mut().unwrap()));
+        true
+    }
+}
+
+/// A subscriber for updates of a [`MaybeObservable`].
+#[derive(Debug)]
+pub struct MaybeSubscriber<T> {
+    inner: Subscriber<Option<T>>,
+}
+
+// No structural pinning
+impl<T> Unpin for MaybeSubscriber<T> {}
+
+impl<T: Clone + Send + 'static> Stream for MaybeSubscriber<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // The inner value is never reset to `None`, so the only updates are
+        // ones with an actual value
+        let value = ready!(Pin::new(&mut self.inner).poll_next(cx));
+        Poll::Ready(value.flatten())
+    }
+}
@@ -0,0 +1,100 @@
+use core::{
+    fmt,
+    future::poll_fn,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+
+use crate::{
+    channel::{Receiver, Sender},
+    observable::BroadcastMessage,
+};
+
+/// A notifier for events without a payload.
+///
+/// This is like an `Observable<()>`, minus the stored value: every call to
+/// [`notify`][Self::notify] wakes all subscribers. Like for [`Subscriber`]s,
+/// notifications that happen while a subscriber isn't polled are coalesced,
+/// and [`NotifierSubscriber`] yields the number of notifications since it
+/// last yielded an item rather than an item per notification.
+///
+/// Like `Observable`, it uses associated functions instead of methods.
+///
+/// [`Subscriber`]: crate::Subscriber
+pub struct Notifier {
+    sender: Sender<()>,
+}
+
+impl Notifier {
+    /// Create a new `Notifier`.
+    pub fn new() -> Self {
+        Self { sender: Sender::new() }
+    }
+
+    /// Obtain a new subscriber.
+    ///
+    /// It only yields notifications that happen after this call.
+    pub fn subscribe(this: &Self) -> NotifierSubscriber {
+        NotifierSubscriber { inner: this.sender.subscribe() }
+    }
+
+    /// Notify all subscribers.
+    pub fn notify(this: &Self) {
+        // Subscribers don't see versions, so there is no need to track them.
+        // Sending only fails if there are no subscribers, which is fine.
+        let _ = this.sender.send(BroadcastMessage { version: 0, value: () });
+    }
+
+    /// Get the number of subscribers.
+    pub fn subscriber_count(this: &Self) -> usize {
+        this.sender.receiver_count()
+    }
+}
+
+impl fmt::Debug for Notifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notifier")
+            .field("subscriber_count", &self.sender.receiver_count())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A subscriber for a [`Notifier`].
+///
+/// Yields the number of notifications that happened since the previous item,
+/// so an item greater than one means that notifications were missed because
+/// the subscriber wasn't polled in between. The stream ends once the
+/// `Notifier` is dropped.
+pub struct NotifierSubscriber {
+    inner: Receiver<()>,
+}
+
+impl NotifierSubscriber {
+    /// Wait for the next notification.
+    ///
+    /// Returns the number of notifications since the last call, or `None` if
+    /// the `Notifier` was dropped.
+    pub async fn next(&mut self) -> Option<u64> {
+        poll_fn(|cx| self.poll_next_count(cx)).await
+    }
+
+    fn poll_next_count(&mut self, cx: &mut Context<'_>) -> Poll<Option<u64>> {
+        self.inner.poll_recv(cx).map(|msg| msg.map(|(skipped, _)| skipped + 1))
+    }
+}
+
+impl Stream for NotifierSubscriber {
+    type Item = u64;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_next_count(cx)
+    }
+}
+
+impl fmt::Debug for NotifierSubscriber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NotifierSubscriber").finish_non_exhaustive()
+    }
+}
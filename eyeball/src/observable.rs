@@ -1,37 +1,797 @@
-use std::{
-    hash::{Hash, Hasher},
+use alloc::{boxed::Box, collections::VecDeque, string::String, sync::Arc, vec::Vec};
+use core::{
+    borrow::Borrow,
+    fmt,
+    future::{poll_fn, Future},
+    hash::Hash,
+    marker::PhantomData,
     mem, ops,
     pin::Pin,
-    task::{Context, Poll},
+    task::{ready, Context, Poll, Waker},
+};
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+#[cfg(feature = "tokio-time")]
+use std::time::Duration;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+use std::{
+    task::Wake,
+    thread::{self, Thread},
 };
 
-use futures_core::Stream;
-use tokio::sync::broadcast::{self, Sender};
-use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use futures_core::{FusedStream, Stream};
+
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+use crate::adapters::Timestamped;
+#[cfg(feature = "tokio-time")]
+use crate::adapters::{Debounce, Throttle};
+use crate::{
+    adapters::{
+        ChangePulses, Coalesce, CombineLatest, Dedup, DedupByKey, EnumerateLossy, Filter,
+        FilterMap, GatedBy, Inspect, LosslessSubscriber, Map, MergeBuilder, Next, OnEdge,
+        PeekableSubscriber, Reconnecting, SubscribeMap, SubscribeMapEq, Versioned, Windowed,
+        ZipLatest,
+    },
+    channel::{LatestGuard, Receiver, Sender},
+    lock::{Mutex, MutexGuard},
+    AlwaysNotify, ChangeStrategy, Instrumentation, NotifyOnEq, NotifyOnHash, SubscribeError,
+    SubscriberError,
+};
 
 /// A value whose changes will be broadcast to subscribers.
 ///
-/// `Observable<T>` dereferences to `T`, and does not have methods of its own to
-/// not clash with methods of the inner type. Instead, to interact with the
+/// `Observable<T>` dereferences to `T`, and does not have methods of its own
+/// to not clash with methods of the inner type. Instead, to interact with the
 /// `Observable` itself rather than the inner value, use its associated
 /// functions (e.g. `Observable::subscribe(observable)`).
-#[derive(Debug)]
+///
+/// If you prefer method call syntax for mutations,
+/// [`Observable::as_mut`][Self::as_mut] returns a handle that exposes them as
+/// regular methods.
 pub struct Observable<T> {
     value: T,
     sender: Sender<T>,
+    version: u64,
+    change_count: u64,
+    #[cfg(feature = "std")]
+    last_changed: Option<Instant>,
+    max_subscribers: Option<usize>,
+    dedup_eq: Option<fn(&T, &T) -> bool>,
+    drop_value: Option<T>,
+    name: Option<&'static str>,
+    kind: Option<&'static str>,
+    /// The kind of the last update, for logging.
+    update_kind: UpdateKind,
+    /// For logging the inner value, set by [`Observable::log_values`].
+    debug_value: Option<DebugFn<T>>,
+    send_error_policy: SendErrorPolicy,
+    frozen: bool,
+    frozen_policy: FrozenPolicy,
+    silenced: bool,
+    deferred: bool,
+    broadcast_pending: bool,
+    callbacks: Vec<UpdateCallback<T>>,
+    projections: Vec<Projection<T>>,
+    #[cfg(feature = "tokio-time")]
+    min_interval: Option<MinInterval<T>>,
+    #[cfg(feature = "graph")]
+    pub(crate) node: crate::graph::Node,
+    #[cfg(feature = "registry")]
+    registration: Option<crate::registry::Registration>,
+}
+
+type DebugFn<T> = fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result;
+
+type UpdateCallback<T> = Box<dyn FnMut(&Observable<T>) + Send + Sync>;
+
+/// Sends the projection of an update to the subscribers of
+/// [`Observable::subscribe_filter_map`], returning `false` once all of them
+/// were dropped.
+type Projection<T> = Box<dyn FnMut(&T, u64) -> bool + Send + Sync>;
+
+#[cfg(feature = "tokio-time")]
+struct MinInterval<T> {
+    interval: Duration,
+    state: Arc<std::sync::Mutex<MinIntervalState<T>>>,
+}
+
+#[cfg(feature = "tokio-time")]
+struct MinIntervalState<T> {
+    last_sent: Option<tokio::time::Instant>,
+    scheduled: Option<BroadcastMessage<T>>,
 }
 
 impl<T: Clone + Send + 'static> Observable<T> {
     /// Create a new `Observable` with the given initial value.
     pub fn new(value: T) -> Self {
-        let (sender, _) = broadcast::channel(1);
-        Self { value, sender }
+        Self {
+            value,
+            sender: Sender::new(),
+            version: 0,
+            change_count: 0,
+            #[cfg(feature = "std")]
+            last_changed: None,
+            max_subscribers: None,
+            dedup_eq: None,
+            drop_value: None,
+            name: None,
+            kind: None,
+            update_kind: UpdateKind::Update,
+            debug_value: None,
+            send_error_policy: SendErrorPolicy::default(),
+            frozen: false,
+            frozen_policy: FrozenPolicy::default(),
+            silenced: false,
+            deferred: false,
+            broadcast_pending: false,
+            callbacks: Vec::new(),
+            projections: Vec::new(),
+            #[cfg(feature = "tokio-time")]
+            min_interval: None,
+            #[cfg(feature = "graph")]
+            node: crate::graph::Node::new(
+                format!("Observable<{}>", std::any::type_name::<T>()),
+                None,
+            ),
+            #[cfg(feature = "registry")]
+            registration: None,
+        }
+    }
+
+    /// Create a new `Observable` with the given initial value that only
+    /// notifies subscribers of actual changes.
+    ///
+    /// For such an `Observable`, [`set`][Self::set],
+    /// [`replace`][Self::replace], [`update`][Self::update],
+    /// [`update_if`][Self::update_if] and [`swap`][Self::swap] compare the
+    /// updated value against the previous one and skip notifying subscribers
+    /// if they are equal. Note that this makes `update` and `update_if` clone
+    /// the previous value on every call, like [`update_eq`][Self::update_eq]
+    /// does.
+    pub fn new_deduped(value: T) -> Self
+    where
+        T: PartialEq,
+    {
+        let mut this = Self::new(value);
+        this.dedup_eq = Some(T::eq);
+        this
+    }
+
+    /// Create a new `Observable` with the given initial value that sends
+    /// `on_drop` to its subscribers when it is dropped.
+    ///
+    /// Subscribers receive `on_drop` as their last item, right before their
+    /// stream ends. This can be used to signal a graceful shutdown (e.g.
+    /// `Status::Disconnected`), rather than just ending the streams.
+    pub fn with_drop_value(value: T, on_drop: T) -> Self {
+        let mut this = Self::new(value);
+        this.drop_value = Some(on_drop);
+        this
+    }
+
+    /// Create a new `Observable` with the given initial value that notifies
+    /// subscribers at most once per `interval`.
+    ///
+    /// If an update happens less than `interval` after the last one that was
+    /// sent out, it is held back and sent once `interval` has passed, from a
+    /// task that is spawned on the current tokio runtime. Further updates
+    /// until then only replace the value that is held back, so subscribers
+    /// see only the latest one. This limits the rate of notifications for all
+    /// subscribers at once.
+    ///
+    /// If the `Observable` is dropped while an update is held back, that
+    /// update is discarded.
+    ///
+    /// Only the channel that regular subscribers receive from is rate-limited.
+    /// [`on_update`][Self::on_update] callbacks and subscribers obtained
+    /// through [`subscribe_filter_map`][Self::subscribe_filter_map] or
+    /// [`subscribe_field`][Self::subscribe_field] are notified directly, so
+    /// they still see every update right away.
+    ///
+    /// This requires the `tokio-time` Cargo feature. Updates must happen
+    /// within a tokio runtime with the time driver enabled.
+    #[cfg(feature = "tokio-time")]
+    pub fn with_min_interval(value: T, interval: Duration) -> Self {
+        let mut this = Self::new(value);
+        let state = MinIntervalState { last_sent: None, scheduled: None };
+        this.min_interval =
+            Some(MinInterval { interval, state: Arc::new(std::sync::Mutex::new(state)) });
+        this
+    }
+
+    /// Create a new `Observable` with the given initial value, along with a
+    /// subscriber for it.
+    ///
+    /// Like any other subscriber, the returned one does not yield the initial
+    /// value, only the updates that happen afterwards. Since it exists before
+    /// the `Observable` can be mutated though, it can't miss any of them.
+    pub fn new_with_subscriber(value: T) -> (Self, Subscriber<T>) {
+        let this = Self::new(value);
+        let subscriber = Self::subscribe(&this);
+        (this, subscriber)
+    }
+
+    /// Create a new `Observable` with the given name and initial value.
+    ///
+    /// The name is included in the [tracing] events emitted for its updates,
+    /// along with the version and the kind of update (`"set"`, `"replace"` or
+    /// `"update"`), to tell apart the events of different observables. Use
+    /// [`log_values`][Self::log_values] to also include the inner value. This
+    /// only has an effect with the `tracing` or `log` Cargo features.
+    ///
+    /// With the `registry` Cargo feature, the `Observable` is also listed by
+    /// [`registry::snapshot`][crate::registry::snapshot] until it is dropped.
+    ///
+    /// [tracing]: https://docs.rs/tracing
+    pub fn with_name(name: &'static str, value: T) -> Self {
+        let mut this = Self::new(value);
+        this.name = Some(name);
+        this.sender.set_name(name);
+        #[cfg(feature = "registry")]
+        {
+            this.registration = Some(crate::registry::Registration::new(name, &this.sender));
+        }
+        this
+    }
+
+    /// Create an `Observable` that is updated with the items of `stream`,
+    /// returning a subscriber for it.
+    ///
+    /// `spawn` is called with a future that owns the `Observable` and
+    /// [`set`][Self::set]s it to every item of `stream`. It must run that
+    /// future to completion, for example through `tokio::spawn`:
+    ///
+    /// ```
+    /// use eyeball::Observable;
+    /// # use tokio_stream::StreamExt as _;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let source = tokio_stream::iter([1, 2, 3]);
+    /// let mut subscriber = Observable::from_stream(0, source, |fut| {
+    ///     tokio::spawn(fut);
+    /// });
+    /// # while let Some(_) = subscriber.next().await {}
+    /// # }
+    /// ```
+    ///
+    /// Once `stream` ends, the future completes and drops the `Observable`, so
+    /// the streams of its subscribers end after they have received the last
+    /// value. The future also completes early once there are no subscribers
+    /// left, since no new ones could be created at that point.
+    pub fn from_stream<S>(
+        initial: T,
+        stream: S,
+        spawn: impl FnOnce(Pin<Box<dyn Future<Output = ()> + Send>>),
+    ) -> Subscriber<T>
+    where
+        S: Stream<Item = T> + Send + 'static,
+    {
+        Self::drive(initial, stream, Self::set, spawn).0
+    }
+
+    /// Get a [`Sink`][futures_sink::Sink] that sets this `Observable` to every
+    /// item sent into it.
+    ///
+    /// This requires the `futures-sink` Cargo feature.
+    #[cfg(feature = "futures-sink")]
+    pub fn sink(this: &mut Self) -> crate::ObservableSink<'_, T> {
+        crate::ObservableSink::new(this)
+    }
+
+    /// Like [`from_stream`][Self::from_stream], but also return a handle for
+    /// stopping the future early.
+    ///
+    /// Once [`DriverHandle::stop`] is called, the future completes the next
+    /// time it is polled, even if `stream` has not ended, which drops the
+    /// `Observable` and ends the streams of its subscribers. Dropping the
+    /// handle doesn't stop the future.
+    pub fn from_stream_with_handle<S>(
+        initial: T,
+        stream: S,
+        spawn: impl FnOnce(Pin<Box<dyn Future<Output = ()> + Send>>),
+    ) -> (Subscriber<T>, DriverHandle)
+    where
+        S: Stream<Item = T> + Send + 'static,
+    {
+        Self::drive(initial, stream, Self::set, spawn)
+    }
+
+    fn drive<S>(
+        initial: T,
+        stream: S,
+        mut set: impl FnMut(&mut Self, S::Item) + Send + 'static,
+        spawn: impl FnOnce(Pin<Box<dyn Future<Output = ()> + Send>>),
+    ) -> (Subscriber<T>, DriverHandle)
+    where
+        S: Stream + Send + 'static,
+    {
+        let (mut this, subscriber) = Self::new_with_subscriber(initial);
+        let handle = DriverHandle::new();
+        let stop = handle.clone();
+        let mut stream = Box::pin(stream);
+        spawn(Box::pin(async move {
+            let mut next = |cx: &mut Context<'_>| {
+                if stop.poll_stopped(cx).is_ready() {
+                    return Poll::Ready(None);
+                }
+                stream.as_mut().poll_next(cx)
+            };
+            while let Some(value) = poll_fn(&mut next).await {
+                set(&mut this, value);
+                if this.sender.receiver_count() == 0 {
+                    break;
+                }
+            }
+        }));
+        (subscriber, handle)
+    }
+
+    /// Create a new `Observable` with the given initial value that allows at
+    /// most `max` subscribers to be obtained through
+    /// [`try_subscribe`][Self::try_subscribe].
+    ///
+    /// [`subscribe`][Self::subscribe] and [`Subscriber::resubscribe`] ignore
+    /// the limit.
+    pub fn with_max_subscribers(value: T, max: usize) -> Self {
+        let mut this = Self::new(value);
+        this.max_subscribers = Some(max);
+        this
+    }
+
+    /// Create a new `Observable` with the given initial value that keeps the
+    /// last `capacity` updates for subscribers obtained through
+    /// [`subscribe_buffered`][Self::subscribe_buffered].
+    ///
+    /// Regular subscribers still only receive the latest value. Note that the
+    /// kept updates are clones of the inner value, so this holds on to up to
+    /// `capacity` additional values while there are subscribers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(value: T, capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be greater than zero");
+        let mut this = Self::new(value);
+        this.sender = Sender::with_capacity(capacity);
+        this
+    }
+
+    /// Set what should happen if broadcasting an update to subscribers fails.
+    ///
+    /// Sending can only fail if all subscribers are dropped between checking
+    /// whether there are any and actually sending the update, so this is
+    /// mostly useful to catch unexpected behavior in tests. The default is
+    /// [`SendErrorPolicy::Ignore`].
+    pub fn on_send_error(mut this: Self, policy: SendErrorPolicy) -> Self {
+        this.send_error_policy = policy;
+        this
+    }
+
+    /// Set what should happen if the `Observable` is mutated after it was
+    /// [frozen][Self::freeze].
+    ///
+    /// The default is [`FrozenPolicy::Panic`] in debug builds and
+    /// [`FrozenPolicy::Ignore`] in release builds.
+    pub fn on_frozen_mutation(mut this: Self, policy: FrozenPolicy) -> Self {
+        this.frozen_policy = policy;
+        this
+    }
+
+    /// Tag the `Observable` with a `kind` that is included in the [tracing]
+    /// events emitted for its updates.
+    ///
+    /// Every event already includes the type name of `T` and the
+    /// [`change_count`][Self::change_count]; the kind can be used to tell
+    /// apart observables of the same type, e.g. `"connection_state"`. This
+    /// only has an effect with the `tracing` Cargo feature.
+    ///
+    /// [tracing]: https://docs.rs/tracing
+    pub fn tagged(mut this: Self, kind: &'static str) -> Self {
+        this.kind = Some(kind);
+        this
+    }
+
+    /// Report updates and subscriber changes of this `Observable` to
+    /// `instrumentation` instead of the global one.
+    ///
+    /// See [`Instrumentation`] for details.
+    pub fn with_instrumentation(this: Self, instrumentation: Arc<dyn Instrumentation>) -> Self {
+        this.sender.set_instrumentation(instrumentation);
+        this
+    }
+
+    /// Include the `Debug` representation of the inner value in the
+    /// [tracing] events emitted for its updates.
+    ///
+    /// This only has an effect with the `tracing` Cargo feature. Since the
+    /// value is formatted for every broadcast, this is best limited to small
+    /// values or enabled only for debugging.
+    ///
+    /// [tracing]: https://docs.rs/tracing
+    pub fn log_values(mut this: Self) -> Self
+    where
+        T: fmt::Debug,
+    {
+        this.debug_value = Some(<T as fmt::Debug>::fmt);
+        this
+    }
+
+    /// Register a callback that is called with the `Observable` after every
+    /// update that is broadcast.
+    ///
+    /// Callbacks run synchronously as part of the update, after subscribers
+    /// have been notified. They are not called for updates that are
+    /// suppressed, e.g. by [`silence`][Self::silence], and only once for all
+    /// of the updates that are broadcast together by [`flush`][Self::flush].
+    ///
+    /// Whether an update is broadcast never depends on the number of
+    /// subscribers: the update functions run their change detection (like the
+    /// comparison of [`update_eq`][Self::update_eq]) either way, and only
+    /// sending the new value to the channel is skipped if there are no
+    /// subscribers. Callbacks are called regardless, so side effects
+    /// implemented through them don't stop when the last subscriber is
+    /// dropped.
+    pub fn on_update(this: &mut Self, f: impl FnMut(&Observable<T>) + Send + Sync + 'static) {
+        this.callbacks.push(Box::new(f));
+    }
+
+    /// Register a `Waker` that is woken after every update that is broadcast,
+    /// without delivering any values.
+    ///
+    /// This is the minimal primitive for "re-read the state" notifications,
+    /// such as repaint requests of immediate-mode GUIs, that would otherwise
+    /// need a task consuming a [`Subscriber`] just to throw the values away.
+    /// The waker is called like an [`on_update`][Self::on_update] callback.
+    pub fn subscribe_waker(this: &mut Self, waker: Waker) {
+        Self::on_update(this, move |_| waker.wake_by_ref());
     }
 
     /// Obtain a new subscriber.
+    ///
+    /// The subscriber doesn't yield the current value, only updates that
+    /// happen after this call. Like a watch channel, it always yields the
+    /// latest value: if there are multiple updates between two polls, it
+    /// skips to the last one. Use
+    /// [`subscribe_with_current`][Self::subscribe_with_current] for a
+    /// subscriber whose first poll yields the current value.
+    ///
+    /// This only needs shared access, so it can also be called from within
+    /// [`on_update`][Self::on_update] callbacks. Subscribers created there see
+    /// all updates after the current one.
     pub fn subscribe(this: &Self) -> Subscriber<T> {
-        let rx = this.sender.subscribe();
-        Subscriber::new(BroadcastStream::new(rx))
+        Subscriber::new(&this.sender)
+    }
+
+    /// Obtain a new subscriber that yields the current value first.
+    ///
+    /// Because the subscription is created at the same time the current value
+    /// is read, no update can be missed in between, so consumers can
+    /// initialize their state from the first item and then react to
+    /// updates. If there are updates before the subscriber is first polled, it
+    /// still yields the value it was created with first, and then the latest
+    /// update.
+    pub fn subscribe_with_current(this: &Self) -> Subscriber<T> {
+        let mut subscriber = Self::subscribe(this);
+        subscriber
+            .buffered
+            .push_back(BroadcastMessage { version: this.version, value: this.value.clone() });
+        subscriber
+    }
+
+    /// Obtain a new subscriber that only yields values with a
+    /// [`version`][Self::version] of at least `version`.
+    ///
+    /// This is useful for read-your-writes consistency: a writer can hand out
+    /// the version of its update as a baseline, and the subscriber skips all
+    /// older values, even if it was created before that update.
+    ///
+    /// Like every subscriber, it only yields updates that happen after it was
+    /// created, so if `version` is already in the past, this is the same as
+    /// [`subscribe`][Self::subscribe] and the current value is **not**
+    /// yielded, even if it has exactly that version. Read it with
+    /// [`get`][Self::get] instead.
+    pub fn subscribe_after(this: &Self, version: u64) -> Subscriber<T> {
+        let mut subscriber = Self::subscribe(this);
+        subscriber.min_version = version;
+        subscriber
+    }
+
+    /// Obtain a new subscriber that reports skipped updates instead of
+    /// silently skipping them.
+    ///
+    /// A regular subscriber that lags behind only yields the latest value.
+    /// The returned one yields a [`SubscriberError::Lagged`] before that
+    /// value instead, so consumers that can't tolerate gaps can detect them,
+    /// e.g. to re-synchronize.
+    pub fn subscribe_lossless(this: &Self) -> LosslessSubscriber<T> {
+        LosslessSubscriber::new(Self::subscribe(this))
+    }
+
+    /// Obtain a new subscriber that receives every update in order, as long
+    /// as it doesn't fall behind by more than the capacity the `Observable`
+    /// was created with.
+    ///
+    /// Like [`subscribe_lossless`][Self::subscribe_lossless], it yields a
+    /// [`SubscriberError::Lagged`] if updates were skipped, which only happens
+    /// once more than the capacity updates were made since the last one it
+    /// received. For an `Observable` that was not created with
+    /// [`with_capacity`][Self::with_capacity], the capacity is one, so this is
+    /// the same as `subscribe_lossless`.
+    pub fn subscribe_buffered(this: &Self) -> LosslessSubscriber<T> {
+        LosslessSubscriber::new(Subscriber::from_receiver(this.sender.subscribe_buffered()))
+    }
+
+    /// Obtain a new subscriber that only starts receiving updates once it is
+    /// polled for the first time.
+    ///
+    /// Until then, it doesn't take up any resources of the `Observable`, which
+    /// makes this useful for subscribers that are created speculatively and
+    /// may never be polled. Updates that happen before the first poll are
+    /// **not** received, and lazy subscribers that were not polled yet don't
+    /// count towards the limit set with
+    /// [`with_max_subscribers`][Self::with_max_subscribers].
+    pub fn subscribe_lazy(this: &Self) -> Subscriber<T> {
+        Subscriber::new_lazy(&this.sender)
+    }
+
+    /// Obtain a new subscriber, unless the maximum number of subscribers set
+    /// with [`with_max_subscribers`][Self::with_max_subscribers] has been
+    /// reached.
+    ///
+    /// Note that the number of subscribers is checked before subscribing, so
+    /// if this is called concurrently from multiple threads, or while other
+    /// code calls [`subscribe`][Self::subscribe], the limit can be exceeded.
+    pub fn try_subscribe(this: &Self) -> Result<Subscriber<T>, SubscribeError> {
+        match this.max_subscribers {
+            Some(max) if this.sender.receiver_count() >= max => {
+                Err(SubscribeError::TooManySubscribers { max })
+            }
+            _ => Ok(Self::subscribe(this)),
+        }
+    }
+
+    /// Obtain a stream of projections of the inner value.
+    ///
+    /// The stream first yields `f` applied to the current value, and then `f`
+    /// applied to every update. Because the subscription is created at the
+    /// same time the current value is read, no update can be missed in
+    /// between.
+    ///
+    /// This yields a new item for every update, even if the projection didn't
+    /// change. Use [`subscribe_map_eq`][Self::subscribe_map_eq] to skip items
+    /// that are equal to the previous one.
+    pub fn subscribe_map<U, F>(this: &Self, f: F) -> SubscribeMap<T, U, F>
+    where
+        F: FnMut(&T) -> U,
+    {
+        SubscribeMap::new(this, f)
+    }
+
+    /// Obtain a stream of projections of the inner value that only yields
+    /// items when the projection changes.
+    ///
+    /// Like [`subscribe_map`][Self::subscribe_map], but updates that result in
+    /// a projection equal to the previously yielded one are skipped.
+    pub fn subscribe_map_eq<U, F>(this: &Self, f: F) -> SubscribeMapEq<T, U, F>
+    where
+        U: Clone + PartialEq,
+        F: FnMut(&T) -> U,
+    {
+        SubscribeMapEq::new(this, f)
+    }
+
+    /// Obtain a subscriber for projections of the inner value, skipping
+    /// updates for which `f` returns `None`.
+    ///
+    /// Unlike [`Subscriber::filter_map`], `f` is applied by the `Observable`
+    /// as part of every update that is broadcast, so only the projected
+    /// values of the relevant updates are sent to the subscriber. This means
+    /// more work on the producer side, but for a subscriber that is only
+    /// interested in a small part of `T` or in few of the updates, it avoids
+    /// cloning the whole value for every update just to throw it away.
+    ///
+    /// Like [`on_update`][Self::on_update], this needs exclusive access to
+    /// register `f`. [`Subscriber::resubscribe`] can be used to obtain more
+    /// subscribers for the same projection. `f` is dropped once all of them
+    /// were dropped.
+    pub fn subscribe_filter_map<U, F>(this: &mut Self, mut f: F) -> Subscriber<U>
+    where
+        U: Clone + Send + 'static,
+        F: FnMut(&T) -> Option<U> + Send + Sync + 'static,
+    {
+        let sender = Sender::new();
+        let subscriber = Subscriber::new(&sender);
+        this.projections.push(Box::new(move |value, version| {
+            if sender.receiver_count() == 0 {
+                return false;
+            }
+            if let Some(value) = f(value) {
+                // Sending only fails if all subscribers were dropped in the
+                // meantime, in which case this is removed on the next update
+                let _ = sender.send(BroadcastMessage { version, value });
+            }
+            true
+        }));
+        subscriber
+    }
+
+    /// Obtain a subscriber for a field of the inner value.
+    ///
+    /// `f` is a "lens" that borrows the field from the value. Like with
+    /// [`subscribe_filter_map`][Self::subscribe_filter_map], it is applied by
+    /// the `Observable` as part of every update, so only the field is cloned
+    /// rather than the whole value. Updates that leave the field unchanged
+    /// compared to the previously sent one are skipped.
+    ///
+    /// ```
+    /// use eyeball::Observable;
+    ///
+    /// #[derive(Clone)]
+    /// struct State {
+    ///     connection: bool,
+    ///     unread: u32,
+    /// }
+    ///
+    /// let mut ob = Observable::new(State { connection: false, unread: 0 });
+    /// let mut connection = Observable::subscribe_field(&mut ob, |state| &state.connection);
+    /// ```
+    pub fn subscribe_field<U, F>(this: &mut Self, f: F) -> Subscriber<U>
+    where
+        U: Clone + PartialEq + Send + Sync + 'static,
+        F: Fn(&T) -> &U + Send + Sync + 'static,
+    {
+        let mut last = f(&this.value).clone();
+        Self::subscribe_filter_map(this, move |value| {
+            let field = f(value);
+            (*field != last).then(|| {
+                last = field.clone();
+                last.clone()
+            })
+        })
+    }
+
+    /// Derive a new observable value from this one, returning a subscriber for
+    /// it that only yields items when the derived value changes.
+    ///
+    /// `f` is applied to the current value and to every update. The result
+    /// becomes the value of a derived `Observable`, which notifies its
+    /// subscribers only if it does not equal the previous result, like with
+    /// [`set_eq`][Self::set_eq]. This avoids recomputations further
+    /// downstream when the relevant part of the source didn't change.
+    ///
+    /// Like for [`from_stream`][Self::from_stream], `spawn` must run the
+    /// given future to completion, which owns the derived `Observable` and
+    /// keeps it up to date. The future completes, dropping the derived
+    /// `Observable` and thus ending the streams of its subscribers, once this
+    /// `Observable` is dropped or there are no subscribers for the derived
+    /// value left.
+    pub fn derive_eq<U, F>(
+        this: &Self,
+        mut f: F,
+        spawn: impl FnOnce(Pin<Box<dyn Future<Output = ()> + Send>>),
+    ) -> Subscriber<U>
+    where
+        U: Clone + PartialEq + Send + 'static,
+        F: FnMut(&T) -> U + Send + 'static,
+    {
+        let initial = f(&this.value);
+        let source = Self::subscribe(this).filter_map(move |value| Some(f(&value)));
+        Observable::drive(
+            initial,
+            source,
+            |derived, value| {
+                Observable::set_eq(derived, value);
+            },
+            spawn,
+        )
+        .0
+    }
+
+    /// Derive a new observable value from this one and `other`, returning a
+    /// subscriber for it that only yields items when the derived value
+    /// changes.
+    ///
+    /// This is like [`derive_eq`][Self::derive_eq], except that `f` is
+    /// applied to the current values of both observables, and recomputes the
+    /// derived value whenever either of them is updated. The future passed to
+    /// `spawn` completes once both observables are dropped or there are no
+    /// subscribers for the derived value left. To derive from more than two
+    /// observables, combine their subscribers with
+    /// [`Subscriber::zip_latest`], map the combined values and pass the
+    /// resulting stream to [`from_stream`][Self::from_stream].
+    pub fn derive_eq2<U, V, F>(
+        this: &Self,
+        other: &Observable<U>,
+        mut f: F,
+        spawn: impl FnOnce(Pin<Box<dyn Future<Output = ()> + Send>>),
+    ) -> Subscriber<V>
+    where
+        U: Clone + Send + 'static,
+        V: Clone + PartialEq + Send + 'static,
+        F: FnMut(&T, &U) -> V + Send + 'static,
+    {
+        let initial = f(&this.value, &other.value);
+        // Starting out with the current values makes the combined stream yield
+        // for updates of either observable right away, rather than only once
+        // both have been updated
+        let source = Self::subscribe_with_current(this)
+            .zip_latest(Observable::subscribe_with_current(other));
+        Observable::drive(
+            initial,
+            source,
+            move |derived, (a, b)| {
+                Observable::set_eq(derived, f(&a, &b));
+            },
+            spawn,
+        )
+        .0
+    }
+
+    /// End the streams of all current subscribers.
+    ///
+    /// Subscribers obtained afterwards through [`subscribe`][Self::subscribe]
+    /// receive updates as usual, but subscribers obtained through
+    /// [`Subscriber::resubscribe`] on one of the disconnected subscribers end
+    /// immediately, since they refer to the closed channel. This also means
+    /// that [`is_same`][Self::is_same] no longer considers this `Observable`
+    /// the same as one it was the same as before.
+    ///
+    /// If the `Observable` was [closed][Self::close], it stays closed.
+    pub fn disconnect_subscribers(this: &mut Self) {
+        let closed = this.sender.is_closed();
+        this.sender = this.sender.renew();
+        if closed {
+            this.sender.close();
+        }
+        #[cfg(feature = "registry")]
+        if let Some(registration) = &this.registration {
+            registration.set_sender(&this.sender);
+        }
+    }
+
+    /// End the streams of all subscribers, as if the `Observable` was
+    /// dropped.
+    ///
+    /// Subscribers that haven't received the latest update yet still receive
+    /// it before their stream ends. The inner value can still be read and
+    /// modified afterwards, but updates are no longer broadcast, and new
+    /// subscribers' streams end immediately.
+    pub fn close(this: &mut Self) {
+        this.sender.close();
+    }
+
+    /// Whether [`close`][Self::close] was called on this `Observable`.
+    pub fn is_closed(this: &Self) -> bool {
+        this.sender.is_closed()
+    }
+
+    /// Wait until there is at least one subscriber.
+    ///
+    /// This completes immediately if there already is one. It is meant for
+    /// starting background work, such as refreshing the value periodically,
+    /// only once somebody is interested in the result. Like
+    /// [`wait_until_no_subscribers`][Self::wait_until_no_subscribers], the
+    /// result may be outdated by the time the caller acts on it.
+    pub async fn wait_for_subscriber(this: &Self) {
+        poll_fn(|cx| this.sender.poll_receivers(cx)).await;
+    }
+
+    /// Wait until all subscribers have been dropped.
+    ///
+    /// This completes immediately if there are no subscribers. It is meant for
+    /// sequencing the teardown of a producer after all of its consumers are
+    /// gone, without polling the number of subscribers in a loop.
+    ///
+    /// Note that nothing keeps new subscribers from being created after this
+    /// completed, for example by another task with shared access to the
+    /// `Observable` or by a [lazy subscriber][Self::subscribe_lazy] that is
+    /// polled for the first time (those are not counted before that). So
+    /// there may be subscribers again by the time the caller acts on the
+    /// result.
+    pub async fn wait_until_no_subscribers(this: &Self) {
+        poll_fn(|cx| this.sender.poll_no_receivers(cx)).await;
     }
 
     /// Get a reference to the inner value.
@@ -44,77 +804,1501 @@ impl<T: Clone + Send + 'static> Observable<T> {
         &this.value
     }
 
+    /// Get a guard that dereferences to the inner value.
+    ///
+    /// This is the same as [`get`][Self::get], except that it returns the same
+    /// guard type as [`Subscriber::read`], for code that works with both.
+    pub fn read(this: &Self) -> ObservableReadGuard<'_, T> {
+        ObservableReadGuard { inner: ReadGuardInner::Borrowed(&this.value), _not_send: PhantomData }
+    }
+
+    /// Get a reference to a part of the inner value.
+    ///
+    /// This is equivalent to calling `f` on the dereferenced `Observable`, but
+    /// makes it explicit that only part of the value is read.
+    ///
+    /// ```
+    /// use eyeball::Observable;
+    ///
+    /// #[derive(Clone)]
+    /// struct Settings {
+    ///     window: Window,
+    /// }
+    ///
+    /// #[derive(Clone)]
+    /// struct Window {
+    ///     title: String,
+    /// }
+    ///
+    /// let ob = Observable::new(Settings { window: Window { title: "eyeball".to_owned() } });
+    /// let title = Observable::project(&ob, |settings| &settings.window.title);
+    /// assert_eq!(title, "eyeball");
+    /// ```
+    pub fn project<U: ?Sized>(this: &Self, f: impl FnOnce(&T) -> &U) -> &U {
+        f(&this.value)
+    }
+
+    /// Get a guard that dereferences to a part of the inner value.
+    ///
+    /// Like [`project`][Self::project], but the returned [`MappedRef`] can be
+    /// passed around and projected further with [`MappedRef::map`], while the
+    /// `Observable` stays borrowed.
+    ///
+    /// ```
+    /// use eyeball::{MappedRef, Observable};
+    ///
+    /// let ob = Observable::new((1, ("nested".to_owned(), 2)));
+    /// let inner = Observable::map_ref(&ob, |(_, inner)| inner);
+    /// let text = MappedRef::map(inner, |(text, _)| text.as_str());
+    /// assert_eq!(&*text, "nested");
+    /// ```
+    pub fn map_ref<U: ?Sized>(this: &Self, f: impl FnOnce(&T) -> &U) -> MappedRef<'_, U> {
+        MappedRef { value: f(&this.value) }
+    }
+
+    /// Get the current version of the inner value.
+    ///
+    /// The version starts at `0` and is incremented every time subscribers are
+    /// notified of a change, whether or not there currently are any
+    /// subscribers.
+    pub fn version(this: &Self) -> u64 {
+        this.version
+    }
+
+    /// Get the number of times the inner value was changed.
+    ///
+    /// This counts every mutation that notifies subscribers or would do so if
+    /// notifications weren't suppressed, for example by
+    /// [`silence`][Self::silence]. Mutations that were detected to not change
+    /// the value, like no-op [`update_eq`][Self::update_eq]s, are not counted.
+    /// Unlike the [`version`][Self::version], this is also incremented for
+    /// suppressed notifications.
+    pub fn change_count(this: &Self) -> u64 {
+        this.change_count
+    }
+
+    /// Get the time of the last change of the inner value, if there was one.
+    ///
+    /// A change is anything counted by [`change_count`][Self::change_count].
+    /// This is always `None` on targets without a clock, like
+    /// `wasm32-unknown-unknown`.
+    ///
+    /// This requires the `std` Cargo feature.
+    #[cfg(feature = "std")]
+    pub fn last_changed(this: &Self) -> Option<Instant> {
+        this.last_changed
+    }
+
+    /// Get the inner value and its version if it changed since `last`.
+    ///
+    /// This is a polling alternative to subscribing that is convenient for
+    /// frame loops such as immediate-mode UIs: store the version returned by
+    /// this function (or [`version`][Self::version]) and pass it back in on
+    /// the next frame.
+    pub fn changed_since(this: &Self, last: u64) -> Option<(&T, u64)> {
+        (this.version != last).then_some((&this.value, this.version))
+    }
+
+    /// Returns `true` if the two observables broadcast through the same
+    /// internal channel.
+    ///
+    /// This is an identity check, similar to [`Arc::ptr_eq`],
+    /// that does not compare the inner values. It keeps working if one of the
+    /// observables is moved.
+    pub fn is_same(a: &Self, b: &Self) -> bool {
+        a.sender.same_channel(&b.sender)
+    }
+
+    /// Get a handle for mutating the `Observable` through method calls.
+    ///
+    /// Like everything else on `Observable`, this is an associated function
+    /// rather than a method, so it never shadows a method of `T` called
+    /// `as_mut` (such as [`AsMut::as_mut`]). Call it as
+    /// `Observable::as_mut(&mut observable)`; the handle it returns is a
+    /// separate type, so its methods can't clash with those of `T`.
+    ///
+    /// ```
+    /// use eyeball::Observable;
+    ///
+    /// let mut ob = Observable::new(1);
+    /// Observable::as_mut(&mut ob).update(|value| *value += 1);
+    /// assert_eq!(*ob, 2);
+    /// ```
+    pub fn as_mut(this: &mut Self) -> ObservableMut<'_, T> {
+        ObservableMut { observable: this }
+    }
+
+    /// Get a pinned reference to the inner value.
+    pub fn as_pin_ref(this: Pin<&Self>) -> Pin<&T> {
+        // SAFETY: See `as_pin_mut`
+        unsafe { this.map_unchecked(|this| &this.value) }
+    }
+
+    /// Get a pinned mutable reference to the inner value.
+    ///
+    /// This is meant for inner values that are `!Unpin`, like futures, which
+    /// can only be polled or otherwise used through `Pin<&mut T>`. Mutating
+    /// the inner value this way does **not** notify subscribers; to replace or
+    /// update the value with a notification, the `Observable` must not be
+    /// pinned (or `T` must be `Unpin`) since all other mutating functions
+    /// need `&mut Observable<T>`.
+    pub fn as_pin_mut(this: Pin<&mut Self>) -> Pin<&mut T> {
+        // SAFETY: The inner value is structurally pinned. It is never moved
+        // out of a pinned `Observable`, because all functions that could do so
+        // take `&mut Self`, which can only be obtained from `Pin<&mut Self>`
+        // if `T: Unpin`. `Observable` is only `Unpin` if `T` is, and its
+        // `Drop` implementation doesn't touch the inner value.
+        unsafe { this.map_unchecked_mut(|this| &mut this.value) }
+    }
+
     /// Set the inner value to the given `value` and notify subscribers.
     pub fn set(this: &mut Self, value: T) {
-        Self::replace(this, value);
+        Self::replace_as(this, value, UpdateKind::Set);
+    }
+
+    /// Set the inner value to the given `value`, notify subscribers and wait
+    /// until all of them received the update.
+    ///
+    /// This completes once every subscriber that existed when the update was
+    /// sent has yielded it (or a later value), or was dropped. Subscribers
+    /// created in the meantime are not waited for. If the update is not sent
+    /// right away, because the `Observable` is [silenced][Self::silence] or
+    /// rate-limited, this only waits for the updates that were sent before.
+    ///
+    /// Like for [`BackpressuredObservable::set_async`], a subscriber that is
+    /// never polled (but also not dropped) makes this wait forever.
+    ///
+    /// [`BackpressuredObservable::set_async`]: crate::BackpressuredObservable::set_async
+    pub async fn set_and_wait(this: &mut Self, value: T) {
+        Self::set(this, value);
+        let seq = this.sender.seq();
+        poll_fn(|cx| this.sender.poll_received(seq, cx)).await;
+    }
+
+    /// Set the inner value to the given `value`, converted into `T`, and
+    /// notify subscribers.
+    ///
+    /// This is a shorthand for `Observable::set(this, value.into())`, for
+    /// example to set an `Observable<String>` from a `&str`.
+    pub fn set_into(this: &mut Self, value: impl Into<T>) {
+        Self::set(this, value.into());
     }
 
     /// Set the inner value to the given `value`, notify subscribers and return
     /// the previous value.
+    ///
+    /// If the `Observable` is [frozen][Self::freeze] and the mutation is
+    /// ignored, the given `value` is returned instead.
     pub fn replace(this: &mut Self, value: T) -> T {
-        let result = mem::replace(&mut this.value, value);
-        Self::broadcast_update(this);
-        result
+        Self::replace_as(this, value, UpdateKind::Replace)
     }
 
-    /// Update the inner value and notify subscribers.
+    /// Set the inner value to the given `value`, converted into `T`, notify
+    /// subscribers and return the previous value.
     ///
-    /// Note that even if the inner value is not actually changed by the
-    /// closure, subscribers will be notified as if it was. Use one of the
-    /// other update methods below if you want to conditionally mutate the
-    /// inner value.
-    pub fn update(this: &mut Self, f: impl FnOnce(&mut T)) {
-        f(&mut this.value);
-        Self::broadcast_update(this);
+    /// This is a shorthand for `Observable::replace(this, value.into())`.
+    pub fn replace_into(this: &mut Self, value: impl Into<T>) -> T {
+        Self::replace(this, value.into())
     }
 
-    /// Update the inner value and notify subscribers if the updated value does
-    /// not equal the previous value.
-    pub fn update_eq(this: &mut Self, f: impl FnOnce(&mut T))
+    /// Set the inner value to the given `value`, notify subscribers if it does
+    /// not equal the previous value and return the previous value along with
+    /// whether subscribers were notified.
+    pub fn replace_checked(this: &mut Self, value: T) -> (T, bool)
     where
         T: PartialEq,
     {
-        let prev = this.value.clone();
-        f(&mut this.value);
-        if this.value != prev {
-            Self::broadcast_update(this);
+        if Self::reject_if_frozen(this) {
+            return (value, false);
+        }
+        let result = mem::replace(&mut this.value, value);
+        let changed = result != this.value;
+        if changed {
+            Self::broadcast_update_as(this, UpdateKind::Replace);
         }
+        (result, changed)
     }
 
-    /// Update the inner value and notify subscribers if the hash of the updated
-    /// value does not equal the hash of the previous value.
-    pub fn update_hash(this: &mut Self, f: impl FnOnce(&mut T))
+    /// Set the inner value to the given `value` and notify subscribers if it
+    /// does not equal the current value.
+    ///
+    /// Unlike [`update_eq`][Self::update_eq], this compares the new value
+    /// against the current one directly instead of cloning the current value
+    /// first, so it doesn't allocate for `Copy` types like flags and counters.
+    /// Returns whether subscribers were notified.
+    pub fn set_eq(this: &mut Self, value: T) -> bool
     where
-        T: Hash,
+        T: PartialEq,
     {
-        use std::collections::hash_map::DefaultHasher;
-
-        let mut hasher = DefaultHasher::new();
-        this.value.hash(&mut hasher);
-        let prev_hash = hasher.finish();
-
-        f(&mut this.value);
-
-        let mut hasher = DefaultHasher::new();
-        this.value.hash(&mut hasher);
-        let new_hash = hasher.finish();
-
-        if prev_hash != new_hash {
-            Self::broadcast_update(this);
+        if Self::reject_if_frozen(this) || this.value == value {
+            false
+        } else {
+            this.value = value;
+            Self::broadcast_update_as(this, UpdateKind::Set);
+            true
         }
     }
 
-    fn broadcast_update(this: &Self) {
-        if this.sender.receiver_count() != 0 {
-            let _num_receivers = this.sender.send(this.value.clone()).unwrap_or(0);
-            #[cfg(feature = "tracing")]
-            tracing::debug!("New observable value broadcast to {_num_receivers} receivers");
+    /// Set the inner value to the given `value` and notify subscribers if it
+    /// does not equal the current value.
+    ///
+    /// Returns the previous value if the value was changed, and `None`
+    /// otherwise, in which case `value` is dropped. Like
+    /// [`set_eq`][Self::set_eq], this doesn't clone the current value.
+    pub fn set_if_not_eq(this: &mut Self, value: T) -> Option<T>
+    where
+        T: PartialEq,
+    {
+        if Self::reject_if_frozen(this) || this.value == value {
+            None
+        } else {
+            let prev = mem::replace(&mut this.value, value);
+            Self::broadcast_update_as(this, UpdateKind::Set);
+            Some(prev)
         }
     }
-}
 
-// Note: No DerefMut because all mutating must go through inherent methods that
-// notify subscribers
+    /// Set the inner value to the given `value` and notify subscribers if its
+    /// hash does not equal the hash of the current value.
+    ///
+    /// Returns the previous value if the value was changed, and `None`
+    /// otherwise, in which case `value` is dropped.
+    pub fn set_if_hash_not_eq(this: &mut Self, value: T) -> Option<T>
+    where
+        T: Hash,
+    {
+        if Self::reject_if_frozen(this)
+            || !NotifyOnHash::has_changed(NotifyOnHash::before_update(&this.value), &value)
+        {
+            None
+        } else {
+            let prev = mem::replace(&mut this.value, value);
+            Self::broadcast_update_as(this, UpdateKind::Set);
+            Some(prev)
+        }
+    }
+
+    /// Swap the inner values of two observables, notifying the subscribers of
+    /// each of them of its new value.
+    ///
+    /// Subscribers of an `Observable` created with
+    /// [`new_deduped`][Self::new_deduped] are not notified if both values are
+    /// equal.
+    pub fn swap(a: &mut Self, b: &mut Self) {
+        if Self::reject_if_frozen(a) | Self::reject_if_frozen(b) {
+            return;
+        }
+        let a_deduped = a.dedup_eq.is_some_and(|eq| eq(&a.value, &b.value));
+        let b_deduped = b.dedup_eq.is_some_and(|eq| eq(&b.value, &a.value));
+        mem::swap(&mut a.value, &mut b.value);
+        if !a_deduped {
+            Self::broadcast_update(a);
+        }
+        if !b_deduped {
+            Self::broadcast_update(b);
+        }
+    }
+
+    /// Swap the inner values of two observables, notifying the subscribers of
+    /// each of them of its new value unless both values are equal.
+    ///
+    /// Returns whether the values were swapped.
+    pub fn swap_eq(a: &mut Self, b: &mut Self) -> bool
+    where
+        T: PartialEq,
+    {
+        if a.value == b.value {
+            false
+        } else {
+            Self::swap(a, b);
+            true
+        }
+    }
+
+    /// Update the inner value and notify subscribers.
+    ///
+    /// Note that even if the inner value is not actually changed by the
+    /// closure, subscribers will be notified as if it was (unless the
+    /// `Observable` was created with [`new_deduped`][Self::new_deduped]). Use
+    /// one of the other update methods below if you want to conditionally
+    /// mutate the inner value.
+    ///
+    /// If the closure panics, the changes it already made to the inner value
+    /// are kept, but subscribers are not notified of them. Use
+    /// [`try_update`][Self::try_update] for updates that may fail halfway
+    /// through.
+    ///
+    /// Like all of the update functions, this accepts an `FnOnce` closure. To
+    /// reuse a closure that mutates captured state across multiple updates,
+    /// pass it by mutable reference, since `&mut F` implements `FnOnce` for
+    /// any `F: FnMut`:
+    ///
+    /// ```
+    /// use eyeball::Observable;
+    ///
+    /// let mut ob = Observable::new(0);
+    /// let mut num_updates = 0;
+    /// let mut increment = |value: &mut i32| {
+    ///     *value += 1;
+    ///     num_updates += 1;
+    /// };
+    ///
+    /// for _ in 0..3 {
+    ///     Observable::update(&mut ob, &mut increment);
+    /// }
+    ///
+    /// assert_eq!(*ob, 3);
+    /// assert_eq!(num_updates, 3);
+    /// ```
+    pub fn update(this: &mut Self, f: impl FnOnce(&mut T)) {
+        match this.dedup_eq {
+            Some(eq) => {
+                Self::update_detecting(this, f, T::clone, |prev, value| {
+                    (!eq(&prev, value)).then_some(())
+                });
+            }
+            None => Self::update_with::<AlwaysNotify>(this, f),
+        }
+    }
+
+    /// Update the inner value and notify subscribers if `f` returns `true`.
+    ///
+    /// Returns the result of `f`, or `false` without calling `f` if the
+    /// `Observable` is [frozen][Self::freeze]. Unlike with
+    /// [`try_update`][Self::try_update], `f` operates on the inner value
+    /// directly, so changes it makes before returning `false` are kept, they
+    /// are just not broadcast.
+    ///
+    /// For an `Observable` created with [`new_deduped`][Self::new_deduped],
+    /// subscribers are also not notified if the updated value is equal to the
+    /// previous one, even if `f` returns `true`. Like for
+    /// [`update`][Self::update], this clones the previous value on every call.
+    pub fn update_if(this: &mut Self, f: impl FnOnce(&mut T) -> bool) -> bool {
+        if Self::reject_if_frozen(this) {
+            return false;
+        }
+        let prev = this.dedup_eq.map(|eq| (eq, this.value.clone()));
+        let changed = f(&mut this.value);
+        let deduped = prev.is_some_and(|(eq, prev)| eq(&prev, &this.value));
+        if changed && !deduped {
+            Self::broadcast_update(this);
+        }
+        changed
+    }
+
+    /// Update a copy of the inner value, and if `f` succeeds, replace the inner
+    /// value with it and notify subscribers.
+    ///
+    /// If `f` returns an error or panics, the inner value stays exactly as it
+    /// was and subscribers are not notified, so a failed update can't leave
+    /// it half-modified. This costs a clone of the inner value per call.
+    ///
+    /// If the `Observable` is [frozen][Self::freeze] with
+    /// [`FrozenPolicy::Ignore`], `f` still runs so that its result can be
+    /// returned, but the updated copy is discarded. With
+    /// [`FrozenPolicy::Panic`], this panics before calling `f`.
+    pub fn try_update<R, E>(
+        this: &mut Self,
+        f: impl FnOnce(&mut T) -> Result<R, E>,
+    ) -> Result<R, E> {
+        let frozen = Self::reject_if_frozen(this);
+        let mut value = this.value.clone();
+        let result = f(&mut value)?;
+        if !frozen {
+            Self::set(this, value);
+        }
+        Ok(result)
+    }
+
+    /// Update the inner value and notify subscribers if the strategy `S`
+    /// considers it changed.
+    ///
+    /// The named update functions like [`update_eq`][Self::update_eq] and
+    /// [`update_hash`][Self::update_hash] are shorthands for this with one of
+    /// the built-in strategies. Implement [`ChangeStrategy`] to plug in custom
+    /// change detection.
+    pub fn update_with<S: ChangeStrategy<T>>(this: &mut Self, f: impl FnOnce(&mut T)) {
+        Self::update_detecting(this, f, S::before_update, |prev, value| {
+            S::has_changed(prev, value).then_some(())
+        });
+    }
+
+    /// Like [`update_with`][Self::update_with], but with the strategy given as
+    /// closures, so that it can capture arguments.
+    ///
+    /// `changed` gets the state captured by `before_update` and the updated
+    /// value, and returns `None` if the value is unchanged. Otherwise,
+    /// subscribers are notified and its result is returned.
+    fn update_detecting<P, R>(
+        this: &mut Self,
+        f: impl FnOnce(&mut T),
+        before_update: impl FnOnce(&T) -> P,
+        changed: impl FnOnce(P, &T) -> Option<R>,
+    ) -> Option<R> {
+        if Self::reject_if_frozen(this) {
+            return None;
+        }
+        let prev = before_update(&this.value);
+        f(&mut this.value);
+        let result = changed(prev, &this.value)?;
+        Self::broadcast_update(this);
+        Some(result)
+    }
+
+    /// Update the inner value through an async closure and notify subscribers
+    /// once the returned future completes.
+    ///
+    /// The future borrows the inner value mutably, so it has to be boxed for
+    /// its type to be nameable:
+    ///
+    /// ```
+    /// use eyeball::Observable;
+    ///
+    /// # async fn commit(_value: &str) {}
+    /// # futures_executor::block_on(async {
+    /// let mut ob = Observable::new(String::new());
+    /// Observable::update_async(&mut ob, |value| {
+    ///     Box::pin(async move {
+    ///         value.push_str("draft");
+    ///         commit(value).await;
+    ///     })
+    /// })
+    /// .await;
+    /// # });
+    /// ```
+    ///
+    /// The `Observable` stays borrowed until the future completes, so nothing
+    /// else can read or update it in the meantime. If the future is dropped
+    /// before completing, any changes it already made to the inner value are
+    /// kept, but subscribers are not notified of them.
+    pub async fn update_async<F>(this: &mut Self, f: F)
+    where
+        F: for<'a> FnOnce(&'a mut T) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>,
+    {
+        if Self::reject_if_frozen(this) {
+            return;
+        }
+        f(&mut this.value).await;
+        Self::broadcast_update(this);
+    }
+
+    /// Update the inner value, notify subscribers and return the result of
+    /// calling `then` on the updated value.
+    ///
+    /// `then` runs after subscribers have been notified, so it observes the
+    /// exact value that was broadcast. This is useful for reacting to the new
+    /// state, for example checking whether a collection became empty, without
+    /// a separate read.
+    pub fn update_then<R>(
+        this: &mut Self,
+        f: impl FnOnce(&mut T),
+        then: impl FnOnce(&T) -> R,
+    ) -> R {
+        Self::update(this, f);
+        then(&this.value)
+    }
+
+    /// Get a guard for mutating the inner value, which notifies subscribers
+    /// when it is dropped.
+    ///
+    /// This is an alternative to [`update`][Self::update] for mutations that
+    /// span multiple statements. Subscribers are only notified if the guard
+    /// was dereferenced mutably, and for an `Observable` created with
+    /// [`new_deduped`][Self::new_deduped], only if the value changed.
+    ///
+    /// If the `Observable` is [frozen][Self::freeze] and the mutation is
+    /// ignored, the guard dereferences to a copy of the inner value that is
+    /// discarded when it is dropped.
+    pub fn write(this: &mut Self) -> ObservableWriteGuard<'_, T> {
+        let state = WriteState::new(this);
+        ObservableWriteGuard { observable: this, state }
+    }
+
+    /// Update the inner value and notify subscribers if the updated value does
+    /// not equal the previous value.
+    pub fn update_eq(this: &mut Self, f: impl FnOnce(&mut T))
+    where
+        T: PartialEq,
+    {
+        Self::update_with::<NotifyOnEq>(this, f);
+    }
+
+    /// Update the inner value and notify subscribers if the updated value does
+    /// not equal the previous value, calling `on_change` with the previous and
+    /// the updated value in that case.
+    ///
+    /// This reuses the clone of the previous value that is needed for the
+    /// comparison anyway, which makes it a cheap hook for change logging.
+    pub fn update_eq_logged(this: &mut Self, f: impl FnOnce(&mut T), on_change: impl FnOnce(&T, &T))
+    where
+        T: PartialEq,
+    {
+        Self::update_detecting(this, f, T::clone, |prev, value| {
+            (prev != *value).then(|| on_change(&prev, value))
+        });
+    }
+
+    /// Update the inner value and notify subscribers if the updated value does
+    /// not equal the previous value, returning the previous value in that
+    /// case.
+    ///
+    /// Returns `None` if the value was left unchanged. This hands back the
+    /// clone of the previous value that is needed for the comparison anyway,
+    /// which makes it a cheap building block for undo stacks.
+    pub fn update_eq_prev(this: &mut Self, f: impl FnOnce(&mut T)) -> Option<T>
+    where
+        T: PartialEq,
+    {
+        Self::update_detecting(this, f, T::clone, |prev, value| (prev != *value).then_some(prev))
+    }
+
+    /// Update the inner value and notify subscribers if the updated value is
+    /// not [`observe_eq`][ObserveEq::observe_eq] to the previous value.
+    ///
+    /// Use this instead of [`update_eq`][Self::update_eq] if `T` has fields
+    /// that should not trigger notifications when they change, like
+    /// timestamps or caches.
+    pub fn update_observe_eq(this: &mut Self, f: impl FnOnce(&mut T))
+    where
+        T: ObserveEq,
+    {
+        Self::update_detecting(this, f, T::clone, |prev, value| {
+            (!value.observe_eq(&prev)).then_some(())
+        });
+    }
+
+    /// Update the inner value and notify subscribers if the updated value
+    /// differs from the previous value by more than `tolerance`.
+    ///
+    /// This keeps small fluctuations, like sensor noise, from notifying
+    /// subscribers. Note that the comparison is against the previous value,
+    /// not the last broadcast one, so a slow drift in small steps is never
+    /// broadcast.
+    pub fn update_approx(this: &mut Self, f: impl FnOnce(&mut T), tolerance: f64)
+    where
+        T: ApproxEq,
+    {
+        Self::update_detecting(this, f, T::clone, |prev, value| {
+            (!value.approx_eq(&prev, tolerance)).then_some(())
+        });
+    }
+
+    /// Update the inner value and notify subscribers if `has_changed` returns
+    /// `true` for the previous and the updated value.
+    ///
+    /// This is for types that don't implement `PartialEq` or `Hash`, or that
+    /// need domain-specific change detection. Like
+    /// [`update_eq`][Self::update_eq], it clones the previous value for the
+    /// comparison.
+    pub fn update_by(
+        this: &mut Self,
+        f: impl FnOnce(&mut T),
+        has_changed: impl FnOnce(&T, &T) -> bool,
+    ) {
+        Self::update_detecting(this, f, T::clone, |prev, value| {
+            has_changed(&prev, value).then_some(())
+        });
+    }
+
+    /// Set the inner value to the given `value` and notify subscribers if
+    /// `has_changed` returns `true` for the current and the new value.
+    ///
+    /// If it returns `false`, the inner value is left as it is and `value` is
+    /// dropped. Returns whether subscribers were notified.
+    pub fn set_by(this: &mut Self, value: T, has_changed: impl FnOnce(&T, &T) -> bool) -> bool {
+        if Self::reject_if_frozen(this) || !has_changed(&this.value, &value) {
+            false
+        } else {
+            this.value = value;
+            Self::broadcast_update_as(this, UpdateKind::Set);
+            true
+        }
+    }
+
+    /// Update the inner value and notify subscribers if the hash of the updated
+    /// value does not equal the hash of the previous value.
+    pub fn update_hash(this: &mut Self, f: impl FnOnce(&mut T))
+    where
+        T: Hash,
+    {
+        Self::update_with::<NotifyOnHash>(this, f);
+    }
+
+    /// Suppress notifications until the returned guard is dropped.
+    ///
+    /// The guard dereferences to the `Observable`, so all of the usual
+    /// associated functions can be used through it (e.g.
+    /// `Observable::set(&mut guard, value)`). None of them will notify
+    /// subscribers while the guard is alive; instead, if any of them would
+    /// have, subscribers are notified of the final value once when the guard
+    /// is dropped.
+    ///
+    /// Conditional updates like [`update_eq`][Self::update_eq] still compare
+    /// the values as usual while silenced, they only defer the resulting
+    /// notification.
+    pub fn silence(this: &mut Self) -> SilenceGuard<'_, T> {
+        let was_silenced = mem::replace(&mut this.silenced, true);
+        SilenceGuard { observable: this, was_silenced }
+    }
+
+    /// Run `f` as a single transaction, notifying subscribers at most once.
+    ///
+    /// This is a closure-based alternative to [`silence`][Self::silence]:
+    /// updates made through the `&mut Observable` passed to `f` are not
+    /// broadcast right away, subscribers are instead notified of the final
+    /// value once `f` returns, if any of the updates would have notified
+    /// them. The same happens if `f` panics.
+    ///
+    /// Transactions can be nested, and inside a silenced or
+    /// [deferred][Self::defer_broadcasts] observable, the notification is
+    /// left to the outer scope.
+    pub fn transaction<R>(this: &mut Self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let mut guard = Self::silence(this);
+        f(&mut guard)
+    }
+
+    /// Defer all notifications until [`flush`][Self::flush] is called.
+    ///
+    /// Subscribers usually run on other tasks, so they can't observe the
+    /// intermediate values of a burst of updates that happens without the
+    /// producing task yielding to the executor in between. With a deferred
+    /// `Observable`, such a burst only clones and sends the final value once,
+    /// when the producer calls `flush`, typically right before it yields.
+    ///
+    /// There is no runtime-agnostic way to detect that a task is about to
+    /// yield, so nothing is sent unless `flush` is called.
+    pub fn defer_broadcasts(mut this: Self) -> Self {
+        this.deferred = true;
+        this
+    }
+
+    /// Notify subscribers of the current value if any updates have been
+    /// deferred.
+    ///
+    /// See [`defer_broadcasts`][Self::defer_broadcasts]. This does nothing
+    /// while a [`silence`][Self::silence] guard is alive.
+    pub fn flush(this: &mut Self) {
+        if !this.silenced && mem::take(&mut this.broadcast_pending) {
+            Self::send_update(this);
+        }
+    }
+
+    /// Make the `Observable` read-only.
+    ///
+    /// This is meant for state that is only mutable during initialization.
+    /// Any deferred update is flushed, and subscribers keep receiving the final
+    /// value, as does [`subscribe`][Self::subscribe] after freezing. What
+    /// happens on later attempts to mutate the `Observable` is configured with
+    /// [`on_frozen_mutation`][Self::on_frozen_mutation]. Freezing can't be
+    /// undone.
+    pub fn freeze(this: &mut Self) {
+        Self::flush(this);
+        this.frozen = true;
+    }
+
+    /// Whether the `Observable` was [frozen][Self::freeze].
+    pub fn is_frozen(this: &Self) -> bool {
+        this.frozen
+    }
+
+    /// Returns whether a mutation has to be skipped because the `Observable`
+    /// is frozen, or panics depending on its [`FrozenPolicy`].
+    fn reject_if_frozen(this: &Self) -> bool {
+        if !this.frozen {
+            return false;
+        }
+        match this.frozen_policy {
+            FrozenPolicy::Ignore => true,
+            FrozenPolicy::Panic => panic!("attempted to mutate a frozen observable"),
+        }
+    }
+
+    fn replace_as(this: &mut Self, value: T, kind: UpdateKind) -> T {
+        if Self::reject_if_frozen(this) {
+            return value;
+        }
+        let result = mem::replace(&mut this.value, value);
+        match this.dedup_eq {
+            Some(eq) if eq(&result, &this.value) => {}
+            _ => Self::broadcast_update_as(this, kind),
+        }
+        result
+    }
+
+    fn broadcast_update(this: &mut Self) {
+        Self::broadcast_update_as(this, UpdateKind::Update);
+    }
+
+    fn broadcast_update_as(this: &mut Self, kind: UpdateKind) {
+        this.update_kind = kind;
+        this.change_count += 1;
+        #[cfg(feature = "std")]
+        {
+            this.last_changed = now();
+        }
+        if this.silenced || this.deferred {
+            this.broadcast_pending = true;
+        } else {
+            Self::send_update(this);
+        }
+    }
+
+    fn send_update(this: &mut Self) {
+        #[cfg(feature = "tokio-time")]
+        let scheduled = Self::schedule_rate_limited(this);
+        #[cfg(not(feature = "tokio-time"))]
+        let scheduled = false;
+
+        if !scheduled {
+            Self::send_now(this);
+        }
+
+        if !this.projections.is_empty() {
+            let version = this.version;
+            this.projections.retain_mut(|projection| projection(&this.value, version));
+        }
+
+        if !this.callbacks.is_empty() {
+            let mut callbacks = mem::take(&mut this.callbacks);
+            for callback in &mut callbacks {
+                callback(this);
+            }
+            this.callbacks = callbacks;
+        }
+    }
+
+    /// Schedule the update to be sent later if it would exceed the rate limit
+    /// set with [`with_min_interval`][Self::with_min_interval].
+    ///
+    /// Returns `true` if it was scheduled, `false` if it should be sent now.
+    #[cfg(feature = "tokio-time")]
+    fn schedule_rate_limited(this: &mut Self) -> bool {
+        let Some(min_interval) = &this.min_interval else {
+            return false;
+        };
+
+        let mut state = min_interval.state.lock().unwrap();
+        let now = tokio::time::Instant::now();
+        let deadline = match state.last_sent {
+            Some(last_sent) if now < last_sent + min_interval.interval => {
+                last_sent + min_interval.interval
+            }
+            _ => {
+                // A scheduled update that is overdue is superseded by this one
+                state.scheduled = None;
+                state.last_sent = Some(now);
+                return false;
+            }
+        };
+
+        match &mut state.scheduled {
+            Some(msg) => msg.value = this.value.clone(),
+            None => {
+                this.version += 1;
+                #[cfg(feature = "registry")]
+                if let Some(registration) = &this.registration {
+                    registration.set_version(this.version);
+                }
+                let msg = BroadcastMessage { version: this.version, value: this.value.clone() };
+                state.scheduled = Some(msg);
+
+                let state = min_interval.state.clone();
+                let sender = this.sender.downgrade();
+                let meta = LogMeta::new(this);
+                tokio::spawn(async move {
+                    tokio::time::sleep_until(deadline).await;
+
+                    let mut state = state.lock().unwrap();
+                    let Some(msg) = state.scheduled.take() else { return };
+                    state.last_sent = Some(tokio::time::Instant::now());
+                    let version = msg.version;
+                    let value = meta.debug_value.map(|_| msg.value.clone());
+                    // Fails if the observable was dropped in the meantime
+                    if let Ok(num_receivers) = sender.send(msg) {
+                        meta.log_broadcast(num_receivers, version, value.as_ref());
+                    }
+                });
+            }
+        }
+
+        true
+    }
+
+    fn send_now(this: &mut Self) {
+        this.version += 1;
+        #[cfg(feature = "registry")]
+        if let Some(registration) = &this.registration {
+            registration.set_version(this.version);
+        }
+        if this.sender.has_receivers() {
+            let msg = BroadcastMessage { version: this.version, value: this.value.clone() };
+            match this.sender.send(msg) {
+                Ok(num_receivers) => {
+                    LogMeta::new(this).log_broadcast(
+                        num_receivers,
+                        this.version,
+                        Some(&this.value),
+                    );
+                }
+                Err(_) => match this.send_error_policy {
+                    SendErrorPolicy::Ignore => {}
+                    SendErrorPolicy::Log => {
+                        warn_event!("Failed to broadcast new observable value");
+                    }
+                    SendErrorPolicy::Panic => {
+                        panic!("failed to broadcast new observable value");
+                    }
+                },
+            }
+        }
+    }
+}
+
+impl Observable<String> {
+    /// Append `string` to the inner value and notify subscribers.
+    ///
+    /// Every subscriber receives its own clone of the whole string, so for
+    /// large strings that grow continuously, like log buffers, consider using
+    /// an `Observable<Arc<str>>` and [`push_str_arc`][Observable::push_str_arc]
+    /// instead.
+    pub fn push_str(this: &mut Self, string: &str) {
+        Self::update(this, |value| value.push_str(string));
+    }
+}
+
+impl Observable<Arc<str>> {
+    /// Append `string` to the inner value and notify subscribers.
+    ///
+    /// This allocates a new buffer for every update, but all subscribers share
+    /// it instead of each of them receiving a clone.
+    pub fn push_str_arc(this: &mut Self, string: &str) {
+        let mut value = String::with_capacity(this.value.len() + string.len());
+        value.push_str(&this.value);
+        value.push_str(string);
+        Self::set(this, value.into());
+    }
+}
+
+impl<T: Clone + Send + 'static> Observable<Option<T>> {
+    /// Take the inner value out, leaving `None` in its place.
+    ///
+    /// Subscribers are only notified if the inner value was `Some(_)`.
+    pub fn take(this: &mut Self) -> Option<T> {
+        match this.value {
+            Some(_) => Self::replace(this, None),
+            None => None,
+        }
+    }
+
+    /// Set the inner value to `Some(value)` and notify subscribers.
+    pub fn set_some(this: &mut Self, value: T) {
+        Self::set(this, Some(value));
+    }
+
+    /// Get the inner value, first setting it to the result of `f` and
+    /// notifying subscribers if it is `None`.
+    ///
+    /// Unlike [`Option::get_or_insert_with`], this returns a clone, so that
+    /// the value computed by `f` is returned even if the `Observable` is
+    /// [frozen][Self::freeze] and the insertion is ignored.
+    pub fn get_or_insert_with(this: &mut Self, f: impl FnOnce() -> T) -> T {
+        if let Some(value) = &this.value {
+            return value.clone();
+        }
+        let value = f();
+        Self::set(this, Some(value.clone()));
+        value
+    }
+}
+
+impl<T> Observable<T> {
+    /// Get the number of subscribers that currently receive updates.
+    ///
+    /// [Lazy subscribers][Self::subscribe_lazy] are only counted once they
+    /// have been polled for the first time. This can be used to skip
+    /// expensive work for updates nobody would receive.
+    pub fn subscriber_count(this: &Self) -> usize {
+        this.sender.receiver_count()
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl<T> Observable<T> {
+    pub(crate) fn is_broadcast_pending(this: &Self) -> bool {
+        this.broadcast_pending
+    }
+}
+
+impl<T: ?Sized + Send + Sync + 'static> Observable<Arc<T>> {
+    /// Set the inner value to `value` and notify subscribers, unless both
+    /// point to the same allocation.
+    ///
+    /// This is an O(1) alternative to [`update_eq`][Self::update_eq] for
+    /// copy-on-write state. Returns whether subscribers were notified.
+    pub fn set_arc_if_ptr_differs(this: &mut Self, value: Arc<T>) -> bool {
+        if Self::reject_if_frozen(this) || Arc::ptr_eq(&this.value, &value) {
+            false
+        } else {
+            Self::set(this, value);
+            true
+        }
+    }
+}
+
+#[cfg(feature = "tokio-interop")]
+impl<T: Clone + Send + Sync + 'static> Observable<T> {
+    /// Create an `Observable` that follows the given [`watch::Receiver`],
+    /// returning a subscriber for it.
+    ///
+    /// The `Observable` starts out with the current value of `rx`. Like for
+    /// [`from_stream`][Self::from_stream], `spawn` must run the given future
+    /// to completion, which owns the `Observable` and sets it to every value
+    /// sent to the watch channel. The future completes once the watch
+    /// channel's sender is dropped or there are no subscribers left.
+    ///
+    /// This requires the `tokio-interop` Cargo feature.
+    ///
+    /// [`watch::Receiver`]: tokio::sync::watch::Receiver
+    pub fn from_watch(
+        mut rx: tokio::sync::watch::Receiver<T>,
+        spawn: impl FnOnce(Pin<Box<dyn Future<Output = ()> + Send>>),
+    ) -> Subscriber<T> {
+        let (mut this, subscriber) = Self::new_with_subscriber(rx.borrow_and_update().clone());
+        spawn(Box::pin(async move {
+            while rx.changed().await.is_ok() {
+                let value = rx.borrow_and_update().clone();
+                Self::set(&mut this, value);
+                if this.sender.receiver_count() == 0 {
+                    break;
+                }
+            }
+        }));
+        subscriber
+    }
+
+    /// Obtain a [`watch::Receiver`] that follows this `Observable`.
+    ///
+    /// The watch channel starts out with the current value. `spawn` must run
+    /// the given future to completion, which forwards every update to the
+    /// watch channel. The future completes once this `Observable` is dropped
+    /// or all receivers of the watch channel are, and dropping it stops the
+    /// forwarding like the latter.
+    ///
+    /// This requires the `tokio-interop` Cargo feature.
+    ///
+    /// [`watch::Receiver`]: tokio::sync::watch::Receiver
+    pub fn subscribe_watch(
+        this: &Self,
+        spawn: impl FnOnce(Pin<Box<dyn Future<Output = ()> + Send>>),
+    ) -> tokio::sync::watch::Receiver<T> {
+        let (tx, rx) = tokio::sync::watch::channel(this.value.clone());
+        let mut subscriber = Self::subscribe(this);
+        spawn(Box::pin(async move {
+            let mut closed = std::pin::pin!(tx.closed());
+            let mut next = |cx: &mut Context<'_>| {
+                if closed.as_mut().poll(cx).is_ready() {
+                    return Poll::Ready(None);
+                }
+                Pin::new(&mut subscriber).poll_next(cx)
+            };
+            while let Some(value) = poll_fn(&mut next).await {
+                tx.send_replace(value);
+            }
+        }));
+        rx
+    }
+}
+
+/// Prints a concise summary that doesn't include the inner value, so it
+/// doesn't require `T: Debug`. Use [`Observable::debug_value`] to include it.
+impl<T> fmt::Debug for Observable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Observable")
+            .field("subscribers", &self.sender.receiver_count())
+            .field("version", &self.version)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: fmt::Debug> Observable<T> {
+    /// Get a value whose `Debug` implementation includes the inner value, in
+    /// addition to the summary printed by the `Debug` implementation of the
+    /// `Observable`.
+    pub fn debug_value(this: &Self) -> impl fmt::Debug + '_ {
+        DebugValue(this)
+    }
+}
+
+struct DebugValue<'a, T>(&'a Observable<T>);
+
+impl<T: fmt::Debug> fmt::Debug for DebugValue<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ob = self.0;
+        f.debug_struct("Observable")
+            .field("value", &ob.value)
+            .field("subscribers", &ob.sender.receiver_count())
+            .field("version", &ob.version)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for Observable<T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.drop_value.take() {
+            // Values that were sent before the sender is dropped are still
+            // received by all subscribers before their stream ends
+            let msg = BroadcastMessage { version: self.version + 1, value };
+            let _ = self.sender.send(msg);
+        }
+    }
+}
+
+/// Forwards to the inner value's `AsRef` implementation.
+///
+/// Unlike the `Borrow` implementation, this does not give access to the inner
+/// value itself, i.e. `Observable<T>` only implements `AsRef<T>` if `T` does.
+/// Implementing `AsRef<T>` for every `T` instead would make
+/// `observable.as_ref()` call it rather than an inherent `as_ref` method of
+/// the inner value, like [`Option::as_ref`]. Use [`Observable::get`] or
+/// dereference the `Observable` to get at the inner value.
+///
+/// ```
+/// use eyeball::Observable;
+///
+/// fn shout(s: impl AsRef<str>) -> String {
+///     s.as_ref().to_uppercase()
+/// }
+///
+/// let ob = Observable::new("hello".to_owned());
+/// assert_eq!(shout(&*ob), "HELLO");
+/// assert_eq!(shout(ob), "HELLO");
+/// ```
+impl<T, U> AsRef<U> for Observable<T>
+where
+    T: AsRef<U>,
+    U: ?Sized,
+{
+    fn as_ref(&self) -> &U {
+        self.value.as_ref()
+    }
+}
+
+impl<T> Borrow<T> for Observable<T> {
+    fn borrow(&self) -> &T {
+        &self.value
+    }
+}
+
+/// Serializes the inner value, as if the `Observable` wasn't there.
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for Observable<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+/// Deserializes the inner value and wraps it in a new `Observable` without
+/// subscribers.
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for Observable<T>
+where
+    T: serde::Deserialize<'de> + Clone + Send + 'static,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// Equality for the purpose of deciding whether subscribers should be notified.
+///
+/// See [`Observable::update_observe_eq`].
+///
+/// ```
+/// use std::time::Instant;
+///
+/// use eyeball::ObserveEq;
+///
+/// #[derive(Clone)]
+/// struct Config {
+///     verbose: bool,
+///     // Changes to this don't need to be observed
+///     last_saved: Instant,
+/// }
+///
+/// impl ObserveEq for Config {
+///     fn observe_eq(&self, other: &Self) -> bool {
+///         self.verbose == other.verbose
+///     }
+/// }
+/// ```
+pub trait ObserveEq {
+    /// Returns `true` if `self` and `other` should be considered equal, such
+    /// that a change from one to the other does not need to be observed.
+    fn observe_eq(&self, other: &Self) -> bool;
+}
+
+/// Equality within a tolerance, for values with floating-point parts.
+///
+/// See [`Observable::update_approx`]. For structs, compare each of the
+/// relevant fields with the same tolerance:
+///
+/// ```
+/// use eyeball::ApproxEq;
+///
+/// #[derive(Clone)]
+/// struct Position {
+///     x: f64,
+///     y: f64,
+/// }
+///
+/// impl ApproxEq for Position {
+///     fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+///         self.x.approx_eq(&other.x, tolerance) && self.y.approx_eq(&other.y, tolerance)
+///     }
+/// }
+/// ```
+pub trait ApproxEq {
+    /// Returns `true` if `self` and `other` differ by at most `tolerance`.
+    fn approx_eq(&self, other: &Self, tolerance: f64) -> bool;
+}
+
+impl ApproxEq for f32 {
+    fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+        f64::from(*self).approx_eq(&f64::from(*other), tolerance)
+    }
+}
+
+impl ApproxEq for f64 {
+    fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+        (self - other).abs() <= tolerance
+    }
+}
+
+/// A guard that suppresses notifications of an [`Observable`] while it is
+/// alive.
+///
+/// Created by [`Observable::silence`].
+#[derive(Debug)]
+pub struct SilenceGuard<'a, T: Clone + Send + 'static> {
+    observable: &'a mut Observable<T>,
+    was_silenced: bool,
+}
+
+impl<T: Clone + Send + 'static> ops::Deref for SilenceGuard<'_, T> {
+    type Target = Observable<T>;
+
+    fn deref(&self) -> &Self::Target {
+        self.observable
+    }
+}
+
+impl<T: Clone + Send + 'static> ops::DerefMut for SilenceGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.observable
+    }
+}
+
+impl<T: Clone + Send + 'static> Drop for SilenceGuard<'_, T> {
+    fn drop(&mut self) {
+        self.observable.silenced = self.was_silenced;
+        if !self.observable.deferred {
+            Observable::flush(self.observable);
+        }
+    }
+}
+
+/// A handle for stopping the future that keeps an [`Observable`] updated from
+/// a stream.
+///
+/// Created by [`Observable::from_stream_with_handle`]. Clones refer to the
+/// same future.
+#[derive(Clone, Debug)]
+pub struct DriverHandle {
+    inner: Arc<Mutex<DriverState>>,
+}
+
+#[derive(Debug)]
+struct DriverState {
+    stopped: bool,
+    waker: Option<Waker>,
+}
+
+impl DriverHandle {
+    fn new() -> Self {
+        Self { inner: Arc::new(Mutex::new(DriverState { stopped: false, waker: None })) }
+    }
+
+    /// Stop the future, dropping the `Observable` it owns.
+    pub fn stop(&self) {
+        let waker = {
+            let mut state = self.inner.lock();
+            state.stopped = true;
+            state.waker.take()
+        };
+        waker.into_iter().for_each(Waker::wake);
+    }
+
+    /// Whether [`stop`][Self::stop] was called.
+    pub fn is_stopped(&self) -> bool {
+        self.inner.lock().stopped
+    }
+
+    fn poll_stopped(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.inner.lock();
+        if state.stopped {
+            return Poll::Ready(());
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// A guard for mutating the inner value of an [`Observable`], which notifies
+/// subscribers when it is dropped.
+///
+/// Created by [`Observable::write`].
+pub struct ObservableWriteGuard<'a, T: Clone + Send + 'static> {
+    observable: &'a mut Observable<T>,
+    state: WriteState<T>,
+}
+
+/// The state of a write guard, shared with the guards of other observable
+/// types that wrap an `Observable`.
+pub(crate) enum WriteState<T> {
+    Unmodified,
+    /// Holds the previous value if the `Observable` is deduplicating.
+    Modified(Option<T>),
+    /// Holds a copy of the value of a frozen `Observable`.
+    Rejected(T),
+}
+
+impl<T: Clone + Send + 'static> WriteState<T> {
+    pub(crate) fn new(observable: &mut Observable<T>) -> Self {
+        if Observable::reject_if_frozen(observable) {
+            Self::Rejected(observable.value.clone())
+        } else {
+            Self::Unmodified
+        }
+    }
+
+    pub(crate) fn value<'a>(&'a self, observable: &'a Observable<T>) -> &'a T {
+        match self {
+            Self::Rejected(value) => value,
+            _ => &observable.value,
+        }
+    }
+
+    pub(crate) fn value_mut<'a>(&'a mut self, observable: &'a mut Observable<T>) -> &'a mut T {
+        match self {
+            Self::Rejected(value) => return value,
+            Self::Modified(_) => {}
+            state @ Self::Unmodified => {
+                let prev = observable.dedup_eq.map(|_| observable.value.clone());
+                *state = Self::Modified(prev);
+            }
+        }
+        &mut observable.value
+    }
+
+    /// Notify subscribers if the value was modified.
+    pub(crate) fn finish(&mut self, observable: &mut Observable<T>) {
+        let Self::Modified(prev) = mem::replace(self, Self::Unmodified) else {
+            return;
+        };
+        match (prev, observable.dedup_eq) {
+            (Some(prev), Some(eq)) if eq(&prev, &observable.value) => {}
+            _ => Observable::broadcast_update(observable),
+        }
+    }
+}
+
+impl<T: Clone + Send + 'static> ops::Deref for ObservableWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.state.value(self.observable)
+    }
+}
+
+impl<T: Clone + Send + 'static> ops::DerefMut for ObservableWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.state.value_mut(self.observable)
+    }
+}
+
+impl<T: Clone + Send + 'static> Drop for ObservableWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.state.finish(self.observable);
+    }
+}
+
+impl<T: Clone + Send + 'static> fmt::Debug for ObservableWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservableWriteGuard")
+            .field("observable", &self.observable)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A guard that dereferences to the value of an [`Observable`], without
+/// cloning it.
+///
+/// Created by [`Observable::read`] and [`Subscriber::read`].
+///
+/// The guard can't be sent to another thread, since one created by
+/// `Subscriber::read` holds a lock:
+///
+/// ```compile_fail
+/// use eyeball::Observable;
+///
+/// fn assert_send<T: Send>(_: T) {}
+///
+/// let ob = Observable::new(0);
+/// assert_send(Observable::read(&ob));
+/// ```
+pub struct ObservableReadGuard<'a, T> {
+    inner: ReadGuardInner<'a, T>,
+    /// Not `Send`, like the lock it may hold, for either kind of guard.
+    _not_send: PhantomData<MutexGuard<'a, ()>>,
+}
+
+enum ReadGuardInner<'a, T> {
+    Borrowed(&'a T),
+    Latest(LatestGuard<'a, T>),
+}
+
+impl<T> ops::Deref for ObservableReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        match &self.inner {
+            ReadGuardInner::Borrowed(value) => value,
+            ReadGuardInner::Latest(guard) => guard,
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ObservableReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+/// A borrowed projection of the inner value of an [`Observable`].
+///
+/// Created by [`Observable::map_ref`].
+#[derive(Debug)]
+pub struct MappedRef<'a, U: ?Sized> {
+    value: &'a U,
+}
+
+impl<'a, U: ?Sized> MappedRef<'a, U> {
+    /// Project the guard further.
+    ///
+    /// This is an associated function to not clash with methods of `U`.
+    pub fn map<V: ?Sized>(orig: Self, f: impl FnOnce(&U) -> &V) -> MappedRef<'a, V> {
+        MappedRef { value: f(orig.value) }
+    }
+}
+
+impl<U: ?Sized> Clone for MappedRef<'_, U> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<U: ?Sized> Copy for MappedRef<'_, U> {}
+
+impl<U: ?Sized> ops::Deref for MappedRef<'_, U> {
+    type Target = U;
+
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+/// A handle for mutating an [`Observable`] through method calls.
+///
+/// Created by [`Observable::as_mut`]. Every method forwards to the associated
+/// function of the same name on `Observable`.
+#[derive(Debug)]
+pub struct ObservableMut<'a, T> {
+    observable: &'a mut Observable<T>,
+}
+
+impl<T: Clone + Send + 'static> ObservableMut<'_, T> {
+    /// See [`Observable::set`].
+    pub fn set(self, value: T) {
+        Observable::set(self.observable, value);
+    }
+
+    /// See [`Observable::replace`].
+    pub fn replace(self, value: T) -> T {
+        Observable::replace(self.observable, value)
+    }
+
+    /// See [`Observable::update`].
+    pub fn update(self, f: impl FnOnce(&mut T)) {
+        Observable::update(self.observable, f);
+    }
+
+    /// See [`Observable::update_eq`].
+    pub fn update_eq(self, f: impl FnOnce(&mut T))
+    where
+        T: PartialEq,
+    {
+        Observable::update_eq(self.observable, f);
+    }
+
+    /// See [`Observable::update_hash`].
+    pub fn update_hash(self, f: impl FnOnce(&mut T))
+    where
+        T: Hash,
+    {
+        Observable::update_hash(self.observable, f);
+    }
+}
+
+/// What an [`Observable`] should do when broadcasting an update fails.
+///
+/// See [`Observable::on_send_error`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SendErrorPolicy {
+    /// Silently ignore the failure.
+    #[default]
+    Ignore,
+    /// Emit a warning.
+    ///
+    /// This requires the `tracing` or the `log` Cargo feature, without either
+    /// of them this behaves like [`Ignore`][Self::Ignore].
+    Log,
+    /// Panic.
+    Panic,
+}
+
+/// What an [`Observable`] should do when it is mutated after being frozen.
+///
+/// See [`Observable::freeze`] and [`Observable::on_frozen_mutation`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrozenPolicy {
+    /// Leave the inner value unchanged and don't notify subscribers.
+    Ignore,
+    /// Panic.
+    Panic,
+}
+
+impl Default for FrozenPolicy {
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            Self::Panic
+        } else {
+            Self::Ignore
+        }
+    }
+}
+
+// Note: No DerefMut because all mutating must go through inherent methods that
+// notify subscribers
 impl<T> ops::Deref for Observable<T> {
     type Target = T;
 
@@ -128,14 +2312,599 @@ impl<T> ops::Deref for Observable<T> {
 /// Use its [`Stream`] implementation to interact with it (futures-util and
 /// other futures-related crates have extension traits with convenience
 /// methods).
+///
+/// # Delivery
+///
+/// A subscriber always yields the latest value. The `Observable` only keeps
+/// its latest update around for subscribers, not a queue of them, so if
+/// multiple updates happen before a subscriber is polled, it skips all but
+/// the last one. Use [`Observable::subscribe_lossless`] to find out how many
+/// were skipped, or a [`BackpressuredObservable`][crate::BackpressuredObservable]
+/// if every value must be received.
+///
+/// # Outliving the `Observable`
+///
+/// A subscriber does not keep its `Observable` alive, and it can't: the
+/// `Observable` is the only thing that can produce updates, so a subscriber
+/// that kept the channel open on its own would wait forever. Once the
+/// `Observable` is dropped, a subscriber still yields the last update it
+/// hasn't received yet, if any, and then its stream ends.
+///
+/// This is why there is no way to turn an `Observable` into a subscriber
+/// that keeps being fed. To hand out a live subscriber while giving up the
+/// `Observable` itself, move the `Observable` to wherever its updates come
+/// from, for example into a task, or use [`Observable::from_stream`] if they
+/// come from a [`Stream`]. To also hand out the current value, read it before
+/// moving the `Observable`, or use [`Observable::subscribe_map`] to get a
+/// stream that starts with it.
 #[derive(Debug)]
 pub struct Subscriber<T> {
-    inner: BroadcastStream<T>,
+    inner: Receiver<T>,
+    terminated: bool,
+    /// Values to yield before polling `inner`.
+    buffered: VecDeque<BroadcastMessage<T>>,
+    /// Values with a lower version are skipped.
+    min_version: u64,
+    #[cfg(feature = "tracing")]
+    timing: Option<Timing>,
+}
+
+// No structural pinning
+impl<T> Unpin for Subscriber<T> {}
+
+impl<T: Clone + Send + 'static> Subscriber<T> {
+    pub(crate) fn new(sender: &Sender<T>) -> Self {
+        Self::from_receiver(sender.subscribe())
+    }
+
+    fn new_lazy(sender: &Sender<T>) -> Self {
+        Self::from_receiver(sender.subscribe_lazy())
+    }
+
+    fn from_receiver(inner: Receiver<T>) -> Self {
+        Self {
+            inner,
+            terminated: false,
+            buffered: VecDeque::new(),
+            min_version: 0,
+            #[cfg(feature = "tracing")]
+            timing: None,
+        }
+    }
+
+    /// Obtain a new subscriber for the same [`Observable`].
+    ///
+    /// The new subscriber is independent of this one, it only receives updates
+    /// that happen after it was created. If the `Observable` has already been
+    /// dropped, the returned subscriber's stream ends immediately.
+    pub fn resubscribe(&self) -> Subscriber<T> {
+        Self::from_receiver(self.inner.resubscribe())
+    }
+
+    /// Wait for the next value, but at most for `duration`.
+    ///
+    /// Returns `Ok(Some(_))` if a value was received, `Ok(None)` if the
+    /// stream ended and `Err(_)` if nothing happened within `duration`.
+    ///
+    /// This requires the `tokio-time` Cargo feature and must be called from
+    /// within a tokio runtime with the time driver enabled.
+    #[cfg(feature = "tokio-time")]
+    pub async fn next_timeout(
+        &mut self,
+        duration: Duration,
+    ) -> Result<Option<T>, tokio::time::error::Elapsed> {
+        tokio::time::timeout(duration, poll_fn(|cx| Pin::new(&mut *self).poll_next(cx))).await
+    }
+
+    /// Collect all values that are ready right now, without waiting for
+    /// further updates.
+    ///
+    /// Like when awaiting values, updates that were missed because the
+    /// subscriber lagged behind are skipped. Returns an empty `Vec` if no
+    /// value is ready.
+    pub fn collect_ready(&mut self) -> Vec<T> {
+        // Unlike a waker created from a `Wake` implementation, cloning this one
+        // doesn't allocate, and it is registered with the channel every time
+        // the subscriber runs out of values
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut values = Vec::new();
+        while let Poll::Ready(Some(msg)) = self.poll_next_msg(&mut cx) {
+            values.push(msg.value);
+        }
+        values
+    }
+
+    /// Get the latest value that was broadcast, without waiting and without
+    /// marking it as received.
+    ///
+    /// This is the value the subscriber would yield next if there is a new one,
+    /// and otherwise the one it yielded last. Returns `None` if nothing was
+    /// broadcast since the subscriber was created, except for subscribers that
+    /// have buffered values, like the ones created by
+    /// [`Observable::subscribe_with_current`].
+    ///
+    /// Note that updates that are not broadcast, e.g. because of
+    /// [`Observable::silence`], are not reflected here. This doesn't require
+    /// an async context, so it can be used e.g. from GUI callbacks or `Drop`
+    /// implementations.
+    pub fn get(&self) -> Option<T> {
+        self.inner.latest().or_else(|| self.buffered.back().map(|msg| msg.value.clone()))
+    }
+
+    /// Get a guard for the value [`get`][Self::get] would return, to inspect it
+    /// without cloning.
+    ///
+    /// The latest value is shared by all subscribers of the [`Observable`], so
+    /// the guard holds a lock that keeps the `Observable` from broadcasting
+    /// further updates until it is dropped. Don't hold on to it for longer
+    /// than necessary. The guard can't be sent to another thread, which also
+    /// keeps it from being held across `.await` points in futures that have to
+    /// be `Send`. Use [`get`][Self::get] instead if you need the value for
+    /// longer.
+    ///
+    /// # Deadlocks
+    ///
+    /// Setting or updating the `Observable`, or polling any of its
+    /// subscribers, waits for the guard to be dropped. Doing so on the same
+    /// thread while the guard is alive deadlocks (or panics, depending on the
+    /// platform):
+    ///
+    /// ```no_run
+    /// use eyeball::Observable;
+    ///
+    /// let mut ob = Observable::new(0);
+    /// let rx = Observable::subscribe(&ob);
+    /// Observable::set(&mut ob, 1);
+    ///
+    /// let guard = rx.read();
+    /// Observable::set(&mut ob, 2); // never returns
+    /// # drop(guard);
+    /// ```
+    pub fn read(&self) -> Option<ObservableReadGuard<'_, T>> {
+        let inner = match self.inner.read_latest() {
+            Some(guard) => ReadGuardInner::Latest(guard),
+            None => ReadGuardInner::Borrowed(&self.buffered.back()?.value),
+        };
+        Some(ObservableReadGuard { inner, _not_send: PhantomData })
+    }
+
+    /// Whether the [`Observable`] was [closed][Observable::close] or dropped.
+    ///
+    /// This distinguishes a subscriber that has not received an update yet
+    /// from one whose source is gone. The stream may still yield the last
+    /// update if it hasn't been received yet, and ends after that.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Wait for the next value.
+    ///
+    /// This is the same as [`StreamExt::next`], without the need for an
+    /// extension trait: it yields the next value that was sent after the
+    /// subscriber was created or after the last value it yielded, skipping
+    /// ones it lagged behind on, and `None` once the stream has ended. For
+    /// subscribers that start out with the current value, such as the ones
+    /// from [`Observable::subscribe_with_current`], that value is yielded
+    /// first. Awaiting `&mut subscriber` directly is equivalent.
+    ///
+    /// [`StreamExt::next`]: https://docs.rs/futures/latest/futures/stream/trait.StreamExt.html#method.next
+    pub async fn next(&mut self) -> Option<T> {
+        Next::new(self).await
+    }
+
+    /// Yield the next value if one is ready, without waiting.
+    ///
+    /// Like when awaiting values, updates that were missed because the
+    /// subscriber lagged behind are skipped. Returns `None` if no new value is
+    /// ready or the stream has ended.
+    pub fn next_now(&mut self) -> Option<T> {
+        let mut cx = Context::from_waker(Waker::noop());
+        match self.poll_next_msg(&mut cx) {
+            Poll::Ready(Some(msg)) => Some(msg.value),
+            Poll::Ready(None) | Poll::Pending => None,
+        }
+    }
+
+    /// Wait for the next value, and return it along with its version.
+    ///
+    /// This is the same as awaiting the next item of
+    /// [`versioned`][Self::versioned], without giving up the `Subscriber`.
+    /// Returns `None` once the stream has ended.
+    pub async fn next_versioned(&mut self) -> Option<(u64, T)> {
+        let msg = poll_fn(|cx| self.poll_next_msg(cx)).await?;
+        Some((msg.version, msg.value))
+    }
+
+    /// Wait until the value satisfies `predicate`, and return it.
+    ///
+    /// The latest value is checked first, see [`get`][Self::get], so if it
+    /// already satisfies `predicate`, this completes immediately. Otherwise,
+    /// it waits for updates until one does. Since only the latest value
+    /// matters for a condition like this, values that are ready at the same
+    /// time are skipped except for the last one. The returned value and
+    /// everything before it counts as received. Returns `None` if the stream
+    /// ends before the condition is met.
+    ///
+    /// Use [`Observable::subscribe_with_current`] to obtain a subscriber for
+    /// which the current value of the `Observable` is checked first, even if
+    /// it hasn't been broadcast.
+    pub async fn wait_for(&mut self, mut predicate: impl FnMut(&T) -> bool) -> Option<T> {
+        let mut value = self.get();
+        loop {
+            while let Some(next) = self.next_now() {
+                value = Some(next);
+            }
+            if let Some(value) = value.take().filter(&mut predicate) {
+                return Some(value);
+            }
+            value = Some(poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await?);
+        }
+    }
+
+    /// Block the current thread until the next value is ready.
+    ///
+    /// This is the synchronous counterpart of awaiting the next value, for
+    /// code that doesn't run inside an async runtime. It doesn't require a
+    /// tokio runtime either, but it must not be called from within an async
+    /// task, since it would block the executor's thread. Returns `None` once
+    /// the stream has ended.
+    ///
+    /// This requires the `std` Cargo feature, and is not available on
+    /// `wasm32-unknown-unknown`, where the main thread can't block.
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn next_blocking(&mut self) -> Option<T> {
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match self.poll_next_msg(&mut cx) {
+                Poll::Ready(opt) => return opt.map(|msg| msg.value),
+                // Spurious wake-ups just lead to another poll
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    /// Block the current thread until a value for which `predicate` returns
+    /// `true` is ready, and return it.
+    ///
+    /// Values for which it returns `false` are skipped, see
+    /// [`next_blocking`][Self::next_blocking]. Returns `None` if the stream
+    /// ends before such a value is received.
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn wait_until_blocking(&mut self, mut predicate: impl FnMut(&T) -> bool) -> Option<T> {
+        loop {
+            let value = self.next_blocking()?;
+            if predicate(&value) {
+                return Some(value);
+            }
+        }
+    }
+
+    /// Create a stream that obtains a new subscriber from `resubscribe`
+    /// whenever the current one ends.
+    ///
+    /// `resubscribe` is first called when the stream is polled for the first
+    /// time. The stream ends once `resubscribe` returns `None`. This allows
+    /// consumers to keep running when the upstream [`Observable`] is replaced.
+    pub fn reconnecting<F>(resubscribe: F) -> Reconnecting<T, F>
+    where
+        F: FnMut() -> Option<Subscriber<T>>,
+    {
+        Reconnecting::new(resubscribe)
+    }
+
+    /// Call `f` on every value before yielding it.
+    ///
+    /// Values that are skipped because the subscriber lagged behind are not
+    /// passed to `f`.
+    pub fn inspect<F>(self, f: F) -> Inspect<T, F>
+    where
+        F: FnMut(&T),
+    {
+        Inspect::new(self, f)
+    }
+
+    /// Yield the latest value once no new value was received for `duration`.
+    ///
+    /// Every new value restarts the wait, so while updates keep coming in at
+    /// shorter intervals, nothing is yielded. The latest value is never lost
+    /// though: once updates settle down, or when the stream ends, it is
+    /// yielded.
+    ///
+    /// This requires the `tokio-time` Cargo feature and must be polled from
+    /// within a tokio runtime with the time driver enabled.
+    #[cfg(feature = "tokio-time")]
+    pub fn debounce(self, duration: Duration) -> Debounce<T> {
+        Debounce::new(self, duration)
+    }
+
+    /// Yield values at most once per `interval`.
+    ///
+    /// A value that is received after a quiet period is yielded right away.
+    /// Values received in the `interval` after that are held back, and the
+    /// latest of them is yielded once it has passed, so the final update is
+    /// never dropped, even if the stream ends in the meantime.
+    ///
+    /// This requires the `tokio-time` Cargo feature and must be polled from
+    /// within a tokio runtime with the time driver enabled.
+    #[cfg(feature = "tokio-time")]
+    pub fn throttle(self, interval: Duration) -> Throttle<T> {
+        Throttle::new(self, interval)
+    }
+
+    /// Combine all values that are ready at the same time into one using
+    /// `combine`.
+    ///
+    /// When only one value is ready, it is yielded as-is without calling
+    /// `combine`. Since a subscriber only buffers the latest value of its
+    /// `Observable`, this makes a difference only for subscribers that buffer
+    /// more than one value, like ones created through
+    /// [`FromIterator`][Subscriber#impl-FromIterator<T>-for-Subscriber<T>].
+    pub fn coalesce<F>(self, combine: F) -> Coalesce<T, F>
+    where
+        F: FnMut(T, T) -> T,
+    {
+        Coalesce::new(self, combine)
+    }
+
+    /// Derive a stream of projections of the values, which only yields items
+    /// when the projection changes.
+    ///
+    /// Unlike mapping with a [`Stream`] extension trait, this skips results
+    /// that are equal to the previously yielded one, so downstream consumers
+    /// only wake up when the part of the value they care about changed.
+    pub fn map<U, F>(self, f: F) -> Map<T, U, F>
+    where
+        U: Clone + PartialEq,
+        F: FnMut(T) -> U,
+    {
+        Map::new(self, f)
+    }
+
+    /// Yield only the values that match `pred`, skipping values that are equal
+    /// to the previously yielded one.
+    pub fn filter<F>(self, pred: F) -> Filter<T, F>
+    where
+        T: PartialEq,
+        F: FnMut(&T) -> bool,
+    {
+        Filter::new(self, pred)
+    }
+
+    /// Skip values that are equal to the previously yielded one.
+    ///
+    /// Producers that use [`Observable::update`] notify subscribers even if
+    /// the value didn't change, this filters out such redundant updates on
+    /// the consumer's side.
+    pub fn dedup(self) -> Dedup<T>
+    where
+        T: PartialEq,
+    {
+        Dedup::new(self)
+    }
+
+    /// Skip values whose key, as returned by `key`, is equal to the key of the
+    /// previously yielded one.
+    ///
+    /// Unlike [`map`][Self::map], this yields the whole values, but only when
+    /// the part that `key` extracts changed.
+    pub fn dedup_by_key<K, F>(self, key: F) -> DedupByKey<T, K, F>
+    where
+        K: PartialEq,
+        F: FnMut(&T) -> K,
+    {
+        DedupByKey::new(self, key)
+    }
+
+    /// Yield only the values for which `f` returns `Some`, mapped to what's
+    /// inside the `Some`.
+    ///
+    /// See [`variant_subscriber!`][crate::variant_subscriber] for the common
+    /// case of extracting the payload of one enum variant.
+    pub fn filter_map<U, F>(self, f: F) -> FilterMap<T, F>
+    where
+        F: FnMut(T) -> Option<U>,
+    {
+        FilterMap::new(self, f)
+    }
+
+    /// Yield only the values for which `pred` returns a different result than
+    /// for the previous value, along with that result.
+    ///
+    /// An item `(true, value)` means `value` entered the condition described
+    /// by `pred`, `(false, value)` means it left it. Values that stay inside
+    /// or outside of the condition are skipped. Before the first value is
+    /// received, the condition is considered not met, so the first value is
+    /// only yielded if `pred` returns `true` for it.
+    pub fn on_edge<F>(self, pred: F) -> OnEdge<T, F>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        OnEdge::new(self, pred)
+    }
+
+    /// Yield the last `size` received values, oldest first, after every new
+    /// one.
+    ///
+    /// The first items contain fewer than `size` values, until that many have
+    /// been received. Values that were skipped because the subscriber lagged
+    /// behind are not part of the window, so consecutive values in it are not
+    /// necessarily consecutive updates of the `Observable`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn windowed(self, size: usize) -> Windowed<T> {
+        Windowed::new(self, size)
+    }
+
+    /// Allow looking at the next value without consuming it.
+    ///
+    /// Like when awaiting values directly, updates that were missed because
+    /// the subscriber lagged behind are skipped when waiting for the value to
+    /// peek at.
+    pub fn peekable(self) -> PeekableSubscriber<T> {
+        PeekableSubscriber::new(self)
+    }
+
+    /// Merge the values of multiple subscribers into one stream.
+    ///
+    /// Use [`MergeBuilder::max_consecutive`] to keep frequently updated
+    /// sources from starving the others, then [`MergeBuilder::build`] to get
+    /// the merged stream.
+    pub fn merge(subscribers: impl IntoIterator<Item = Self>) -> MergeBuilder<T> {
+        MergeBuilder::new(subscribers.into_iter().collect())
+    }
+
+    /// Combine this subscriber with `other` into a stream of the latest
+    /// values of both.
+    ///
+    /// The stream yields a new pair whenever either of them is updated, once
+    /// both have yielded a value. Since subscribers only yield updates that
+    /// happen after they were created, use
+    /// [`Observable::subscribe_with_current`] for both to get the first pair
+    /// right away. The stream ends once both subscribers have ended, or one of
+    /// them ended without yielding a value.
+    pub fn zip_latest<U: Clone + Send + 'static>(self, other: Subscriber<U>) -> ZipLatest<T, U> {
+        ZipLatest::new(self, other)
+    }
+
+    /// Combine multiple subscribers into a stream of the latest values of all
+    /// of them, in the same order.
+    ///
+    /// This is [`zip_latest`][Self::zip_latest] for any number of subscribers
+    /// of the same type.
+    pub fn combine_latest(subscribers: impl IntoIterator<Item = Self>) -> CombineLatest<T> {
+        CombineLatest::new(subscribers.into_iter().collect())
+    }
+
+    /// Only yield values while the latest value of `enabled` is `true`.
+    ///
+    /// By default, nothing passes through until `enabled` yields its first
+    /// value, and the latest value received while disabled is yielded once
+    /// `enabled` becomes `true` again. Both can be changed through methods of
+    /// the returned [`GatedBy`] stream. The stream ends when `self` ends.
+    pub fn gated_by(self, enabled: Subscriber<bool>) -> GatedBy<T> {
+        GatedBy::new(self, enabled)
+    }
+
+    /// Tag every value with its version.
+    ///
+    /// The version is the one returned by [`Observable::version`] right after
+    /// the update. Since subscribers skip values they were too slow to receive,
+    /// a gap between consecutive versions means the updates in between were
+    /// missed.
+    pub fn versioned(self) -> Versioned<T> {
+        Versioned::new(self)
+    }
+
+    /// Tag every value with the number of updates that were skipped right
+    /// before it because the subscriber lagged behind.
+    ///
+    /// This is zero for most values of a subscriber that keeps up. Unlike
+    /// [`Observable::subscribe_lossless`], gaps don't interrupt the stream,
+    /// which makes this convenient for consumers that only need to measure
+    /// them, e.g. for metrics.
+    pub fn enumerate_lossy(self) -> EnumerateLossy<T> {
+        EnumerateLossy::new(self)
+    }
+
+    /// Yield `()` instead of every value, for consumers that only need to know
+    /// that something changed, like a UI that re-renders on any update.
+    ///
+    /// Note that the values are still cloned for this subscriber when they
+    /// are broadcast. To avoid that, use [`Observable::subscribe_waker`]
+    /// instead.
+    pub fn into_change_pulses(self) -> ChangePulses<T> {
+        ChangePulses::new(self)
+    }
+
+    /// Tag every value with the time it was received.
+    ///
+    /// Note that this is the time at which the value was yielded from the
+    /// subscriber, not the time the value was set on the [`Observable`].
+    ///
+    /// This requires the `std` Cargo feature, and is not available on
+    /// `wasm32-unknown-unknown`, which has no clock.
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn timestamped(self) -> Timestamped<T> {
+        Timestamped::new(self)
+    }
+
+    /// Emit [tracing] events with timing information for every value received
+    /// through this subscriber.
+    ///
+    /// The events include the time elapsed since this method was called (which
+    /// is usually right after subscribing) for the first value, and the time
+    /// since the previous value for subsequent ones. This is useful to
+    /// diagnose slow consumers and producer stalls.
+    ///
+    /// On targets without a clock, like `wasm32-unknown-unknown`, this does
+    /// nothing.
+    #[cfg(feature = "tracing")]
+    pub fn trace_timing(mut self) -> Self {
+        self.timing = now().map(|subscribed_at| Timing { subscribed_at, last_value_at: None });
+        self
+    }
+
+    /// Receive values without cloning them while this is the only subscriber
+    /// of the [`Observable`].
+    ///
+    /// Normally, the latest update stays in the channel after it was received,
+    /// for [`get`][Self::get] and [`read`][Self::read] and for other
+    /// subscribers, so every subscriber receives a clone of it. With this,
+    /// the update is moved out of the channel instead when there are no other
+    /// subscribers, which saves a clone of every received value in the common
+    /// case of a single subscriber. In exchange, `get` and `read` return
+    /// `None` once the latest update was received this way.
+    pub fn take_values(mut self) -> Self {
+        self.inner.take_received();
+        self
+    }
+}
+
+#[cfg(feature = "tracing")]
+#[derive(Clone, Debug)]
+struct Timing {
+    subscribed_at: Instant,
+    last_value_at: Option<Instant>,
+}
+
+#[cfg(feature = "tracing")]
+impl Timing {
+    fn record_value(&mut self) {
+        let now = Instant::now();
+        match self.last_value_at.replace(now) {
+            Some(last_value_at) => {
+                let since_last_value = now - last_value_at;
+                tracing::debug!(?since_last_value, "Subscriber received a new value");
+            }
+            None => {
+                let since_subscribed = now - self.subscribed_at;
+                tracing::debug!(?since_subscribed, "Subscriber received its first value");
+            }
+        }
+    }
 }
 
-impl<T> Subscriber<T> {
-    fn new(inner: BroadcastStream<T>) -> Self {
-        Self { inner }
+/// Create a subscriber that is at the same position as this one.
+///
+/// Unlike [`resubscribe`][Subscriber::resubscribe], which only receives
+/// updates that happen afterwards, the clone also yields the value this
+/// subscriber would yield next, if there is one, and the values it has
+/// buffered. From there on, both receive the same updates independently. This
+/// allows handing out subscribers from a place that doesn't have access to
+/// the [`Observable`].
+impl<T: Clone> Clone for Subscriber<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            terminated: self.terminated,
+            buffered: self.buffered.clone(),
+            min_version: self.min_version,
+            #[cfg(feature = "tracing")]
+            timing: self.timing.clone(),
+        }
     }
 }
 
@@ -143,15 +2912,210 @@ impl<T: Clone + Send + 'static> Stream for Subscriber<T> {
     type Item = T;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_next_msg(cx).map(|opt| opt.map(|msg| msg.value))
+    }
+
+    /// Returns `(0, None)`, unless the stream has ended.
+    ///
+    /// There is no upper bound on the number of updates a subscriber can
+    /// receive, and since values that were not received in time are skipped,
+    /// no value is guaranteed to be ready either.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.terminated {
+            (0, Some(0))
+        } else {
+            (0, None)
+        }
+    }
+}
+
+impl<T: Clone + Send + 'static> Subscriber<T> {
+    pub(crate) fn poll_next_msg(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<BroadcastMessage<T>>> {
         loop {
-            let poll = match Pin::new(&mut self.inner).poll_next(cx) {
-                Poll::Ready(Some(Ok(value))) => Poll::Ready(Some(value)),
+            let poll = match self.poll_next_msg_lossless(cx) {
+                Poll::Ready(Some(Ok(msg))) => Poll::Ready(Some(msg)),
+                Poll::Ready(Some(Err(SubscriberError::Lagged { .. }))) => continue,
                 Poll::Ready(None) => Poll::Ready(None),
-                Poll::Ready(Some(Err(BroadcastStreamRecvError::Lagged(_)))) => continue,
                 Poll::Pending => Poll::Pending,
             };
 
             return poll;
         }
     }
+
+    /// Like `poll_next_msg`, but reports lag instead of skipping it.
+    pub(crate) fn poll_next_msg_lossless(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<BroadcastMessage<T>, SubscriberError>>> {
+        if let Some(msg) = self.buffered.pop_front() {
+            return Poll::Ready(Some(Ok(msg)));
+        }
+        if self.terminated {
+            return Poll::Ready(None);
+        }
+
+        loop {
+            let Some((skipped, msg)) = ready!(self.inner.poll_recv(cx)) else {
+                self.terminated = true;
+                return Poll::Ready(None);
+            };
+            if msg.version < self.min_version {
+                continue;
+            }
+
+            #[cfg(feature = "tracing")]
+            if let Some(timing) = &mut self.timing {
+                timing.record_value();
+            }
+
+            if skipped == 0 {
+                return Poll::Ready(Some(Ok(msg)));
+            }
+            // Report the gap first, the message is yielded on the next poll
+            self.buffered.push_back(msg);
+            return Poll::Ready(Some(Err(SubscriberError::Lagged { skipped })));
+        }
+    }
+}
+
+/// Create a subscriber that yields the given values in order and then ends,
+/// without an [`Observable`].
+///
+/// This is meant for testing code that consumes a `Subscriber`. Unlike for a
+/// regular subscriber, all of the values are buffered, so none of them are
+/// skipped.
+impl<T: Clone + Send + 'static> FromIterator<T> for Subscriber<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+        // The sender is dropped right away, so the stream ends after the
+        // buffered values
+        let mut subscriber = Subscriber::new(&Sender::new());
+        subscriber.buffered =
+            (1..).zip(values).map(|(version, value)| BroadcastMessage { version, value }).collect();
+        subscriber
+    }
+}
+
+impl<T: Clone + Send + 'static> FusedStream for Subscriber<T> {
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct BroadcastMessage<T> {
+    pub(crate) version: u64,
+    pub(crate) value: T,
+}
+
+/// The kind of mutation that caused a broadcast, included in log events.
+#[derive(Clone, Copy, Debug)]
+enum UpdateKind {
+    Set,
+    Replace,
+    Update,
+}
+
+#[cfg(any(feature = "tracing", feature = "log"))]
+impl UpdateKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Set => "set",
+            Self::Replace => "replace",
+            Self::Update => "update",
+        }
+    }
+}
+
+/// The parts of an `Observable` that are included in the events emitted for
+/// its broadcasts.
+struct LogMeta<T> {
+    name: Option<&'static str>,
+    kind: Option<&'static str>,
+    update_kind: UpdateKind,
+    change_count: u64,
+    debug_value: Option<DebugFn<T>>,
+}
+
+impl<T> LogMeta<T> {
+    fn new(observable: &Observable<T>) -> Self {
+        Self {
+            name: observable.name,
+            kind: observable.kind,
+            update_kind: observable.update_kind,
+            change_count: observable.change_count,
+            debug_value: observable.debug_value,
+        }
+    }
+
+    /// Emit a debug event for a successful broadcast through `tracing` and /
+    /// or `log`, depending on the enabled Cargo features.
+    ///
+    /// `value` is only formatted if [`Observable::log_values`] was used.
+    fn log_broadcast(&self, _num_receivers: usize, _version: u64, _value: Option<&T>) {
+        let Self {
+            name: _name,
+            kind: _kind,
+            update_kind: _update_kind,
+            change_count: _change_count,
+            debug_value: _debug_value,
+        } = self;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            name = _name,
+            value_type = std::any::type_name::<T>(),
+            change_count = _change_count,
+            version = _version,
+            kind = _kind,
+            update = _update_kind.as_str(),
+            value = _debug_value
+                .zip(_value)
+                .map(|(fmt, value)| tracing::field::debug(DebugWith(value, fmt))),
+            "New observable value broadcast to {_num_receivers} receivers"
+        );
+        #[cfg(feature = "log")]
+        match _name {
+            Some(name) => log::debug!(
+                "New value of observable `{name}` ({}) broadcast to {_num_receivers} receivers",
+                _update_kind.as_str(),
+            ),
+            None => log::debug!("New observable value broadcast to {_num_receivers} receivers"),
+        }
+    }
+}
+
+/// Formats a value with a `Debug` implementation captured as a function
+/// pointer.
+#[cfg(feature = "tracing")]
+struct DebugWith<'a, T>(&'a T, DebugFn<T>);
+
+#[cfg(feature = "tracing")]
+impl<T> fmt::Debug for DebugWith<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (self.1)(self.0, f)
+    }
+}
+
+/// A waker that unparks the thread it was created on.
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+struct ThreadWaker(Thread);
+
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Get the current time, unless the target has no clock.
+#[cfg(feature = "std")]
+fn now() -> Option<Instant> {
+    // `Instant::now` panics on `wasm32-unknown-unknown`
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    return None;
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    return Some(Instant::now());
 }
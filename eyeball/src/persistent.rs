@@ -0,0 +1,86 @@
+//! Helpers for observables of [`im`] collections.
+//!
+//! `im` collections use structural sharing, so cloning them is O(1). That
+//! makes them a good fit for the inner value of an [`Observable`], which is
+//! cloned for every update that is sent out.
+
+use std::{
+    borrow::Borrow,
+    hash::{BuildHasher, Hash},
+};
+
+use crate::Observable;
+
+impl<T: Clone + Send + Sync + 'static> Observable<im::Vector<T>> {
+    /// Append `value` to the back of the inner vector and notify subscribers.
+    pub fn push_back(this: &mut Self, value: T) {
+        Self::update(this, |vector| vector.push_back(value));
+    }
+
+    /// Prepend `value` to the front of the inner vector and notify
+    /// subscribers.
+    pub fn push_front(this: &mut Self, value: T) {
+        Self::update(this, |vector| vector.push_front(value));
+    }
+
+    /// Remove the last element of the inner vector and notify subscribers.
+    ///
+    /// Returns `None` without notifying subscribers if the vector is empty.
+    pub fn pop_back(this: &mut Self) -> Option<T> {
+        let mut result = None;
+        if !this.is_empty() {
+            Self::update(this, |vector| result = vector.pop_back());
+        }
+        result
+    }
+
+    /// Remove the first element of the inner vector and notify subscribers.
+    ///
+    /// Returns `None` without notifying subscribers if the vector is empty.
+    pub fn pop_front(this: &mut Self) -> Option<T> {
+        let mut result = None;
+        if !this.is_empty() {
+            Self::update(this, |vector| result = vector.pop_front());
+        }
+        result
+    }
+
+    /// Insert `value` at `index` into the inner vector and notify subscribers.
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(this: &mut Self, index: usize, value: T) {
+        Self::update(this, |vector| vector.insert(index, value));
+    }
+}
+
+impl<K, V, S> Observable<im::HashMap<K, V, S>>
+where
+    K: Hash + Eq + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    /// Insert a key-value pair into the inner map, notify subscribers and
+    /// return the previous value for `key`, if any.
+    pub fn insert_entry(this: &mut Self, key: K, value: V) -> Option<V> {
+        let mut result = None;
+        Self::update(this, |map| result = map.insert(key, value));
+        result
+    }
+
+    /// Remove `key` from the inner map, notify subscribers and return the
+    /// value it was mapped to.
+    ///
+    /// Returns `None` without notifying subscribers if the map doesn't contain
+    /// `key`.
+    pub fn remove_entry<Q>(this: &mut Self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut result = None;
+        if this.contains_key(key) {
+            Self::update(this, |map| result = map.remove(key));
+        }
+        result
+    }
+}
@@ -0,0 +1,15 @@
+//! Convenience re-exports of the most commonly used items.
+//!
+//! ```
+//! use eyeball::prelude::*;
+//!
+//! let mut ob = Observable::new(0);
+//! Observable::update_with::<NotifyOnEq>(&mut ob, |value| *value += 1);
+//! ```
+
+pub use crate::{
+    AlwaysNotify, ApproxEq, ArcObservable, AtomicObservable, BackpressuredObservable,
+    BackpressuredSubscriber, ChangeStrategy, FrozenPolicy, LocalObservable, LocalSubscriber,
+    MaybeObservable, MaybeSubscriber, NotifyOnEq, NotifyOnHash, Observable, ObservableMut,
+    ObserveEq, SendErrorPolicy, SharedObservable, Subscriber,
+};
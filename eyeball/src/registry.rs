@@ -0,0 +1,101 @@
+//! A global registry of named observables, for debugging.
+//!
+//! Every [`Observable`][crate::Observable] created with
+//! [`Observable::with_name`][crate::Observable::with_name] is registered here
+//! until it is dropped, so [`snapshot`] can list the observables that are
+//! currently alive along with how many subscribers they have. This helps
+//! finding out why a background task or a subscriber is still around.
+//!
+//! This requires the `registry` Cargo feature.
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, Weak,
+    },
+};
+
+use crate::channel::{ReceiverCount, Sender};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static ENTRIES: Mutex<BTreeMap<u64, Arc<Entry>>> = Mutex::new(BTreeMap::new());
+
+struct Entry {
+    name: &'static str,
+    type_name: &'static str,
+    version: AtomicU64,
+    /// Replaced when the observable disconnects its subscribers.
+    channel: Mutex<Weak<dyn ReceiverCount>>,
+}
+
+/// The entry of an observable in the registry, removed from it when dropped.
+pub(crate) struct Registration {
+    id: u64,
+    entry: Arc<Entry>,
+}
+
+impl Registration {
+    pub(crate) fn new<T: Send + 'static>(name: &'static str, sender: &Sender<T>) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let entry = Arc::new(Entry {
+            name,
+            type_name: std::any::type_name::<T>(),
+            version: AtomicU64::new(0),
+            channel: Mutex::new(sender.receiver_count_handle()),
+        });
+        ENTRIES.lock().unwrap().insert(id, entry.clone());
+        Self { id, entry }
+    }
+
+    pub(crate) fn set_sender<T: Send + 'static>(&self, sender: &Sender<T>) {
+        *self.entry.channel.lock().unwrap() = sender.receiver_count_handle();
+    }
+
+    pub(crate) fn set_version(&self, version: u64) {
+        self.entry.version.store(version, Ordering::Relaxed);
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        ENTRIES.lock().unwrap().remove(&self.id);
+    }
+}
+
+/// Information about a named observable that is currently alive.
+///
+/// Returned by [`snapshot`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ObservableInfo {
+    /// The name given to [`Observable::with_name`][crate::Observable::with_name].
+    pub name: &'static str,
+    /// The type name of the inner value, see [`std::any::type_name`].
+    pub type_name: &'static str,
+    /// The number of subscribers that currently receive updates.
+    pub subscriber_count: usize,
+    /// The current version, see
+    /// [`Observable::version`][crate::Observable::version].
+    pub version: u64,
+}
+
+/// Get information about all named observables that are currently alive, in
+/// the order they were created.
+pub fn snapshot() -> Vec<ObservableInfo> {
+    let entries: Vec<_> = ENTRIES.lock().unwrap().values().cloned().collect();
+    entries
+        .iter()
+        .map(|entry| ObservableInfo {
+            name: entry.name,
+            type_name: entry.type_name,
+            subscriber_count: entry
+                .channel
+                .lock()
+                .unwrap()
+                .upgrade()
+                .map_or(0, |channel| channel.receiver_count()),
+            version: entry.version.load(Ordering::Relaxed),
+        })
+        .collect()
+}
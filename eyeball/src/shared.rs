@@ -0,0 +1,183 @@
+use alloc::sync::{Arc, Weak};
+use core::{fmt, ops};
+
+use crate::{
+    lock::{RwLock, RwLockReadGuard},
+    Observable, Subscriber,
+};
+
+/// A variant of [`Observable`] that can be cloned and updated through a shared
+/// reference.
+///
+/// All clones refer to the same inner `Observable`, which is protected by a
+/// lock, so the value can be updated from multiple tasks or components. Every
+/// mutation takes the write lock and notifies subscribers once, just like
+/// the corresponding function of `Observable`. Subscribers are regular
+/// [`Subscriber`]s.
+///
+/// The lock is held while subscribers are notified, but not while they
+/// process the update, since that happens on their own tasks.
+///
+/// Unlike `Observable`, it can't dereference to `T`, but like `Observable`, it
+/// uses associated functions instead of methods.
+///
+/// Subscribers don't keep the inner `Observable` alive. Once the last clone is
+/// dropped, the streams of all subscribers end, even if [`WeakObservable`]s
+/// still exist.
+pub struct SharedObservable<T> {
+    inner: Arc<RwLock<Observable<T>>>,
+}
+
+impl<T: Clone + Send + 'static> SharedObservable<T> {
+    /// Create a new `SharedObservable` with the given initial value.
+    pub fn new(value: T) -> Self {
+        Self::from(Observable::new(value))
+    }
+
+    /// Obtain a new subscriber.
+    pub fn subscribe(this: &Self) -> Subscriber<T> {
+        Observable::subscribe(&this.inner.read())
+    }
+
+    /// Get a clone of the inner value.
+    pub fn get(this: &Self) -> T {
+        Self::read(this).clone()
+    }
+
+    /// Lock the inner value for reading.
+    ///
+    /// Updates through other clones of the `SharedObservable` wait until the
+    /// returned guard is dropped.
+    pub fn read(this: &Self) -> SharedReadGuard<'_, T> {
+        SharedReadGuard { guard: this.inner.read() }
+    }
+
+    /// Set the inner value to the given `value` and notify subscribers.
+    pub fn set(this: &Self, value: T) {
+        Observable::set(&mut this.inner.write(), value);
+    }
+
+    /// Set the inner value to the given `value`, notify subscribers and return
+    /// the previous value.
+    pub fn replace(this: &Self, value: T) -> T {
+        Observable::replace(&mut this.inner.write(), value)
+    }
+
+    /// Update the inner value and notify subscribers.
+    ///
+    /// The write lock is held while `f` runs, so the update is atomic with
+    /// respect to updates through other clones.
+    pub fn update(this: &Self, f: impl FnOnce(&mut T)) {
+        Observable::update(&mut this.inner.write(), f);
+    }
+
+    /// Update the inner value and notify subscribers if `f` returns `true`.
+    ///
+    /// See [`Observable::update_if`].
+    pub fn update_if(this: &Self, f: impl FnOnce(&mut T) -> bool) -> bool {
+        Observable::update_if(&mut this.inner.write(), f)
+    }
+
+    /// Update a copy of the inner value, and if `f` succeeds, replace the inner
+    /// value with it and notify subscribers.
+    ///
+    /// Like [`Observable::try_update`], this leaves the inner value intact if
+    /// `f` returns an error or panics.
+    pub fn try_update<R, E>(this: &Self, f: impl FnOnce(&mut T) -> Result<R, E>) -> Result<R, E> {
+        Observable::try_update(&mut this.inner.write(), f)
+    }
+
+    /// Update the inner value and notify subscribers if the updated value does
+    /// not equal the previous value.
+    pub fn update_eq(this: &Self, f: impl FnOnce(&mut T))
+    where
+        T: PartialEq,
+    {
+        Observable::update_eq(&mut this.inner.write(), f);
+    }
+}
+
+impl<T> SharedObservable<T> {
+    /// Create a [`WeakObservable`] that refers to the same inner `Observable`
+    /// without keeping it alive.
+    pub fn downgrade(this: &Self) -> WeakObservable<T> {
+        WeakObservable { inner: Arc::downgrade(&this.inner) }
+    }
+}
+
+impl<T> Clone for SharedObservable<T> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone() }
+    }
+}
+
+/// Share an existing `Observable`, keeping its configuration and subscribers.
+impl<T> From<Observable<T>> for SharedObservable<T> {
+    fn from(observable: Observable<T>) -> Self {
+        Self { inner: Arc::new(RwLock::new(observable)) }
+    }
+}
+
+impl<T> fmt::Debug for SharedObservable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedObservable").field("inner", &self.inner).finish()
+    }
+}
+
+/// A weak handle to a [`SharedObservable`].
+///
+/// Like [`std::sync::Weak`], it doesn't keep the inner `Observable` alive, so
+/// it can be held by background tasks or by other observables without
+/// creating reference cycles. Created by [`SharedObservable::downgrade`].
+pub struct WeakObservable<T> {
+    inner: Weak<RwLock<Observable<T>>>,
+}
+
+impl<T> WeakObservable<T> {
+    /// Create a `WeakObservable` that doesn't refer to any `Observable`.
+    ///
+    /// [`upgrade`][Self::upgrade] always returns `None` for it.
+    pub fn new() -> Self {
+        Self { inner: Weak::new() }
+    }
+
+    /// Get a strong handle to the inner `Observable`, if it still exists.
+    ///
+    /// Returns `None` if all clones of the `SharedObservable` were dropped.
+    pub fn upgrade(&self) -> Option<SharedObservable<T>> {
+        self.inner.upgrade().map(|inner| SharedObservable { inner })
+    }
+}
+
+impl<T> Clone for WeakObservable<T> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone() }
+    }
+}
+
+impl<T> fmt::Debug for WeakObservable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakObservable").finish_non_exhaustive()
+    }
+}
+
+/// A read guard for the inner value of a [`SharedObservable`].
+///
+/// Created by [`SharedObservable::read`].
+pub struct SharedReadGuard<'a, T> {
+    guard: RwLockReadGuard<'a, Observable<T>>,
+}
+
+impl<T> ops::Deref for SharedReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SharedReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        T::fmt(self, f)
+    }
+}
@@ -0,0 +1,49 @@
+use core::{
+    convert::Infallible,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_sink::Sink;
+
+use crate::Observable;
+
+/// A [`Sink`] that [`set`][Observable::set]s an [`Observable`] to every item
+/// sent into it.
+///
+/// Created by [`Observable::sink`]. It is always ready to receive items and
+/// never fails, so every item is set right away; the `Observable` itself
+/// doesn't buffer anything. Closing the sink doesn't affect the `Observable`.
+///
+/// This requires the `futures-sink` Cargo feature.
+#[derive(Debug)]
+pub struct ObservableSink<'a, T: Clone + Send + 'static> {
+    observable: &'a mut Observable<T>,
+}
+
+impl<'a, T: Clone + Send + 'static> ObservableSink<'a, T> {
+    pub(crate) fn new(observable: &'a mut Observable<T>) -> Self {
+        Self { observable }
+    }
+}
+
+impl<T: Clone + Send + 'static> Sink<T> for ObservableSink<'_, T> {
+    type Error = Infallible;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        Observable::set(self.observable, item);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,131 @@
+use core::hash::{Hash, Hasher};
+#[cfg(feature = "std")]
+use std::collections::hash_map::DefaultHasher;
+
+/// A way of deciding whether an update of an [`Observable`][crate::Observable]
+/// should be broadcast to its subscribers.
+///
+/// Used with [`Observable::update_with`][crate::Observable::update_with].
+/// Strategies are plain types that are never instantiated, so they can be
+/// selected with a type parameter:
+///
+/// ```
+/// use eyeball::{ChangeStrategy, Observable};
+///
+/// /// Only notify if the value changed by more than `0.1`.
+/// struct Approx;
+///
+/// impl ChangeStrategy<f64> for Approx {
+///     type State = f64;
+///
+///     fn before_update(value: &f64) -> f64 {
+///         *value
+///     }
+///
+///     fn has_changed(prev: f64, value: &f64) -> bool {
+///         (prev - value).abs() > 0.1
+///     }
+/// }
+///
+/// let mut ob = Observable::new(1.0);
+///
+/// Observable::update_with::<Approx>(&mut ob, |v| *v += 0.01);
+/// assert_eq!(Observable::version(&ob), 0);
+///
+/// Observable::update_with::<Approx>(&mut ob, |v| *v += 1.0);
+/// assert_eq!(Observable::version(&ob), 1);
+/// ```
+pub trait ChangeStrategy<T> {
+    /// Whatever needs to be remembered about the previous value to detect a
+    /// change, for example a clone of it or its hash.
+    type State;
+
+    /// Capture the state of `value` before it is updated.
+    fn before_update(value: &T) -> Self::State;
+
+    /// Returns `true` if `value` changed compared to the captured state, such
+    /// that subscribers should be notified.
+    fn has_changed(prev: Self::State, value: &T) -> bool;
+}
+
+/// A [`ChangeStrategy`] that always notifies subscribers.
+#[derive(Debug)]
+pub enum AlwaysNotify {}
+
+impl<T> ChangeStrategy<T> for AlwaysNotify {
+    type State = ();
+
+    fn before_update(_value: &T) {}
+
+    fn has_changed(_prev: (), _value: &T) -> bool {
+        true
+    }
+}
+
+/// A [`ChangeStrategy`] that notifies subscribers if the updated value does
+/// not equal the previous value.
+#[derive(Debug)]
+pub enum NotifyOnEq {}
+
+impl<T: Clone + PartialEq> ChangeStrategy<T> for NotifyOnEq {
+    type State = T;
+
+    fn before_update(value: &T) -> T {
+        value.clone()
+    }
+
+    fn has_changed(prev: T, value: &T) -> bool {
+        prev != *value
+    }
+}
+
+/// A [`ChangeStrategy`] that notifies subscribers if the hash of the updated
+/// value differs from the hash of the previous value.
+#[derive(Debug)]
+pub enum NotifyOnHash {}
+
+impl<T: Hash> ChangeStrategy<T> for NotifyOnHash {
+    type State = u64;
+
+    fn before_update(value: &T) -> u64 {
+        hash(value)
+    }
+
+    fn has_changed(prev: u64, value: &T) -> bool {
+        prev != hash(value)
+    }
+}
+
+fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The FNV-1a hash function, since `DefaultHasher` is not available without
+/// `std`.
+///
+/// The hashes are only compared to each other, so they don't need to be
+/// resistant to collisions caused on purpose.
+#[cfg(not(feature = "std"))]
+struct DefaultHasher(u64);
+
+#[cfg(not(feature = "std"))]
+impl Default for DefaultHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Hasher for DefaultHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -0,0 +1,105 @@
+use std::{fmt, ops, sync::Arc};
+
+use arc_swap::{ArcSwap, Guard};
+
+use crate::{channel::VersionedSender, Subscriber};
+
+/// A variant of [`Observable`][crate::Observable] for values that are read
+/// far more often than they are updated, and that can be set through a shared
+/// reference.
+///
+/// The value is stored in an [`ArcSwap`], so [`get`][Self::get] and
+/// [`read`][Self::read] never take a lock and are never blocked by writers,
+/// unlike with a [`SharedObservable`][crate::SharedObservable]. Subscribers
+/// receive `Arc<T>`s, like those of an [`ArcObservable`][crate::ArcObservable].
+///
+/// Updates wait for each other, so they are broadcast in the order they took
+/// effect. Readers never wait for updates.
+///
+/// Like `Observable`, it uses associated functions instead of methods. This
+/// requires the `arc-swap` Cargo feature.
+pub struct SwapObservable<T> {
+    value: ArcSwap<T>,
+    sender: VersionedSender<Arc<T>>,
+}
+
+impl<T: Send + Sync + 'static> SwapObservable<T> {
+    /// Create a new `SwapObservable` with the given initial value.
+    pub fn new(value: T) -> Self {
+        Self { value: ArcSwap::from_pointee(value), sender: VersionedSender::new() }
+    }
+
+    /// Obtain a new subscriber.
+    pub fn subscribe(this: &Self) -> Subscriber<Arc<T>> {
+        Subscriber::new(this.sender.sender())
+    }
+
+    /// Get a new reference to the inner value.
+    pub fn get(this: &Self) -> Arc<T> {
+        this.value.load_full()
+    }
+
+    /// Get a guard that dereferences to the inner value.
+    ///
+    /// This is cheaper than [`get`][Self::get], but the guard is meant to be
+    /// short-lived: holding on to many of them at once on the same thread
+    /// makes further reads slower.
+    pub fn read(this: &Self) -> SwapReadGuard<T> {
+        SwapReadGuard { guard: this.value.load() }
+    }
+
+    /// Set the inner value to the given `value` and notify subscribers.
+    pub fn set(this: &Self, value: T) {
+        Self::replace(this, value);
+    }
+
+    /// Set the inner value to the given `value`, notify subscribers and return
+    /// the previous value.
+    pub fn replace(this: &Self, value: T) -> Arc<T> {
+        let guard = this.sender.lock();
+        let value = Arc::new(value);
+        let result = this.value.swap(value.clone());
+        guard.send(value);
+        result
+    }
+
+    /// Update the inner value and notify subscribers.
+    ///
+    /// `f` is called with the current value and returns the new one. Other
+    /// updates wait until it returns, so the update is atomic, while readers
+    /// keep seeing the previous value until then. If `f` panics, the value
+    /// is left unchanged.
+    pub fn update(this: &Self, f: impl FnOnce(&T) -> T) {
+        let guard = this.sender.lock();
+        let value = Arc::new(f(&this.value.load()));
+        this.value.store(value.clone());
+        guard.send(value);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SwapObservable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SwapObservable").field("value", &self.value.load()).finish_non_exhaustive()
+    }
+}
+
+/// A guard that dereferences to the inner value of a [`SwapObservable`].
+///
+/// Created by [`SwapObservable::read`].
+pub struct SwapReadGuard<T> {
+    guard: Guard<Arc<T>>,
+}
+
+impl<T> ops::Deref for SwapReadGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SwapReadGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        T::fmt(self, f)
+    }
+}
@@ -0,0 +1,73 @@
+//! Helpers for testing code that uses eyeball.
+//!
+//! These let tests poll subscribers synchronously and inspect an
+//! [`Observable`]'s internal state, instead of resorting to sleeps and
+//! yields. This module is only available with the `test-util` Cargo feature
+//! and is not meant to be used outside of tests.
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use futures_core::Stream;
+
+use crate::Observable;
+
+/// Poll `stream` once, with a waker that does nothing.
+pub fn poll_now<S: Stream + Unpin>(stream: &mut S) -> Poll<Option<S::Item>> {
+    let mut cx = Context::from_waker(Waker::noop());
+    Pin::new(stream).poll_next(&mut cx)
+}
+
+/// Collect all of the items that `stream` yields without waiting.
+///
+/// Stops at the first poll that is pending, or at the end of the stream.
+pub fn collect_now<S: Stream + Unpin>(stream: &mut S) -> Vec<S::Item> {
+    let mut items = Vec::new();
+    while let Poll::Ready(Some(item)) = poll_now(stream) {
+        items.push(item);
+    }
+    items
+}
+
+/// Set the value of `observable` and return what `stream` yields right away.
+///
+/// The result is empty if `stream` has nothing ready after the update, for
+/// example because the update was suppressed.
+pub fn set_and_collect<T, S>(
+    observable: &mut Observable<T>,
+    value: T,
+    stream: &mut S,
+) -> Vec<S::Item>
+where
+    T: Clone + Send + 'static,
+    S: Stream + Unpin,
+{
+    Observable::set(observable, value);
+    collect_now(stream)
+}
+
+/// Assert that `stream` has no items ready and has not ended.
+///
+/// # Panics
+///
+/// Panics if polling `stream` does not return [`Poll::Pending`].
+#[track_caller]
+pub fn assert_pending<S: Stream + Unpin>(stream: &mut S) {
+    if poll_now(stream).is_ready() {
+        panic!("expected stream to be pending");
+    }
+}
+
+/// Get the number of subscribers that currently receive updates from
+/// `observable`.
+pub fn subscriber_count<T>(observable: &Observable<T>) -> usize {
+    Observable::subscriber_count(observable)
+}
+
+/// Returns `true` if `observable` has an update that was not broadcast yet
+/// because it is silenced or defers broadcasts.
+pub fn is_broadcast_pending<T>(observable: &Observable<T>) -> bool {
+    Observable::is_broadcast_pending(observable)
+}
@@ -1,6 +1,13 @@
+use std::{rc::Rc, sync::Arc};
+
+use futures_core::stream::FusedStream;
+use futures_util::FutureExt as _;
 use tokio_stream::StreamExt as _;
 
-use eyeball::Observable;
+use eyeball::{
+    ArcObservable, AtomicObservable, FrozenPolicy, LocalObservable, Observable, ObserveEq,
+    SendErrorPolicy, SharedObservable, SubscribeError, Subscriber,
+};
 
 #[tokio::test]
 async fn lag() {
@@ -15,3 +22,2429 @@ async fn lag() {
     assert_eq!(rx1.next().await, Some("B".to_owned()));
     assert_eq!(rx2.next().await, Some("B".to_owned()));
 }
+
+#[test]
+fn no_tokio_runtime() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+
+    Observable::set(&mut ob, 1);
+    assert_eq!(futures_executor::block_on(rx.next()), Some(1));
+
+    drop(ob);
+    assert_eq!(futures_executor::block_on(rx.next()), None);
+}
+
+#[tokio::test]
+async fn next_versioned() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+
+    Observable::set(&mut ob, 1);
+    assert_eq!(rx.next_versioned().await, Some((1, 1)));
+
+    Observable::set(&mut ob, 2);
+    Observable::set(&mut ob, 3);
+    assert_eq!(rx.next_versioned().await, Some((3, 3)));
+    assert_eq!(Observable::version(&ob), 3);
+
+    drop(ob);
+    assert_eq!(rx.next_versioned().await, None);
+}
+
+#[test]
+fn try_update() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let mut ob = Observable::new(vec![1]);
+    let mut rx = Observable::subscribe(&ob);
+
+    let result = Observable::try_update(&mut ob, |vec| {
+        vec.push(2);
+        Err::<(), _>("failed")
+    });
+    assert_eq!(result, Err("failed"));
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        Observable::try_update(&mut ob, |vec| -> Result<(), ()> {
+            vec.push(3);
+            panic!("update failed");
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(*ob, [1]);
+    assert_eq!(rx.next_now(), None);
+
+    assert_eq!(Observable::try_update(&mut ob, |vec| Ok::<_, ()>(vec.pop())), Ok(Some(1)));
+    assert_eq!(rx.next_now(), Some(vec![]));
+
+    let shared = SharedObservable::new(0);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        SharedObservable::try_update(&shared, |value| -> Result<(), ()> {
+            *value = 1;
+            panic!("update failed");
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(SharedObservable::get(&shared), 0);
+}
+
+#[test]
+fn option_helpers() {
+    let mut ob = Observable::new(None);
+    let mut rx = Observable::subscribe(&ob);
+
+    assert_eq!(Observable::take(&mut ob), None);
+    assert_eq!(rx.next_now(), None);
+
+    assert_eq!(Observable::get_or_insert_with(&mut ob, || 1), 1);
+    assert_eq!(Observable::get_or_insert_with(&mut ob, || 2), 1);
+    assert_eq!(rx.collect_ready(), [Some(1)]);
+
+    Observable::set_some(&mut ob, 3);
+    assert_eq!(rx.next_now(), Some(Some(3)));
+    assert_eq!(Observable::take(&mut ob), Some(3));
+    assert_eq!(rx.next_now(), Some(None));
+}
+
+#[tokio::test]
+async fn wait_for() {
+    #[derive(Clone, Debug, PartialEq)]
+    enum State {
+        Disconnected,
+        Connecting,
+        Connected(u32),
+    }
+
+    let mut ob = Observable::new(State::Disconnected);
+    let mut rx = Observable::subscribe_with_current(&ob);
+    assert_eq!(rx.wait_for(|state| *state == State::Disconnected).await, Some(State::Disconnected));
+
+    let task = tokio::spawn(async move {
+        let state = rx.wait_for(|state| matches!(state, State::Connected(_))).await;
+        (state, rx)
+    });
+    Observable::set(&mut ob, State::Connecting);
+    tokio::task::yield_now().await;
+    Observable::set(&mut ob, State::Connected(1));
+    let (state, mut rx) = task.await.unwrap();
+    assert_eq!(state, Some(State::Connected(1)));
+
+    // Already satisfied by the latest value
+    assert_eq!(rx.wait_for(|state| *state != State::Connecting).await, Some(State::Connected(1)));
+    assert_eq!(rx.next().now_or_never(), None);
+
+    drop(ob);
+    assert_eq!(rx.wait_for(|_| false).await, None);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn next_blocking() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+
+    Observable::set(&mut ob, 1);
+    assert_eq!(rx.next_blocking(), Some(1));
+
+    let handle = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        Observable::set(&mut ob, 2);
+        Observable::set(&mut ob, 3);
+    });
+    assert_eq!(rx.wait_until_blocking(|&value| value == 3), Some(3));
+    handle.join().unwrap();
+    assert_eq!(rx.next_blocking(), None);
+}
+
+#[test]
+fn changed_since() {
+    let mut ob = Observable::new(0);
+    let version = Observable::version(&ob);
+    assert_eq!(Observable::changed_since(&ob, version), None);
+
+    Observable::set(&mut ob, 1);
+    let (value, version) = Observable::changed_since(&ob, version).unwrap();
+    assert_eq!(*value, 1);
+    assert_eq!(Observable::changed_since(&ob, version), None);
+
+    Observable::update_eq(&mut ob, |value| *value = 1);
+    assert_eq!(Observable::changed_since(&ob, version), None);
+}
+
+#[test]
+fn is_same() {
+    let mut a = Observable::new(0);
+    let b = Observable::new(0);
+    assert!(Observable::is_same(&a, &a));
+    assert!(!Observable::is_same(&a, &b));
+
+    // The identity doesn't depend on the value or on where the observable is
+    Observable::set(&mut a, 1);
+    let a = Box::new(a);
+    assert!(Observable::is_same(&a, &a));
+    assert!(!Observable::is_same(&a, &b));
+}
+
+#[test]
+fn as_ref_borrow() {
+    use std::{borrow::Borrow, path::Path};
+
+    fn path_len(path: impl AsRef<Path>) -> usize {
+        path.as_ref().as_os_str().len()
+    }
+
+    // AsRef forwards to the inner value
+    let ob = Observable::new("dir/file".to_owned());
+    assert_eq!(path_len(&ob), 8);
+    let borrowed: &String = ob.borrow();
+    assert_eq!(borrowed, "dir/file");
+
+    // Method call syntax still reaches inherent `as_ref` methods of the inner
+    // value
+    let ob = Observable::new(Some(1));
+    assert_eq!(ob.as_ref(), Some(&1));
+}
+
+#[test]
+fn observable_mut() {
+    #[derive(Clone)]
+    struct Named(&'static str);
+
+    impl Named {
+        fn as_mut(&self) -> &'static str {
+            self.0
+        }
+    }
+
+    let mut ob = Observable::new(Named("a"));
+    let mut rx = Observable::subscribe(&ob);
+
+    // Method call syntax reaches the inner value's method
+    assert_eq!(ob.as_mut(), "a");
+
+    Observable::as_mut(&mut ob).set(Named("b"));
+    assert_eq!(ob.as_mut(), "b");
+    assert_eq!(rx.next_now().map(|named| named.0), Some("b"));
+
+    let prev = Observable::as_mut(&mut ob).replace(Named("c"));
+    assert_eq!(prev.0, "b");
+    Observable::as_mut(&mut ob).update(|named| named.0 = "d");
+    assert_eq!(rx.next_now().map(|named| named.0), Some("d"));
+}
+
+#[tokio::test]
+async fn poll_after_end() {
+    let ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+    drop(ob);
+
+    assert!(!rx.is_terminated());
+    assert_eq!(rx.next().await, None);
+    assert!(rx.is_terminated());
+    assert_eq!(rx.next().await, None);
+    assert_eq!(rx.next().await, None);
+}
+
+#[tokio::test]
+async fn close() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+    assert!(!rx.is_closed());
+
+    Observable::set(&mut ob, 1);
+    Observable::close(&mut ob);
+    assert!(Observable::is_closed(&ob));
+    assert!(rx.is_closed());
+    assert_eq!(rx.next().await, Some(1));
+    assert_eq!(rx.next().await, None);
+
+    Observable::set(&mut ob, 2);
+    assert_eq!(*ob, 2);
+    let mut rx2 = Observable::subscribe(&ob);
+    assert!(rx2.is_closed());
+    assert_eq!(rx2.next().await, None);
+
+    Observable::disconnect_subscribers(&mut ob);
+    assert!(Observable::is_closed(&ob));
+}
+
+#[tokio::test]
+async fn subscribe_map() {
+    let mut ob = Observable::new((false, 0));
+    let mut rx = Observable::subscribe_map(&ob, |&(logged_in, _)| logged_in);
+    let mut rx_eq = Observable::subscribe_map_eq(&ob, |&(logged_in, _)| logged_in);
+    assert_eq!(rx.next().await, Some(false));
+    assert_eq!(rx_eq.next().await, Some(false));
+
+    Observable::set(&mut ob, (false, 1));
+    assert_eq!(rx.next().await, Some(false));
+
+    Observable::set(&mut ob, (true, 1));
+    assert_eq!(rx.next().await, Some(true));
+    assert_eq!(rx_eq.next().await, Some(true));
+}
+
+#[tokio::test]
+async fn silence() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+
+    {
+        let mut guard = Observable::silence(&mut ob);
+        Observable::set(&mut guard, 1);
+        Observable::update(&mut guard, |value| *value += 1);
+        Observable::set(&mut guard, 3);
+        assert_eq!(rx.next().now_or_never(), None);
+    }
+
+    assert_eq!(rx.next().await, Some(3));
+    assert_eq!(rx.next().now_or_never(), None);
+}
+
+#[tokio::test]
+async fn transaction() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+
+    let ret = Observable::transaction(&mut ob, |txn| {
+        Observable::set(txn, 1);
+        Observable::update(txn, |value| *value += 1);
+        Observable::transaction(txn, |txn| Observable::update(txn, |value| *value *= 4));
+        assert_eq!(rx.next().now_or_never(), None);
+        "done"
+    });
+    assert_eq!(ret, "done");
+    assert_eq!(rx.next().await, Some(8));
+    assert_eq!(rx.next().now_or_never(), None);
+
+    Observable::transaction(&mut ob, |txn| Observable::set_eq(txn, 8));
+    assert_eq!(rx.next().now_or_never(), None);
+}
+
+#[tokio::test]
+async fn resubscribe() {
+    let mut ob = Observable::new(0);
+    let mut rx1 = Observable::subscribe(&ob);
+    let mut rx2 = rx1.resubscribe();
+
+    Observable::set(&mut ob, 1);
+    assert_eq!(rx1.next().await, Some(1));
+    assert_eq!(rx2.next().await, Some(1));
+}
+
+#[tokio::test]
+async fn clone_subscriber() {
+    let mut ob = Observable::new(0);
+    let mut rx1 = Observable::subscribe(&ob);
+
+    Observable::set(&mut ob, 1);
+    let mut rx2 = rx1.clone();
+    let mut rx3 = rx1.resubscribe();
+    assert_eq!(rx1.next().await, Some(1));
+    assert_eq!(rx2.next().await, Some(1));
+    assert_eq!(rx3.next().now_or_never(), None);
+
+    Observable::set(&mut ob, 2);
+    assert_eq!(rx1.next().await, Some(2));
+    assert_eq!(rx2.next().await, Some(2));
+
+    let mut rx = rx1.clone();
+    drop(ob);
+    assert_eq!(rx.next().await, None);
+}
+
+#[tokio::test]
+async fn versioned() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob).versioned();
+
+    Observable::set(&mut ob, 1);
+    assert_eq!(rx.next().await, Some((1, 1)));
+
+    Observable::set(&mut ob, 2);
+    Observable::set(&mut ob, 3);
+    assert_eq!(rx.next().await, Some((3, 3)));
+}
+
+#[tokio::test]
+async fn enumerate_lossy() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob).enumerate_lossy();
+
+    Observable::set(&mut ob, 1);
+    assert_eq!(rx.next().await, Some((0, 1)));
+
+    for i in 2..=5 {
+        Observable::set(&mut ob, i);
+    }
+    assert_eq!(rx.next().await, Some((3, 5)));
+
+    drop(ob);
+    assert_eq!(rx.next().await, None);
+}
+
+#[test]
+fn max_subscribers() {
+    let ob = Observable::with_max_subscribers(0, 2);
+    let rx1 = Observable::try_subscribe(&ob).unwrap();
+    let _rx2 = Observable::try_subscribe(&ob).unwrap();
+    assert_eq!(
+        Observable::try_subscribe(&ob).unwrap_err(),
+        SubscribeError::TooManySubscribers { max: 2 }
+    );
+
+    drop(rx1);
+    Observable::try_subscribe(&ob).unwrap();
+}
+
+#[tokio::test]
+async fn defer_broadcasts() {
+    let mut ob = Observable::defer_broadcasts(Observable::new(0));
+    let mut rx = Observable::subscribe(&ob);
+
+    for i in 1..=10 {
+        Observable::set(&mut ob, i);
+    }
+    assert_eq!(rx.next().now_or_never(), None);
+
+    Observable::flush(&mut ob);
+    assert_eq!(rx.next().await, Some(10));
+
+    Observable::flush(&mut ob);
+    assert_eq!(rx.next().now_or_never(), None);
+}
+
+#[tokio::test]
+async fn reconnecting() {
+    let mut obs = vec![Observable::new(0), Observable::new(0)];
+    let mut subscribers: Vec<_> = obs.iter().map(Observable::subscribe).collect();
+    let mut rx = Subscriber::reconnecting(move || subscribers.pop());
+
+    let mut ob1 = obs.pop().unwrap();
+    Observable::set(&mut ob1, 1);
+    assert_eq!(rx.next().await, Some(1));
+    drop(ob1);
+
+    let mut ob2 = obs.pop().unwrap();
+    Observable::set(&mut ob2, 2);
+    assert_eq!(rx.next().await, Some(2));
+    drop(ob2);
+
+    assert_eq!(rx.next().await, None);
+}
+
+#[tokio::test]
+async fn set_arc_if_ptr_differs() {
+    let value = Arc::new("hello".to_owned());
+    let mut ob = Observable::new(value.clone());
+    let mut rx = Observable::subscribe(&ob);
+
+    assert!(!Observable::set_arc_if_ptr_differs(&mut ob, value));
+    assert_eq!(rx.next().now_or_never(), None);
+
+    let value = Arc::new("hello".to_owned());
+    assert!(Observable::set_arc_if_ptr_differs(&mut ob, value.clone()));
+    assert!(Arc::ptr_eq(&rx.next().await.unwrap(), &value));
+}
+
+#[test]
+fn update_eq_logged() {
+    let mut ob = Observable::new(1);
+    let mut changes = Vec::new();
+
+    Observable::update_eq_logged(&mut ob, |value| *value = 1, |_, _| unreachable!());
+    Observable::update_eq_logged(
+        &mut ob,
+        |value| *value = 2,
+        |prev, new| changes.push((*prev, *new)),
+    );
+    assert_eq!(changes, [(1, 2)]);
+}
+
+#[tokio::test]
+async fn local_observable() {
+    let mut ob = LocalObservable::new(Rc::new(0));
+    let mut rx = LocalObservable::subscribe(&ob);
+
+    LocalObservable::set(&mut ob, Rc::new(1));
+    assert_eq!(rx.next().await, Some(Rc::new(1)));
+
+    LocalObservable::set(&mut ob, Rc::new(2));
+    LocalObservable::set(&mut ob, Rc::new(3));
+    assert_eq!(rx.next().await, Some(Rc::new(3)));
+    assert_eq!(rx.next().now_or_never(), None);
+
+    drop(ob);
+    assert_eq!(rx.next().await, None);
+}
+
+#[tokio::test]
+async fn update_observe_eq() {
+    #[derive(Clone, Debug, PartialEq)]
+    struct State {
+        value: u8,
+        cache: u8,
+    }
+
+    impl ObserveEq for State {
+        fn observe_eq(&self, other: &Self) -> bool {
+            self.value == other.value
+        }
+    }
+
+    let mut ob = Observable::new(State { value: 0, cache: 0 });
+    let mut rx = Observable::subscribe(&ob);
+
+    Observable::update_observe_eq(&mut ob, |state| state.cache = 1);
+    assert_eq!(rx.next().now_or_never(), None);
+
+    Observable::update_observe_eq(&mut ob, |state| state.value = 1);
+    assert_eq!(rx.next().await, Some(State { value: 1, cache: 1 }));
+}
+
+#[tokio::test]
+async fn deduped() {
+    let mut ob = Observable::new_deduped(0);
+    let mut rx = Observable::subscribe(&ob);
+
+    Observable::set(&mut ob, 0);
+    Observable::update(&mut ob, |value| *value = 0);
+    assert_eq!(rx.next().now_or_never(), None);
+
+    Observable::update(&mut ob, |value| *value = 1);
+    assert_eq!(rx.next().await, Some(1));
+    assert_eq!(Observable::replace(&mut ob, 1), 1);
+    assert_eq!(rx.next().now_or_never(), None);
+    assert!(Observable::update_if(&mut ob, |value| {
+        *value = 1;
+        true
+    }));
+    assert_eq!(rx.next().now_or_never(), None);
+    assert!(Observable::update_if(&mut ob, |value| {
+        *value = 2;
+        true
+    }));
+    assert_eq!(rx.next().await, Some(2));
+
+    let mut other = Observable::new(2);
+    let mut other_rx = Observable::subscribe(&other);
+    Observable::swap(&mut ob, &mut other);
+    assert_eq!(rx.next().now_or_never(), None);
+    // Only the deduped observable skips the notification
+    assert_eq!(other_rx.next().await, Some(2));
+
+    Observable::set(&mut other, 3);
+    assert_eq!(other_rx.next().await, Some(3));
+    Observable::swap(&mut ob, &mut other);
+    assert_eq!(rx.next().await, Some(3));
+    assert_eq!(other_rx.next().await, Some(2));
+}
+
+#[tokio::test]
+async fn drop_value() {
+    let ob = Observable::with_drop_value("connected", "disconnected");
+    let mut rx = Observable::subscribe(&ob);
+
+    drop(ob);
+    assert_eq!(rx.next().await, Some("disconnected"));
+    assert_eq!(rx.next().await, None);
+}
+
+#[test]
+fn update_eq_prev() {
+    let mut ob = Observable::new(1);
+    let mut rx = Observable::subscribe(&ob);
+
+    assert_eq!(Observable::update_eq_prev(&mut ob, |_| {}), None);
+    assert_eq!(rx.next().now_or_never(), None);
+
+    assert_eq!(Observable::update_eq_prev(&mut ob, |v| *v = 2), Some(1));
+    assert_eq!(rx.next().now_or_never(), Some(Some(2)));
+}
+
+#[test]
+fn update_with() {
+    use eyeball::{ChangeStrategy, NotifyOnEq};
+
+    struct ByLen;
+
+    impl ChangeStrategy<String> for ByLen {
+        type State = usize;
+
+        fn before_update(value: &String) -> usize {
+            value.len()
+        }
+
+        fn has_changed(prev: usize, value: &String) -> bool {
+            prev != value.len()
+        }
+    }
+
+    let mut ob = Observable::new("abc".to_owned());
+    let mut rx = Observable::subscribe(&ob);
+
+    Observable::update_with::<ByLen>(&mut ob, |s| s.replace_range(.., "xyz"));
+    assert_eq!(rx.next().now_or_never(), None);
+    Observable::update_with::<NotifyOnEq>(&mut ob, |_| {});
+    assert_eq!(rx.next().now_or_never(), None);
+
+    Observable::update_with::<ByLen>(&mut ob, |s| s.push('!'));
+    assert_eq!(rx.next().now_or_never(), Some(Some("xyz!".to_owned())));
+}
+
+#[test]
+fn backpressured() {
+    use eyeball::BackpressuredObservable;
+
+    let mut ob = BackpressuredObservable::new(0, 1);
+    let mut rx = BackpressuredObservable::subscribe(&ob);
+
+    assert_eq!(BackpressuredObservable::set_async(&mut ob, 1).now_or_never(), Some(()));
+    // The subscriber's buffer is full
+    assert_eq!(BackpressuredObservable::set_async(&mut ob, 2).now_or_never(), None);
+
+    assert_eq!(rx.next().now_or_never(), Some(Some(1)));
+    assert_eq!(BackpressuredObservable::set_async(&mut ob, 3).now_or_never(), Some(()));
+    assert_eq!(rx.next().now_or_never(), Some(Some(3)));
+
+    drop(ob);
+    assert_eq!(rx.next().now_or_never(), Some(None));
+}
+
+#[tokio::test]
+async fn coalesce() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob).coalesce(|_, _| unreachable!());
+
+    Observable::set(&mut ob, 1);
+    Observable::set(&mut ob, 2);
+    assert_eq!(rx.next().await, Some(2));
+
+    drop(ob);
+    assert_eq!(rx.next().await, None);
+}
+
+#[tokio::test]
+async fn on_update_subscribe() {
+    use std::sync::Mutex;
+
+    let mut ob = Observable::new(0);
+    let late_rx = Arc::new(Mutex::new(None));
+    Observable::on_update(&mut ob, {
+        let late_rx = late_rx.clone();
+        move |ob| {
+            late_rx.lock().unwrap().get_or_insert_with(|| Observable::subscribe(ob));
+        }
+    });
+
+    Observable::set(&mut ob, 1);
+    let mut rx = late_rx.lock().unwrap().take().unwrap();
+    assert_eq!(rx.next().now_or_never(), None);
+
+    Observable::set(&mut ob, 2);
+    assert_eq!(rx.next().await, Some(2));
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn test_util() {
+    use eyeball::test_util::{
+        assert_pending, collect_now, is_broadcast_pending, set_and_collect, subscriber_count,
+    };
+
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+    assert_eq!(subscriber_count(&ob), 1);
+    assert_pending(&mut rx);
+
+    assert_eq!(set_and_collect(&mut ob, 1, &mut rx), [1]);
+    assert_pending(&mut rx);
+
+    {
+        let mut guard = Observable::silence(&mut ob);
+        Observable::set(&mut guard, 2);
+        assert!(is_broadcast_pending(&guard));
+        assert_eq!(collect_now(&mut rx), []);
+    }
+    assert_eq!(collect_now(&mut rx), [2]);
+
+    drop(rx);
+    assert_eq!(subscriber_count(&ob), 0);
+}
+
+#[cfg(feature = "futures-sink")]
+#[tokio::test]
+async fn sink() {
+    use futures_util::{stream, SinkExt as _};
+
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+
+    Observable::sink(&mut ob).send(1).await.unwrap();
+    assert_eq!(rx.next().await, Some(1));
+
+    let mut sink = Observable::sink(&mut ob);
+    sink.send_all(&mut stream::iter([2, 3]).map(Ok)).await.unwrap();
+    assert_eq!(*ob, 3);
+    assert_eq!(rx.next().await, Some(3));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn deserialize() {
+    use serde::de::{value::Error, Deserialize, IntoDeserializer};
+
+    let deserializer = IntoDeserializer::<Error>::into_deserializer(5_u32);
+    let ob = Observable::<u32>::deserialize(deserializer).unwrap();
+    assert_eq!(*ob, 5);
+    assert_eq!(Observable::subscriber_count(&ob), 0);
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn with_name() {
+    use std::{fmt, sync::Mutex};
+
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Metadata,
+    };
+
+    #[derive(Default)]
+    struct Fields(Vec<String>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.push(format!("{field}={value:?}"));
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder(Arc<Mutex<Vec<Vec<String>>>>);
+
+    impl tracing::Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            self.0.lock().unwrap().push(fields.0);
+        }
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    let recorder = Recorder::default();
+    let events = recorder.0.clone();
+    tracing::subscriber::with_default(recorder, || {
+        let mut ob = Observable::log_values(Observable::with_name("counter", 0));
+        let _rx = Observable::subscribe(&ob);
+        Observable::set(&mut ob, 1);
+        Observable::update(&mut ob, |value| *value += 1);
+    });
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 2);
+    for (fields, (update, value)) in events.iter().zip([("set", 1), ("update", 2)]) {
+        assert!(fields.contains(&r#"name="counter""#.to_owned()));
+        assert!(fields.contains(&format!("update={update:?}")));
+        assert!(fields.contains(&format!("value={value}")));
+        assert!(fields.contains(&format!("version={value}")));
+    }
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn trace_timing() {
+    use std::{fmt, sync::Mutex};
+
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Metadata,
+    };
+
+    #[derive(Default)]
+    struct TimingFields(Vec<&'static str>);
+
+    impl Visit for TimingFields {
+        fn record_debug(&mut self, field: &Field, _value: &dyn fmt::Debug) {
+            if field.name().starts_with("since_") {
+                self.0.push(field.name());
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder(Arc<Mutex<Vec<&'static str>>>);
+
+    impl tracing::Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = TimingFields::default();
+            event.record(&mut fields);
+            self.0.lock().unwrap().extend(fields.0);
+        }
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    let recorder = Recorder::default();
+    let events = recorder.0.clone();
+    tracing::subscriber::with_default(recorder, || {
+        let mut ob = Observable::new(0);
+        let mut timed = Observable::subscribe(&ob).trace_timing();
+        let mut untimed = Observable::subscribe(&ob);
+        for i in 1..=3 {
+            Observable::set(&mut ob, i);
+            assert_eq!(timed.next_now(), Some(i));
+            assert_eq!(untimed.next_now(), Some(i));
+        }
+    });
+
+    // Only the subscriber that opted in emits timing events
+    assert_eq!(
+        *events.lock().unwrap(),
+        ["since_subscribed", "since_last_value", "since_last_value"]
+    );
+}
+
+#[cfg(feature = "registry")]
+#[test]
+fn registry() {
+    use eyeball::registry;
+
+    let find = |name| registry::snapshot().into_iter().find(|info| info.name == name);
+
+    let mut ob = Observable::with_name("registry_test", 0_u8);
+    let rx = Observable::subscribe(&ob);
+    Observable::set(&mut ob, 1);
+
+    let info = find("registry_test").unwrap();
+    assert_eq!(info.type_name, "u8");
+    assert_eq!(info.subscriber_count, 1);
+    assert_eq!(info.version, 1);
+
+    drop(rx);
+    assert_eq!(find("registry_test").unwrap().subscriber_count, 0);
+    drop(ob);
+    assert!(find("registry_test").is_none());
+}
+
+#[cfg(feature = "arc-swap")]
+#[tokio::test]
+async fn swap_observable() {
+    use eyeball::SwapObservable;
+
+    let ob = Arc::new(SwapObservable::new(vec![1]));
+    let mut rx = SwapObservable::subscribe(&ob);
+
+    let handles: Vec<_> = (2..=5)
+        .map(|i| {
+            let ob = ob.clone();
+            std::thread::spawn(move || {
+                SwapObservable::update(&ob, |value| {
+                    let mut value = value.clone();
+                    value.push(i);
+                    value
+                })
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let value = SwapObservable::read(&ob);
+    assert_eq!(value.len(), 5);
+    assert_eq!(rx.next().await.as_deref(), Some(&*value));
+
+    assert_eq!(*SwapObservable::replace(&ob, vec![]), *value);
+    assert_eq!(*SwapObservable::get(&ob), []);
+
+    // A panicking update doesn't block later ones
+    let result = std::panic::catch_unwind(|| SwapObservable::update(&ob, |_| panic!("boom")));
+    assert!(result.is_err());
+    assert_eq!(*SwapObservable::get(&ob), []);
+    SwapObservable::set(&ob, vec![6]);
+    assert_eq!(rx.next().await.as_deref(), Some(&vec![6]));
+}
+
+#[test]
+fn set_eq() {
+    let mut ob = Observable::new(false);
+    let mut rx = Observable::subscribe(&ob);
+
+    assert!(!Observable::set_eq(&mut ob, false));
+    assert_eq!(rx.next().now_or_never(), None);
+
+    assert!(Observable::set_eq(&mut ob, true));
+    assert_eq!(rx.next().now_or_never(), Some(Some(true)));
+}
+
+#[test]
+fn set_if_not_eq() {
+    let mut ob = Observable::new("a".to_owned());
+    let mut rx = Observable::subscribe(&ob);
+
+    assert_eq!(Observable::set_if_not_eq(&mut ob, "a".to_owned()), None);
+    assert_eq!(Observable::set_if_hash_not_eq(&mut ob, "a".to_owned()), None);
+    assert_eq!(rx.next().now_or_never(), None);
+
+    assert_eq!(Observable::set_if_not_eq(&mut ob, "b".to_owned()), Some("a".to_owned()));
+    assert_eq!(rx.next().now_or_never(), Some(Some("b".to_owned())));
+    assert_eq!(Observable::set_if_hash_not_eq(&mut ob, "c".to_owned()), Some("b".to_owned()));
+    assert_eq!(rx.next().now_or_never(), Some(Some("c".to_owned())));
+}
+
+#[test]
+fn gated_by() {
+    let mut data = Observable::new(0);
+    let mut enabled = Observable::new(false);
+    let mut rx = Observable::subscribe(&data).gated_by(Observable::subscribe(&enabled));
+
+    Observable::set(&mut data, 1);
+    assert_eq!(rx.next().now_or_never(), None);
+
+    Observable::set(&mut enabled, true);
+    assert_eq!(rx.next().now_or_never(), Some(Some(1)));
+    Observable::set(&mut data, 2);
+    assert_eq!(rx.next().now_or_never(), Some(Some(2)));
+
+    Observable::set(&mut enabled, false);
+    Observable::set(&mut data, 3);
+    assert_eq!(rx.next().now_or_never(), None);
+
+    let mut rx = Observable::subscribe(&data)
+        .gated_by(Observable::subscribe(&enabled))
+        .initially_enabled(true)
+        .buffer_while_disabled(false);
+    Observable::set(&mut data, 4);
+    assert_eq!(rx.next().now_or_never(), Some(Some(4)));
+
+    Observable::set(&mut enabled, false);
+    Observable::set(&mut data, 5);
+    assert_eq!(rx.next().now_or_never(), None);
+    Observable::set(&mut enabled, true);
+    assert_eq!(rx.next().now_or_never(), None);
+}
+
+#[test]
+fn replace_checked() {
+    let mut ob = Observable::new(1);
+    let mut rx = Observable::subscribe(&ob);
+
+    assert_eq!(Observable::replace_checked(&mut ob, 1), (1, false));
+    assert_eq!(rx.next().now_or_never(), None);
+
+    assert_eq!(Observable::replace_checked(&mut ob, 2), (1, true));
+    assert_eq!(rx.next().now_or_never(), Some(Some(2)));
+}
+
+#[cfg(feature = "tokio-time")]
+#[tokio::test]
+async fn next_timeout() {
+    use std::time::Duration;
+
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+    let duration = Duration::from_millis(10);
+
+    assert!(rx.next_timeout(duration).await.is_err());
+
+    Observable::set(&mut ob, 1);
+    assert_eq!(rx.next_timeout(duration).await, Ok(Some(1)));
+
+    drop(ob);
+    assert_eq!(rx.next_timeout(duration).await, Ok(None));
+}
+
+#[cfg(feature = "graph")]
+#[test]
+fn dump_graph() {
+    #[derive(Clone)]
+    struct GraphMarker;
+
+    let ob = Observable::new(GraphMarker);
+    let derived = Observable::subscribe_map(&ob, |_| ());
+
+    // Other tests may add nodes concurrently, so only look at this test's ones
+    let graph = eyeball::dump_graph();
+    let source_id = graph
+        .lines()
+        .find(|line| line.contains("GraphMarker"))
+        .and_then(|line| line.trim().split(' ').next())
+        .unwrap()
+        .to_owned();
+    assert!(graph.lines().any(|line| line.trim().starts_with(&format!("{source_id} -> "))));
+
+    drop(derived);
+    drop(ob);
+    assert!(!eyeball::dump_graph().contains("GraphMarker"));
+}
+
+#[tokio::test]
+async fn update_async() {
+    let mut ob = Observable::new(1);
+    let mut rx = Observable::subscribe(&ob);
+
+    Observable::update_async(&mut ob, |value| {
+        Box::pin(async move {
+            *value += 1;
+            tokio::task::yield_now().await;
+            *value *= 10;
+        })
+    })
+    .await;
+    assert_eq!(rx.next().now_or_never(), Some(Some(20)));
+}
+
+#[tokio::test]
+async fn subscribe_after_disconnect() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+
+    Observable::disconnect_subscribers(&mut ob);
+    assert_eq!(rx.next().await, None);
+    // Resubscribing to the closed channel doesn't hang
+    assert_eq!(rx.resubscribe().next().await, None);
+
+    let mut rx = Observable::subscribe(&ob);
+    Observable::set(&mut ob, 1);
+    assert_eq!(rx.next().await, Some(1));
+
+    // Same once the observable is dropped
+    drop(ob);
+    assert_eq!(rx.next().await, None);
+    assert_eq!(rx.resubscribe().next().await, None);
+}
+
+#[test]
+fn subscribe_waker() {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        task::{Wake, Waker},
+    };
+
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+    let mut ob = Observable::new(0);
+    Observable::subscribe_waker(&mut ob, Waker::from(counter.clone()));
+
+    Observable::set(&mut ob, 1);
+    Observable::set(&mut ob, 2);
+    assert_eq!(counter.0.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn as_pin_mut() {
+    use std::{future::Future, pin::pin, task::Poll};
+
+    let fut = pin!(Observable::new(std::future::ready(1)));
+    let waker = futures_util::task::noop_waker();
+    let mut cx = std::task::Context::from_waker(&waker);
+    assert_eq!(Observable::as_pin_mut(fut).poll(&mut cx), Poll::Ready(1));
+}
+
+#[test]
+fn collect_ready() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+    assert_eq!(rx.collect_ready(), []);
+
+    Observable::set(&mut ob, 1);
+    Observable::set(&mut ob, 2);
+    assert_eq!(rx.collect_ready(), [2]);
+    assert_eq!(rx.collect_ready(), []);
+}
+
+#[tokio::test]
+async fn variant_subscriber() {
+    #[derive(Clone, Debug)]
+    enum Connection {
+        Disconnected,
+        Connected(u32),
+    }
+
+    let mut ob = Observable::new(Connection::Disconnected);
+    let mut sessions =
+        eyeball::variant_subscriber!(Observable::subscribe(&ob), Connection::Connected);
+
+    Observable::set(&mut ob, Connection::Connected(1));
+    assert_eq!(sessions.next().await, Some(1));
+    Observable::set(&mut ob, Connection::Disconnected);
+    assert_eq!(sessions.next().now_or_never(), None);
+    Observable::set(&mut ob, Connection::Connected(2));
+    assert_eq!(sessions.next().await, Some(2));
+}
+
+#[test]
+fn swap() {
+    let mut front = Observable::new(1);
+    let mut back = Observable::new(2);
+    let mut front_rx = Observable::subscribe(&front);
+    let mut back_rx = Observable::subscribe(&back);
+
+    Observable::swap(&mut front, &mut back);
+    assert_eq!(front_rx.next().now_or_never(), Some(Some(2)));
+    assert_eq!(back_rx.next().now_or_never(), Some(Some(1)));
+
+    Observable::set(&mut back, 2);
+    back_rx.collect_ready();
+    assert!(!Observable::swap_eq(&mut front, &mut back));
+    assert_eq!(front_rx.next().now_or_never(), None);
+    assert_eq!(back_rx.next().now_or_never(), None);
+}
+
+#[tokio::test]
+async fn from_stream() {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut subscriber = Observable::from_stream(
+        0,
+        tokio_stream::wrappers::UnboundedReceiverStream::new(rx),
+        |fut| {
+            tokio::spawn(fut);
+        },
+    );
+
+    tx.send(1).unwrap();
+    assert_eq!(subscriber.next().await, Some(1));
+    tx.send(2).unwrap();
+    assert_eq!(subscriber.next().await, Some(2));
+
+    drop(tx);
+    assert_eq!(subscriber.next().await, None);
+}
+
+#[tokio::test]
+async fn maybe_observable() {
+    use eyeball::MaybeObservable;
+
+    let mut ob = MaybeObservable::new();
+    let mut rx = MaybeObservable::subscribe(&ob);
+    assert_eq!(MaybeObservable::get(&ob), None);
+    assert!(!MaybeObservable::update(&mut ob, |value: &mut i32| *value += 1));
+    assert_eq!(rx.next().now_or_never(), None);
+
+    MaybeObservable::set(&mut ob, 1);
+    assert_eq!(MaybeObservable::get(&ob), Some(&1));
+    assert_eq!(rx.next().await, Some(1));
+
+    assert!(MaybeObservable::update(&mut ob, |value| *value += 1));
+    assert_eq!(rx.next().await, Some(2));
+}
+
+#[test]
+fn push_str() {
+    let mut ob = Observable::new("hello".to_owned());
+    let mut rx = Observable::subscribe(&ob);
+    Observable::push_str(&mut ob, ", world");
+    assert_eq!(rx.next().now_or_never(), Some(Some("hello, world".to_owned())));
+
+    let mut ob: Observable<Arc<str>> = Observable::new("hello".into());
+    let mut rx = Observable::subscribe(&ob);
+    Observable::push_str_arc(&mut ob, ", world");
+    assert_eq!(rx.next().now_or_never(), Some(Some("hello, world".into())));
+}
+
+#[tokio::test]
+async fn on_edge() {
+    let mut ob: Observable<Result<u8, &str>> = Observable::new(Ok(0));
+    let mut rx = Observable::subscribe(&ob).on_edge(|value| value.is_err());
+
+    Observable::set(&mut ob, Ok(1));
+    assert_eq!(rx.next().now_or_never(), None);
+    Observable::set(&mut ob, Err("timeout"));
+    assert_eq!(rx.next().await, Some((true, Err("timeout"))));
+    Observable::set(&mut ob, Err("refused"));
+    assert_eq!(rx.next().now_or_never(), None);
+    Observable::set(&mut ob, Ok(2));
+    assert_eq!(rx.next().await, Some((false, Ok(2))));
+}
+
+#[tokio::test]
+async fn subscriber_from_iter() {
+    let mut rx: Subscriber<_> = [1, 2, 3].into_iter().collect();
+    assert_eq!(rx.next().await, Some(1));
+    assert_eq!(rx.next().await, Some(2));
+    assert_eq!(rx.next().await, Some(3));
+    assert_eq!(rx.next().await, None);
+
+    let mut rx = Subscriber::from_iter([1, 2, 3]).coalesce(|a, b| a + b);
+    assert_eq!(rx.next().await, Some(6));
+    assert_eq!(rx.next().await, None);
+}
+
+#[test]
+fn update_approx() {
+    let mut ob = Observable::new(20.0);
+    let mut rx = Observable::subscribe(&ob);
+
+    Observable::update_approx(&mut ob, |t| *t += 0.05, 0.1);
+    assert_eq!(rx.next().now_or_never(), None);
+
+    Observable::update_approx(&mut ob, |t| *t += 1.0, 0.1);
+    assert_eq!(rx.next().now_or_never(), Some(Some(21.05)));
+}
+
+#[test]
+fn update_by() {
+    // Neither `PartialEq` nor `Hash`
+    #[derive(Clone)]
+    struct Handler {
+        id: u32,
+        _callback: Arc<dyn Fn() + Send + Sync>,
+    }
+
+    let handler = |id| Handler { id, _callback: Arc::new(|| {}) };
+    let id_changed = |a: &Handler, b: &Handler| a.id != b.id;
+    let mut ob = Observable::new(handler(0));
+    let mut rx = Observable::subscribe(&ob);
+
+    Observable::update_by(&mut ob, |h| h._callback = Arc::new(|| println!()), id_changed);
+    assert!(!Observable::set_by(&mut ob, handler(0), id_changed));
+    assert!(rx.next().now_or_never().is_none());
+
+    Observable::update_by(&mut ob, |h| h.id = 1, id_changed);
+    assert_eq!(rx.next().now_or_never().unwrap().unwrap().id, 1);
+    assert!(Observable::set_by(&mut ob, handler(2), id_changed));
+    assert_eq!(rx.next().now_or_never().unwrap().unwrap().id, 2);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn change_count() {
+    let mut ob = Observable::new(0);
+    assert_eq!(Observable::change_count(&ob), 0);
+    assert_eq!(Observable::last_changed(&ob), None);
+
+    Observable::set(&mut ob, 1);
+    Observable::update_eq(&mut ob, |_| {});
+    assert_eq!(Observable::change_count(&ob), 1);
+    let first_change = Observable::last_changed(&ob).unwrap();
+
+    {
+        let mut guard = Observable::silence(&mut ob);
+        Observable::set(&mut guard, 2);
+        Observable::set(&mut guard, 3);
+    }
+    // Both changes are counted, but only broadcast once
+    assert_eq!(Observable::change_count(&ob), 3);
+    assert_eq!(Observable::version(&ob), 2);
+    assert!(Observable::last_changed(&ob).unwrap() >= first_change);
+}
+
+#[tokio::test]
+async fn subscribe_lazy() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe_lazy(&ob);
+
+    Observable::set(&mut ob, 1);
+    // Subscribes, but doesn't see the update from before
+    assert_eq!(rx.next().now_or_never(), None);
+
+    Observable::set(&mut ob, 2);
+    assert_eq!(rx.next().await, Some(2));
+
+    let mut rx = Observable::subscribe_lazy(&ob);
+    drop(ob);
+    assert_eq!(rx.next().await, None);
+}
+
+#[tokio::test]
+async fn derive_eq() {
+    let mut ob = Observable::new((1, "a"));
+    let mut rx = Observable::derive_eq(
+        &ob,
+        |(num, _)| *num,
+        |fut| {
+            tokio::spawn(fut);
+        },
+    );
+
+    Observable::set(&mut ob, (1, "b"));
+    Observable::set(&mut ob, (2, "b"));
+    assert_eq!(rx.next().await, Some(2));
+
+    drop(ob);
+    assert_eq!(rx.next().await, None);
+}
+
+#[tokio::test]
+async fn from_stream_with_handle() {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let stream = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
+    let (mut subscriber, handle) = Observable::from_stream_with_handle(0, stream, |fut| {
+        tokio::spawn(fut);
+    });
+
+    tx.send(1).unwrap();
+    assert_eq!(subscriber.next().await, Some(1));
+
+    handle.stop();
+    assert!(handle.is_stopped());
+    assert_eq!(subscriber.next().await, None);
+    // The stream was dropped along with the observable
+    assert!(tx.send(2).is_err());
+}
+
+#[tokio::test]
+async fn derive_eq2() {
+    let mut price = Observable::new(3);
+    let mut quantity = Observable::new(2);
+    let mut total = Observable::derive_eq2(
+        &price,
+        &quantity,
+        |price, quantity| price * quantity,
+        |fut| {
+            tokio::spawn(fut);
+        },
+    );
+
+    Observable::set(&mut quantity, 4);
+    assert_eq!(total.next().await, Some(12));
+    Observable::set(&mut price, 5);
+    assert_eq!(total.next().await, Some(20));
+
+    // Same total, no update
+    Observable::set(&mut price, 10);
+    Observable::set(&mut quantity, 2);
+    tokio::task::yield_now().await;
+    assert_eq!(total.next().now_or_never(), None);
+
+    drop(price);
+    Observable::set(&mut quantity, 3);
+    assert_eq!(total.next().await, Some(30));
+    drop(quantity);
+    assert_eq!(total.next().await, None);
+}
+
+#[cfg(feature = "tokio-time")]
+#[tokio::test(start_paused = true)]
+async fn min_interval() {
+    use std::time::Duration;
+
+    let mut ob = Observable::with_min_interval(0, Duration::from_millis(100));
+    let mut rx = Observable::subscribe(&ob);
+
+    Observable::set(&mut ob, 1);
+    assert_eq!(rx.next().now_or_never(), Some(Some(1)));
+
+    Observable::set(&mut ob, 2);
+    Observable::set(&mut ob, 3);
+    assert_eq!(rx.next().now_or_never(), None);
+    assert_eq!(rx.next().await, Some(3));
+    assert_eq!(Observable::version(&ob), 2);
+}
+
+#[cfg(feature = "tokio-time")]
+#[tokio::test(start_paused = true)]
+async fn min_interval_direct_notifications() {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    let mut ob = Observable::with_min_interval(0, Duration::from_millis(100));
+    let mut rx = Observable::subscribe(&ob);
+    let mut filtered = Observable::subscribe_filter_map(&mut ob, |value| Some(*value));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls2 = calls.clone();
+    Observable::on_update(&mut ob, move |_| {
+        calls2.fetch_add(1, Ordering::SeqCst);
+    });
+
+    Observable::set(&mut ob, 1);
+    Observable::set(&mut ob, 2);
+
+    // Callbacks and projections are not held back by the rate limit
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(filtered.collect_ready(), [2]);
+    assert_eq!(rx.next().now_or_never(), Some(Some(1)));
+    assert_eq!(rx.next().now_or_never(), None);
+    assert_eq!(rx.next().await, Some(2));
+}
+
+#[cfg(feature = "tokio-time")]
+#[tokio::test(start_paused = true)]
+async fn debounce_throttle() {
+    use std::time::Duration;
+
+    let mut ob = Observable::new(0);
+    let mut debounced = Observable::subscribe(&ob).debounce(Duration::from_millis(100));
+    let mut throttled = Observable::subscribe(&ob).throttle(Duration::from_millis(100));
+
+    Observable::set(&mut ob, 1);
+    assert_eq!(debounced.next().now_or_never(), None);
+    assert_eq!(throttled.next().now_or_never(), Some(Some(1)));
+
+    Observable::set(&mut ob, 2);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    Observable::set(&mut ob, 3);
+    assert_eq!(debounced.next().now_or_never(), None);
+    assert_eq!(throttled.next().now_or_never(), None);
+    assert_eq!(throttled.next().await, Some(3));
+    assert_eq!(debounced.next().await, Some(3));
+
+    Observable::set(&mut ob, 4);
+    drop(ob);
+    assert_eq!(debounced.next().await, Some(4));
+    assert_eq!(debounced.next().await, None);
+    assert_eq!(throttled.next().await, Some(4));
+    assert_eq!(throttled.next().await, None);
+}
+
+#[tokio::test]
+async fn windowed() {
+    let mut rx = Subscriber::from_iter([1, 2, 3, 4]).windowed(3);
+    assert_eq!(rx.next().await, Some(vec![1]));
+    assert_eq!(rx.next().await, Some(vec![1, 2]));
+    assert_eq!(rx.next().await, Some(vec![1, 2, 3]));
+    assert_eq!(rx.next().await, Some(vec![2, 3, 4]));
+    assert_eq!(rx.next().await, None);
+}
+
+#[tokio::test]
+async fn subscribe_lossless() {
+    use eyeball::SubscriberError;
+
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe_lossless(&ob);
+
+    Observable::set(&mut ob, 1);
+    assert_eq!(rx.next().await, Some(Ok(1)));
+
+    Observable::set(&mut ob, 2);
+    Observable::set(&mut ob, 3);
+    assert_eq!(rx.next().await, Some(Err(SubscriberError::Lagged { skipped: 1 })));
+    assert_eq!(rx.next().await, Some(Ok(3)));
+
+    drop(ob);
+    assert_eq!(rx.next().await, None);
+}
+
+#[tokio::test]
+async fn subscribe_buffered() {
+    use eyeball::SubscriberError;
+
+    let mut ob = Observable::with_capacity(0, 2);
+    let mut rx = Observable::subscribe_buffered(&ob);
+    let mut latest = Observable::subscribe(&ob);
+
+    Observable::set(&mut ob, 1);
+    Observable::set(&mut ob, 2);
+    assert_eq!(rx.next().await, Some(Ok(1)));
+    assert_eq!(rx.next().await, Some(Ok(2)));
+    assert_eq!(latest.next().await, Some(2));
+
+    for i in 3..=6 {
+        Observable::set(&mut ob, i);
+    }
+    assert_eq!(rx.next().await, Some(Err(SubscriberError::Lagged { skipped: 2 })));
+    assert_eq!(rx.next().await, Some(Ok(5)));
+    assert_eq!(rx.next().await, Some(Ok(6)));
+
+    drop(ob);
+    assert_eq!(rx.next().await, None);
+}
+
+#[test]
+fn lag_accounting() {
+    let mut ob = Observable::new(0);
+    let mut rx1 = Observable::subscribe(&ob).enumerate_lossy();
+    Observable::set(&mut ob, 1);
+    let mut rx2 = Observable::subscribe(&ob).enumerate_lossy();
+
+    // Skipped updates are counted from the point each subscriber was at
+    Observable::set(&mut ob, 2);
+    Observable::set(&mut ob, 3);
+    assert_eq!(rx1.next().now_or_never(), Some(Some((2, 3))));
+    assert_eq!(rx2.next().now_or_never(), Some(Some((1, 3))));
+    assert_eq!(rx1.next().now_or_never(), None);
+
+    // A clone continues from the position of the original
+    let mut rx3 = Observable::subscribe(&ob);
+    Observable::set(&mut ob, 4);
+    let mut rx4 = rx3.clone().enumerate_lossy();
+    assert_eq!(rx3.next_now(), Some(4));
+    Observable::set(&mut ob, 5);
+    assert_eq!(rx4.next().now_or_never(), Some(Some((1, 5))));
+    assert_eq!(rx3.next_versioned().now_or_never(), Some(Some((5, 5))));
+}
+
+#[tokio::test]
+async fn history_eviction() {
+    use eyeball::SubscriberError;
+
+    let mut ob = Observable::with_capacity(0, 3);
+    let mut partial = Observable::subscribe_buffered(&ob);
+    let mut behind = Observable::subscribe_buffered(&ob);
+
+    Observable::set(&mut ob, 1);
+    Observable::set(&mut ob, 2);
+    assert_eq!(partial.next().await, Some(Ok(1)));
+
+    // Only the last three updates are kept, so 2 is evicted for `partial`,
+    // and 1 and 2 for `behind`
+    for i in 3..=5 {
+        Observable::set(&mut ob, i);
+    }
+    assert_eq!(partial.next().await, Some(Err(SubscriberError::Lagged { skipped: 1 })));
+    assert_eq!(behind.next().await, Some(Err(SubscriberError::Lagged { skipped: 2 })));
+    for i in 3..=5 {
+        assert_eq!(partial.next().await, Some(Ok(i)));
+        assert_eq!(behind.next().await, Some(Ok(i)));
+    }
+
+    // Without subscribers, the history is discarded
+    drop(partial);
+    drop(behind);
+    Observable::set(&mut ob, 6);
+    let mut rx = Observable::subscribe_buffered(&ob);
+    Observable::set(&mut ob, 7);
+    assert_eq!(rx.next().await, Some(Ok(7)));
+    assert_eq!(rx.next().now_or_never(), None);
+}
+
+#[tokio::test]
+async fn close_delivers_latest() {
+    let mut ob = Observable::with_capacity(0, 2);
+    let mut buffered = Observable::subscribe_buffered(&ob);
+    let mut received = Observable::subscribe(&ob);
+    let mut not_received = Observable::subscribe(&ob);
+
+    Observable::set(&mut ob, 1);
+    Observable::set(&mut ob, 2);
+    assert_eq!(received.next().await, Some(2));
+
+    Observable::close(&mut ob);
+    assert_eq!(buffered.next().await, Some(Ok(1)));
+    assert_eq!(buffered.next().await, Some(Ok(2)));
+    assert_eq!(buffered.next().await, None);
+    assert_eq!(received.next().await, None);
+    assert_eq!(not_received.next().await, Some(2));
+    assert_eq!(not_received.next().await, None);
+
+    // Dropping the observable closes the channel in the same way
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+    Observable::set(&mut ob, 1);
+    drop(ob);
+    assert_eq!(rx.next().await, Some(1));
+    assert_eq!(rx.next().await, None);
+}
+
+#[tokio::test]
+async fn subscriber_drop_frees_slot() {
+    let mut ob = Observable::new(0);
+    let rx1 = Observable::subscribe(&ob);
+    let mut rx2 = Observable::subscribe(&ob);
+    drop(rx1);
+    assert_eq!(Observable::subscriber_count(&ob), 1);
+
+    // A subscriber in a reused slot only sees updates after it subscribed
+    Observable::set(&mut ob, 1);
+    let mut rx3 = Observable::subscribe(&ob);
+    assert_eq!(Observable::subscriber_count(&ob), 2);
+    assert_eq!(rx3.next_now(), None);
+    assert_eq!(rx2.next_now(), Some(1));
+
+    // Dropping a subscriber that hasn't received an update yet wakes up
+    // `set_and_wait`
+    let mut set = std::pin::pin!(Observable::set_and_wait(&mut ob, 2));
+    assert_eq!((&mut set).now_or_never(), None);
+    assert_eq!(rx2.next().await, Some(2));
+    assert_eq!((&mut set).now_or_never(), None);
+    let waiting = tokio::spawn(async move {
+        tokio::task::yield_now().await;
+        drop(rx3);
+    });
+    set.await;
+    waiting.await.unwrap();
+}
+
+#[test]
+fn lazy_subscriber_wakes_wait_for_subscriber() {
+    use std::{
+        future::Future,
+        sync::atomic::{AtomicUsize, Ordering},
+        task::{Context, Poll, Wake, Waker},
+    };
+
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let ob = Observable::new(0);
+    let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+    let waker = Waker::from(counter.clone());
+    let mut waiting = std::pin::pin!(Observable::wait_for_subscriber(&ob));
+    assert_eq!(waiting.as_mut().poll(&mut Context::from_waker(&waker)), Poll::Pending);
+
+    let mut lazy = Observable::subscribe_lazy(&ob);
+    assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+    assert_eq!(lazy.next_now(), None);
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    assert_eq!(waiting.poll(&mut Context::from_waker(&waker)), Poll::Ready(()));
+}
+
+#[test]
+fn debug() {
+    #[derive(Clone)]
+    struct NotDebug;
+
+    let ob = Observable::new(NotDebug);
+    let _rx = Observable::subscribe(&ob);
+    assert_eq!(format!("{ob:?}"), "Observable { subscribers: 1, version: 0, .. }");
+
+    let ob = Observable::new(1);
+    assert_eq!(
+        format!("{:?}", Observable::debug_value(&ob)),
+        "Observable { value: 1, subscribers: 0, version: 0, .. }"
+    );
+}
+
+#[tokio::test]
+async fn peekable() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob).peekable();
+
+    Observable::set(&mut ob, 1);
+    Observable::set(&mut ob, 2);
+    assert_eq!(rx.peek().await, Some(&2));
+    assert_eq!(rx.peek().await, Some(&2));
+    assert_eq!(rx.next().await, Some(2));
+
+    drop(ob);
+    assert_eq!(rx.peek().await, None);
+    assert_eq!(rx.next().await, None);
+}
+
+#[test]
+fn freeze() {
+    let mut ob = Observable::on_frozen_mutation(Observable::new(0), FrozenPolicy::Ignore);
+    let mut rx = Observable::subscribe(&ob);
+
+    Observable::set(&mut ob, 1);
+    Observable::freeze(&mut ob);
+    assert!(Observable::is_frozen(&ob));
+    assert_eq!(rx.next().now_or_never(), Some(Some(1)));
+
+    Observable::set(&mut ob, 2);
+    Observable::update(&mut ob, |value| *value += 1);
+    assert_eq!(Observable::replace(&mut ob, 3), 3);
+    assert!(!Observable::set_eq(&mut ob, 4));
+    // The result of `f` is returned, but the update is dropped
+    assert_eq!(
+        Observable::try_update(&mut ob, |value| Ok::<_, ()>(std::mem::replace(value, 5))),
+        Ok(1)
+    );
+    assert_eq!(*ob, 1);
+    assert_eq!(rx.next().now_or_never(), None);
+}
+
+#[test]
+#[should_panic = "frozen"]
+fn freeze_panic() {
+    let mut ob = Observable::on_frozen_mutation(Observable::new(0), FrozenPolicy::Panic);
+    Observable::freeze(&mut ob);
+    Observable::set(&mut ob, 1);
+}
+
+/// A value whose clone drops the subscriber stored in it.
+///
+/// `Observable` clones the value after checking for subscribers, right before
+/// sending it, so this makes sending fail deterministically.
+#[derive(Default)]
+struct DropSubscriberOnClone(Arc<std::sync::Mutex<Option<Subscriber<DropSubscriberOnClone>>>>);
+
+impl Clone for DropSubscriberOnClone {
+    fn clone(&self) -> Self {
+        self.0.lock().unwrap().take();
+        Self(self.0.clone())
+    }
+}
+
+fn failing_send(policy: SendErrorPolicy) -> Observable<DropSubscriberOnClone> {
+    let value = DropSubscriberOnClone::default();
+    let slot = value.0.clone();
+    let mut ob = Observable::on_send_error(Observable::new(value), policy);
+    *slot.lock().unwrap() = Some(Observable::subscribe(&ob));
+    Observable::set(&mut ob, DropSubscriberOnClone(slot));
+    ob
+}
+
+#[test]
+fn send_error_ignore_log() {
+    for policy in [SendErrorPolicy::Ignore, SendErrorPolicy::Log] {
+        let ob = failing_send(policy);
+        assert_eq!(Observable::subscriber_count(&ob), 0);
+        assert_eq!(Observable::version(&ob), 1);
+    }
+
+    // Not having any subscribers in the first place is not a failure
+    let mut ob = Observable::on_send_error(Observable::new(0), SendErrorPolicy::Panic);
+    Observable::set(&mut ob, 1);
+}
+
+#[test]
+#[should_panic = "failed to broadcast"]
+fn send_error_panic() {
+    failing_send(SendErrorPolicy::Panic);
+}
+
+#[test]
+fn freeze_panic_try_update() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let mut ob = Observable::on_frozen_mutation(Observable::new(0), FrozenPolicy::Panic);
+    Observable::freeze(&mut ob);
+
+    let mut called = false;
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        Observable::try_update(&mut ob, |_| {
+            called = true;
+            Ok::<_, ()>(())
+        })
+    }));
+    assert!(result.is_err());
+    assert!(!called);
+}
+
+#[cfg(feature = "im")]
+#[test]
+fn im_collections() {
+    let mut vector = Observable::new(im::Vector::new());
+    let mut rx = Observable::subscribe(&vector);
+
+    Observable::push_back(&mut vector, 1);
+    Observable::push_front(&mut vector, 0);
+    assert_eq!(rx.next().now_or_never(), Some(Some(im::vector![0, 1])));
+    assert_eq!(Observable::pop_back(&mut vector), Some(1));
+    assert_eq!(Observable::pop_back(&mut vector), Some(0));
+    assert_eq!(rx.next().now_or_never(), Some(Some(im::Vector::new())));
+    assert_eq!(Observable::pop_front(&mut vector), None);
+    assert_eq!(rx.next().now_or_never(), None);
+
+    let mut map = Observable::new(im::HashMap::new());
+    let mut rx = Observable::subscribe(&map);
+
+    assert_eq!(Observable::insert_entry(&mut map, "a", 1), None);
+    assert_eq!(rx.next().now_or_never(), Some(Some(im::hashmap! { "a" => 1 })));
+    assert_eq!(Observable::remove_entry(&mut map, "b"), None);
+    assert_eq!(rx.next().now_or_never(), None);
+    assert_eq!(Observable::remove_entry(&mut map, "a"), Some(1));
+    assert_eq!(rx.next().now_or_never(), Some(Some(im::HashMap::new())));
+}
+
+#[tokio::test]
+async fn merge() {
+    let sources = || [(1..=4).collect::<Subscriber<_>>(), [10, 20].into_iter().collect()];
+
+    let merged = Subscriber::merge(sources()).build();
+    assert_eq!(merged.collect::<Vec<_>>().await, [1, 2, 3, 4, 10, 20]);
+
+    let merged = Subscriber::merge(sources()).max_consecutive(2).build();
+    assert_eq!(merged.collect::<Vec<_>>().await, [1, 2, 10, 20, 3, 4]);
+}
+
+#[tokio::test]
+async fn zip_latest() {
+    let mut user = Observable::new("alice");
+    let mut theme = Observable::new("light");
+    let mut rx = Observable::subscribe_with_current(&user)
+        .zip_latest(Observable::subscribe_with_current(&theme));
+    assert_eq!(rx.next().await, Some(("alice", "light")));
+
+    Observable::set(&mut theme, "dark");
+    assert_eq!(rx.next().await, Some(("alice", "dark")));
+    Observable::set(&mut user, "bob");
+    assert_eq!(rx.next().await, Some(("bob", "dark")));
+    assert_eq!(rx.next().now_or_never(), None);
+
+    drop(user);
+    Observable::set(&mut theme, "light");
+    assert_eq!(rx.next().await, Some(("bob", "light")));
+    drop(theme);
+    assert_eq!(rx.next().await, None);
+
+    // Without initial values, nothing is yielded until all sources have one
+    let mut obs = [Observable::new(0), Observable::new(0)];
+    let mut rx = Subscriber::combine_latest(obs.iter().map(Observable::subscribe));
+    Observable::set(&mut obs[0], 1);
+    assert_eq!(rx.next().now_or_never(), None);
+    Observable::set(&mut obs[1], 2);
+    assert_eq!(rx.next().await, Some(vec![1, 2]));
+}
+
+#[tokio::test]
+async fn wait_until_no_subscribers() {
+    let ob = Observable::new(0);
+    Observable::wait_until_no_subscribers(&ob).await;
+
+    let rx1 = Observable::subscribe(&ob);
+    let rx2 = rx1.resubscribe();
+    let waiting = Observable::wait_until_no_subscribers(&ob);
+    tokio::pin!(waiting);
+    assert_eq!(waiting.as_mut().now_or_never(), None);
+
+    drop(rx1);
+    assert_eq!(waiting.as_mut().now_or_never(), None);
+    drop(rx2);
+    assert_eq!(waiting.now_or_never(), Some(()));
+}
+
+#[tokio::test]
+async fn wait_for_subscriber() {
+    let ob = Observable::new(0);
+    assert_eq!(Observable::subscriber_count(&ob), 0);
+
+    let waiting = Observable::wait_for_subscriber(&ob);
+    tokio::pin!(waiting);
+    assert_eq!(waiting.as_mut().now_or_never(), None);
+
+    let mut lazy = Observable::subscribe_lazy(&ob);
+    assert_eq!(waiting.as_mut().now_or_never(), None);
+    assert_eq!(lazy.next().now_or_never(), None);
+    assert_eq!(Observable::subscriber_count(&ob), 1);
+    assert_eq!(waiting.now_or_never(), Some(()));
+
+    let _rx = Observable::subscribe(&ob);
+    assert_eq!(Observable::subscriber_count(&ob), 2);
+    Observable::wait_for_subscriber(&ob).await;
+}
+
+#[tokio::test]
+async fn atomic_observable() {
+    let ob = Arc::new(AtomicObservable::new(0_u32));
+    let mut rx = AtomicObservable::subscribe(&ob);
+
+    let handles: Vec<_> = (1..=4)
+        .map(|i| {
+            let ob = ob.clone();
+            std::thread::spawn(move || AtomicObservable::set(&ob, i))
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let latest = AtomicObservable::get(&ob);
+    assert_ne!(latest, 0);
+    assert_eq!(rx.next().await, Some(latest));
+    assert_eq!(AtomicObservable::replace(&ob, 5), latest);
+    assert_eq!(rx.next().await, Some(5));
+}
+
+#[tokio::test]
+async fn atomic_fetch_ops() {
+    let ob = Arc::new(AtomicObservable::new(0_usize));
+    let mut rx = AtomicObservable::subscribe(&ob);
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let ob = ob.clone();
+            std::thread::spawn(move || {
+                for _ in 0..100 {
+                    AtomicObservable::fetch_add(&ob, 1);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(AtomicObservable::get(&ob), 400);
+    assert_eq!(rx.next().await, Some(400));
+    assert_eq!(AtomicObservable::fetch_sub(&ob, 1), 400);
+    assert_eq!(rx.next().await, Some(399));
+
+    let flag = AtomicObservable::new(false);
+    assert!(!AtomicObservable::update(&flag, |value| !value));
+    assert!(AtomicObservable::get(&flag));
+
+    // A panicking update doesn't block later ones
+    let result = std::panic::catch_unwind(|| AtomicObservable::update(&ob, |_| panic!("boom")));
+    assert!(result.is_err());
+    assert_eq!(AtomicObservable::fetch_add(&ob, 1), 399);
+    assert_eq!(rx.next().await, Some(400));
+}
+
+#[tokio::test]
+async fn arc_observable() {
+    // Not `Clone`
+    #[derive(Debug, PartialEq)]
+    struct Big(Vec<u8>);
+
+    let mut ob = ArcObservable::new(Big(vec![0]));
+    let mut rx = ArcObservable::subscribe(&ob);
+
+    ArcObservable::set(&mut ob, Big(vec![1, 2]));
+    let received = rx.next().await.unwrap();
+    assert!(Arc::ptr_eq(&received, &ArcObservable::get(&ob)));
+    assert_eq!(ob.0, [1, 2]);
+
+    let previous = ArcObservable::replace(&mut ob, Big(vec![3]));
+    assert!(Arc::ptr_eq(&previous, &received));
+    assert_eq!(*rx.next().await.unwrap(), Big(vec![3]));
+}
+
+#[test]
+fn arc_observable_update_clones() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Counted(Arc<AtomicUsize>);
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Self(self.0.clone())
+        }
+    }
+
+    let clones = Arc::new(AtomicUsize::new(0));
+    let mut ob = ArcObservable::new(Counted(clones.clone()));
+
+    // Without subscribers, the value is updated in place
+    ArcObservable::update(&mut ob, |_| {});
+    assert_eq!(clones.load(Ordering::Relaxed), 0);
+
+    let mut rx = ArcObservable::subscribe(&ob);
+    ArcObservable::update(&mut ob, |_| {});
+    assert_eq!(clones.load(Ordering::Relaxed), 0);
+
+    // The channel keeps a reference to the broadcast value, even after it was
+    // received, so the next update has to clone it
+    drop(rx.next_now());
+    assert_eq!(Arc::strong_count(&ArcObservable::get(&ob)), 3);
+    ArcObservable::update(&mut ob, |_| {});
+    assert_eq!(clones.load(Ordering::Relaxed), 1);
+
+    drop(rx);
+    ArcObservable::update(&mut ob, |_| {});
+    assert_eq!(clones.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn set_into() {
+    let mut ob = Observable::new(String::new());
+    let mut rx = Observable::subscribe(&ob);
+
+    Observable::set_into(&mut ob, "hi");
+    assert_eq!(rx.next().now_or_never(), Some(Some("hi".to_owned())));
+    assert_eq!(Observable::replace_into(&mut ob, 'x'), "hi");
+    assert_eq!(*ob, "x");
+}
+
+#[tokio::test]
+async fn into_change_pulses() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob).into_change_pulses();
+
+    Observable::set(&mut ob, 1);
+    assert_eq!(rx.next().await, Some(()));
+    drop(ob);
+    assert_eq!(rx.next().await, None);
+}
+
+#[test]
+fn on_update_without_subscribers() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let mut ob = Observable::new(0);
+    let num_calls = Arc::new(AtomicUsize::new(0));
+    Observable::on_update(&mut ob, {
+        let num_calls = num_calls.clone();
+        move |_| {
+            num_calls.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+
+    drop(Observable::subscribe(&ob));
+    Observable::set(&mut ob, 1);
+    Observable::update_eq(&mut ob, |value| *value = 1);
+    Observable::update_eq(&mut ob, |value| *value = 2);
+    assert_eq!(num_calls.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test]
+async fn subscribe_after() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe_after(&ob, 2);
+    let mut past_rx = Observable::subscribe_after(&ob, 0);
+
+    Observable::set(&mut ob, 1);
+    assert_eq!(rx.next().now_or_never(), None);
+    assert_eq!(past_rx.next().await, Some(1));
+
+    Observable::set(&mut ob, 2);
+    assert_eq!(rx.next().await, Some(2));
+}
+
+#[tokio::test]
+async fn subscribe_filter_map() {
+    let mut ob = Observable::new((0, "a"));
+    let mut rx = Observable::subscribe_filter_map(&mut ob, |&(n, _)| (n % 2 == 0).then_some(n));
+
+    Observable::set(&mut ob, (1, "b"));
+    assert_eq!(rx.next().now_or_never(), None);
+    Observable::set(&mut ob, (2, "c"));
+    assert_eq!(rx.next().await, Some(2));
+
+    drop(ob);
+    assert_eq!(rx.next().await, None);
+}
+
+#[tokio::test]
+async fn subscribe_field() {
+    let mut ob = Observable::new((0, "a"));
+    let mut rx = Observable::subscribe_field(&mut ob, |(_, s)| s);
+
+    Observable::set(&mut ob, (1, "a"));
+    assert_eq!(rx.next().now_or_never(), None);
+    Observable::set(&mut ob, (1, "b"));
+    assert_eq!(rx.next().await, Some("b"));
+    Observable::update(&mut ob, |(n, _)| *n += 1);
+    assert_eq!(rx.next().now_or_never(), None);
+
+    drop(ob);
+    assert_eq!(rx.next().await, None);
+}
+
+#[tokio::test]
+async fn weak_observable() {
+    let ob = SharedObservable::new(0);
+    let weak = SharedObservable::downgrade(&ob);
+    let mut rx = SharedObservable::subscribe(&ob);
+
+    SharedObservable::set(&weak.upgrade().unwrap(), 1);
+    assert_eq!(rx.next().await, Some(1));
+
+    drop(ob);
+    assert!(weak.upgrade().is_none());
+    assert_eq!(rx.next().await, None);
+}
+
+#[tokio::test]
+async fn notifier() {
+    use eyeball::Notifier;
+
+    let notifier = Notifier::new();
+    let mut rx = Notifier::subscribe(&notifier);
+    assert_eq!(rx.next().now_or_never(), None);
+
+    Notifier::notify(&notifier);
+    assert_eq!(rx.next().await, Some(1));
+    Notifier::notify(&notifier);
+    Notifier::notify(&notifier);
+    assert_eq!(rx.next().await, Some(2));
+
+    drop(notifier);
+    assert_eq!(rx.next().await, None);
+}
+
+#[tokio::test]
+async fn set_and_wait() {
+    let mut ob = Observable::new(0);
+    let mut rx1 = Observable::subscribe(&ob);
+    let mut rx2 = Observable::subscribe(&ob);
+
+    let handle = tokio::spawn(async move {
+        assert_eq!(rx1.next().await, Some(1));
+        rx1
+    });
+    let rx1 = {
+        let set = Observable::set_and_wait(&mut ob, 1);
+        tokio::pin!(set);
+        assert_eq!((&mut set).now_or_never(), None);
+
+        let rx1 = handle.await.unwrap();
+        assert_eq!((&mut set).now_or_never(), None);
+        assert_eq!(rx2.next().now_or_never(), Some(Some(1)));
+        assert_eq!(set.now_or_never(), Some(()));
+        rx1
+    };
+
+    // Dropped subscribers are not waited for
+    drop(rx1);
+    drop(rx2);
+    Observable::set_and_wait(&mut ob, 2).now_or_never().unwrap();
+}
+
+#[cfg(feature = "tokio-interop")]
+#[tokio::test]
+async fn watch_interop() {
+    let (tx, rx) = tokio::sync::watch::channel(0);
+    let mut sub = Observable::from_watch(rx, |fut| {
+        tokio::spawn(fut);
+    });
+    tx.send(1).unwrap();
+    assert_eq!(sub.next().await, Some(1));
+    drop(tx);
+    assert_eq!(sub.next().await, None);
+
+    let mut ob = Observable::new("a");
+    let mut rx = Observable::subscribe_watch(&ob, |fut| {
+        tokio::spawn(fut);
+    });
+    assert_eq!(*rx.borrow(), "a");
+    Observable::set(&mut ob, "b");
+    rx.changed().await.unwrap();
+    assert_eq!(*rx.borrow_and_update(), "b");
+    drop(ob);
+    assert!(rx.changed().await.is_err());
+}
+
+#[test]
+fn update_if() {
+    let mut ob = Observable::new(1);
+    let mut rx = Observable::subscribe(&ob);
+
+    assert!(!Observable::update_if(&mut ob, |n| *n > 1));
+    assert_eq!(rx.next().now_or_never(), None);
+    assert!(Observable::update_if(&mut ob, |n| {
+        *n += 1;
+        true
+    }));
+    assert_eq!(rx.next().now_or_never(), Some(Some(2)));
+}
+
+#[tokio::test]
+async fn inherent_next() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+    let mut with_current = Observable::subscribe_with_current(&ob);
+
+    Observable::set(&mut ob, 1);
+    assert_eq!(Subscriber::next(&mut rx).await, Some(1));
+    assert_eq!(Subscriber::next(&mut with_current).await, Some(0));
+    assert_eq!((&mut with_current).await, Some(1));
+
+    drop(ob);
+    assert_eq!((&mut rx).await, None);
+}
+
+#[cfg(feature = "async-lock")]
+#[tokio::test]
+async fn async_shared_observable() {
+    use eyeball::AsyncSharedObservable;
+
+    let mut ob = AsyncSharedObservable::new(0);
+    let mut rx = AsyncSharedObservable::subscribe(&ob).await;
+
+    let other = ob.clone();
+    AsyncSharedObservable::update(&other, |value| *value += 1).await;
+    assert_eq!(rx.next().await, Some(1));
+
+    assert_eq!(*AsyncSharedObservable::read(&mut ob).await, 1);
+    {
+        let mut guard = AsyncSharedObservable::write(&mut ob).await;
+        *guard += 1;
+        assert_eq!(rx.next().now_or_never(), None);
+    }
+    assert_eq!(rx.next().await, Some(2));
+    assert_eq!(AsyncSharedObservable::get(&other).await, 2);
+}
+
+#[test]
+fn instrumentation() {
+    use std::sync::Mutex;
+
+    use eyeball::Instrumentation;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Instrumentation for Recorder {
+        fn on_update(&self, name: Option<&'static str>, subscriber_count: usize) {
+            self.0.lock().unwrap().push(format!("update {name:?} {subscriber_count}"));
+        }
+
+        fn on_subscribe(&self, _name: Option<&'static str>, subscriber_count: usize) {
+            self.0.lock().unwrap().push(format!("subscribe {subscriber_count}"));
+        }
+
+        fn on_unsubscribe(&self, _name: Option<&'static str>, subscriber_count: usize) {
+            self.0.lock().unwrap().push(format!("unsubscribe {subscriber_count}"));
+        }
+
+        fn on_lag(&self, _name: Option<&'static str>, skipped: u64) {
+            self.0.lock().unwrap().push(format!("lag {skipped}"));
+        }
+    }
+
+    let recorder = Arc::new(Recorder::default());
+    let mut ob = Observable::with_instrumentation(
+        Observable::with_name("counter", 0),
+        recorder.clone() as Arc<dyn Instrumentation>,
+    );
+    Observable::set(&mut ob, 1);
+    let mut rx = Observable::subscribe(&ob);
+    Observable::set(&mut ob, 2);
+    Observable::set(&mut ob, 3);
+    assert_eq!(rx.next_now(), Some(3));
+    drop(rx);
+
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        [
+            "subscribe 1",
+            "update Some(\"counter\") 1",
+            "update Some(\"counter\") 1",
+            "lag 1",
+            "unsubscribe 0",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn any_observable() {
+    use std::collections::BTreeMap;
+
+    use eyeball::AnyObservable;
+
+    let mut settings = BTreeMap::new();
+    settings.insert("volume", AnyObservable::new(Observable::new(5_u8)));
+    settings.insert("theme", AnyObservable::new(Observable::new("dark".to_owned())));
+
+    let volume = settings.get_mut("volume").unwrap();
+    assert!(AnyObservable::downcast_subscribe::<String>(volume).is_none());
+    let mut rx = AnyObservable::downcast_subscribe::<u8>(volume).unwrap();
+    let mut dyn_rx = AnyObservable::subscribe(volume);
+
+    Observable::set(AnyObservable::downcast_mut::<u8>(volume).unwrap(), 7);
+    assert_eq!(rx.next().await, Some(7));
+    let value = dyn_rx.next().await.unwrap();
+    assert_eq!(value.downcast_ref::<u8>(), Some(&7));
+
+    let dyn_rx = dyn_rx.downcast::<String>().unwrap_err();
+    assert!(dyn_rx.downcast::<u8>().is_ok());
+}
+
+#[tokio::test]
+async fn shared_observable() {
+    use eyeball::SharedObservable;
+
+    let ob = SharedObservable::new(0);
+    let mut rx = SharedObservable::subscribe(&ob);
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let ob = ob.clone();
+            std::thread::spawn(move || SharedObservable::update(&ob, |value| *value += 1))
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(SharedObservable::get(&ob), 4);
+    assert_eq!(rx.next().await, Some(4));
+    assert_eq!(SharedObservable::replace(&ob, 5), 4);
+    assert_eq!(*SharedObservable::read(&ob), 5);
+    assert_eq!(rx.next().await, Some(5));
+
+    // A panicking update doesn't make the observable unusable for other clones
+    let result = std::panic::catch_unwind(|| SharedObservable::update(&ob, |_| panic!("boom")));
+    assert!(result.is_err());
+    SharedObservable::set(&ob, 6);
+    assert_eq!(rx.next().await, Some(6));
+}
+
+#[test]
+fn latest_value_semantics() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+    let mut rx_current = Observable::subscribe_with_current(&ob);
+
+    // Only `subscribe_with_current` yields the current value right away
+    assert_eq!(rx.next_now(), None);
+    assert_eq!(rx_current.next_now(), Some(0));
+    assert_eq!(rx_current.next_now(), None);
+
+    Observable::set(&mut ob, 1);
+    assert_eq!(rx.next_now(), Some(1));
+    assert_eq!(rx_current.next_now(), Some(1));
+
+    // Quick updates are coalesced into the latest one
+    Observable::set(&mut ob, 2);
+    Observable::set(&mut ob, 3);
+    Observable::set(&mut ob, 4);
+    assert_eq!(rx.next_now(), Some(4));
+    assert_eq!(rx.next_now(), None);
+    assert_eq!(rx_current.next_now(), Some(4));
+    assert_eq!(rx_current.next_now(), None);
+}
+
+#[tokio::test]
+async fn subscribe_with_current() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe_with_current(&ob);
+
+    Observable::set(&mut ob, 1);
+    assert_eq!(rx.next().await, Some(0));
+    assert_eq!(rx.next().await, Some(1));
+
+    drop(ob);
+    assert_eq!(rx.next().await, None);
+}
+
+#[test]
+fn subscriber_get_next_now() {
+    let mut ob = Observable::new(0);
+    let mut rx = Observable::subscribe(&ob);
+    assert_eq!(rx.get(), None);
+    assert_eq!(rx.next_now(), None);
+
+    Observable::set(&mut ob, 1);
+    Observable::set(&mut ob, 2);
+    assert_eq!(rx.get(), Some(2));
+    assert_eq!(rx.next_now(), Some(2));
+    assert_eq!(rx.next_now(), None);
+    assert_eq!(rx.get(), Some(2));
+
+    // Updates without subscribers to send them to invalidate the latest value
+    drop(rx);
+    Observable::set(&mut ob, 3);
+    assert_eq!(Observable::subscribe(&ob).get(), None);
+}
+
+#[test]
+fn subscriber_take_values() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug)]
+    struct Counted(Arc<AtomicUsize>);
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Self(self.0.clone())
+        }
+    }
+
+    let clones = Arc::new(AtomicUsize::new(0));
+    let mut ob = Observable::new(Counted(clones.clone()));
+
+    // The update is cloned into the channel, and again when it is received
+    let mut rx = Observable::subscribe(&ob);
+    Observable::set(&mut ob, Counted(clones.clone()));
+    assert!(rx.next_now().is_some());
+    assert_eq!(clones.swap(0, Ordering::Relaxed), 2);
+    drop(rx);
+
+    // The only subscriber takes the update out of the channel
+    let mut rx = Observable::subscribe(&ob).take_values();
+    Observable::set(&mut ob, Counted(clones.clone()));
+    assert!(rx.next_now().is_some());
+    assert_eq!(clones.swap(0, Ordering::Relaxed), 1);
+    assert!(rx.get().is_none());
+
+    // With another subscriber, both of them need their own clone
+    let mut rx2 = Observable::subscribe(&ob);
+    Observable::set(&mut ob, Counted(clones.clone()));
+    assert!(rx.next_now().is_some());
+    assert!(rx2.next_now().is_some());
+    assert_eq!(clones.swap(0, Ordering::Relaxed), 3);
+
+    drop(rx2);
+    Observable::set(&mut ob, Counted(clones.clone()));
+    assert!(rx.next_now().is_some());
+    assert_eq!(clones.swap(0, Ordering::Relaxed), 1);
+}
+
+#[tokio::test]
+async fn write() {
+    let mut ob = Observable::new(vec![1]);
+    let mut rx = Observable::subscribe(&ob);
+
+    {
+        let mut guard = Observable::write(&mut ob);
+        guard.push(2);
+        let last = guard.last_mut().unwrap();
+        *last *= 10;
+        assert_eq!(rx.next().now_or_never(), None);
+    }
+    assert_eq!(rx.next().await, Some(vec![1, 20]));
+
+    assert_eq!(Observable::write(&mut ob).len(), 2);
+    assert_eq!(rx.next().now_or_never(), None);
+
+    let mut ob = Observable::new_deduped(0);
+    let mut rx = Observable::subscribe(&ob);
+    *Observable::write(&mut ob) = 0;
+    assert_eq!(rx.next().now_or_never(), None);
+    *Observable::write(&mut ob) = 1;
+    assert_eq!(rx.next().await, Some(1));
+}
+
+#[test]
+fn read() {
+    let mut ob = Observable::new(vec![1]);
+    assert_eq!(*Observable::read(&ob), [1]);
+
+    let rx = Observable::subscribe_with_current(&ob);
+    assert_eq!(*rx.read().unwrap(), [1]);
+
+    Observable::update(&mut ob, |vec| vec.push(2));
+    let guard = rx.read().unwrap();
+    assert_eq!(guard.len(), 2);
+    assert_eq!(format!("{guard:?}"), "[1, 2]");
+}
+
+#[tokio::test]
+async fn dedup() {
+    let values = || [(1, 'a'), (1, 'a'), (1, 'b'), (2, 'b')].into_iter();
+
+    let rx = values().collect::<Subscriber<_>>().dedup();
+    assert_eq!(rx.collect::<Vec<_>>().await, [(1, 'a'), (1, 'b'), (2, 'b')]);
+
+    let rx = values().collect::<Subscriber<_>>().dedup_by_key(|&(n, _)| n);
+    assert_eq!(rx.collect::<Vec<_>>().await, [(1, 'a'), (2, 'b')]);
+}
+
+#[tokio::test]
+async fn map_filter() {
+    let mut ob = Observable::new((0, 'a'));
+    let mut chars = Observable::subscribe(&ob).map(|(_, c)| c);
+    let mut even = Observable::subscribe(&ob).filter(|(n, _)| n % 2 == 0);
+
+    Observable::set(&mut ob, (1, 'b'));
+    Observable::set(&mut ob, (2, 'b'));
+    assert_eq!(chars.next().await, Some('b'));
+    assert_eq!(even.next().await, Some((2, 'b')));
+
+    Observable::set(&mut ob, (3, 'b'));
+    assert_eq!(chars.next().now_or_never(), None);
+    assert_eq!(even.next().now_or_never(), None);
+
+    Observable::set(&mut ob, (4, 'c'));
+    drop(ob);
+    assert_eq!(chars.next().await, Some('c'));
+    assert_eq!(chars.next().await, None);
+    assert_eq!(even.next().await, Some((4, 'c')));
+}